@@ -0,0 +1,262 @@
+use clap::{ArgGroup, Parser};
+use rustmsg::swift::bic_directory::FileBicDirectory;
+use rustmsg::swift::mt::{join_rje, FinSimulator, MessageReader, Validator};
+use std::fs;
+use std::io;
+use std::io::Write;
+use std::net::{TcpListener, TcpStream};
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+use std::sync::mpsc::{channel, Receiver, Sender};
+use std::thread;
+use std::time::Duration;
+
+/// Simulates the FIN interface for integration tests. Messages sent over TCP or dropped into
+/// a directory are validated, numbered and answered by ACKs or NAKs, and accepted messages are
+/// written as output messages for their receivers.
+///
+/// Each TCP connection is a session. Clients end every message with `$` as in RJE batches, and
+/// the ACK or NAK of the message is written back on the connection followed by `$` as soon as
+/// the `$` arrives. Messages sent without `$` in between are answered as one invalid message.
+/// Each dropped `*.fin` file is a session as well: the acknowledgements are written to
+/// `<name>.ack` and the file is renamed to `<name>.fin.done`. Files that cannot be read, e.g.
+/// because they are not UTF-8 or hold a message above 64 KiB, are renamed to
+/// `<name>.fin.rejected` without acknowledgements. Files modified within the last poll
+/// interval are left for a later poll, as they may still be being written; write a file under
+/// another name and rename it to `*.fin` once it is complete. Output messages are written to
+/// `<receiver>_<session><sequence>.fin`.
+#[derive(Parser)]
+#[command(name = "fin-simulator", version)]
+#[command(group(ArgGroup::new("input").required(true).multiple(true).args(["listen", "drop_dir"])))]
+struct Cli {
+    /// Address to accept connections on, e.g. 127.0.0.1:5000
+    #[arg(long)]
+    listen: Option<String>,
+    /// Directory polled for message files
+    #[arg(long)]
+    drop_dir: Option<PathBuf>,
+    /// Directory for the acknowledgements of dropped files, defaults to the drop directory
+    #[arg(long)]
+    ack_dir: Option<PathBuf>,
+    /// Directory the output messages for the receivers are written to
+    #[arg(long, short, default_value = "output")]
+    output_dir: PathBuf,
+    /// BIC directory file checked for unknown, expired and not connected BICs
+    #[arg(long)]
+    bic_directory: Option<PathBuf>,
+    /// Milliseconds between polls of the drop directory
+    #[arg(long, default_value_t = 1000)]
+    poll_interval: u64,
+}
+
+/// Requests to the thread owning the simulator
+enum Request {
+    OpenSession(Sender<u32>),
+    Submit {
+        session: u32,
+        raw: String,
+        acknowledgement: Sender<String>,
+    },
+}
+
+fn main() -> ExitCode {
+    return match run(Cli::parse()) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("fin-simulator: {}", e);
+            ExitCode::from(2)
+        }
+    };
+}
+
+fn run(cli: Cli) -> Result<(), io::Error> {
+    let bic_directory = cli
+        .bic_directory
+        .as_deref()
+        .map(FileBicDirectory::load)
+        .transpose()?;
+    fs::create_dir_all(&cli.output_dir)?;
+
+    let (requests, receiver) = channel();
+    let output_dir = cli.output_dir.clone();
+    thread::spawn(move || simulate(receiver, bic_directory, &output_dir));
+
+    let mut watcher = None;
+    if let Some(drop_dir) = cli.drop_dir {
+        let ack_dir = cli.ack_dir.unwrap_or_else(|| drop_dir.clone());
+        let interval = Duration::from_millis(cli.poll_interval);
+        let requests = requests.clone();
+        watcher = Some(thread::spawn(move || {
+            // the TCP input would keep running without anyone noticing the dead watcher
+            if let Err(e) = watch(&drop_dir, &ack_dir, interval, &requests) {
+                eprintln!("fin-simulator: drop directory {}: {}", drop_dir.display(), e);
+                std::process::exit(2);
+            }
+        }));
+    }
+
+    if let Some(address) = cli.listen {
+        let listener = TcpListener::bind(&address)?;
+        eprintln!("Listening on {}", listener.local_addr()?);
+        for stream in listener.incoming() {
+            let stream = stream?;
+            let requests = requests.clone();
+            thread::spawn(move || {
+                let peer = stream
+                    .peer_addr()
+                    .map(|a| a.to_string())
+                    .unwrap_or_default();
+                if let Err(e) = serve(stream, &requests) {
+                    eprintln!("Connection {}: {}", peer, e);
+                }
+            });
+        }
+    }
+
+    return match watcher.map(|w| w.join()) {
+        Some(Err(_)) => Err(io::Error::other("Drop directory watcher failed")),
+        _ => Ok(()),
+    };
+}
+
+/// Owns the simulator, which is not shared between threads, and writes the output messages
+fn simulate(requests: Receiver<Request>, bic_directory: Option<FileBicDirectory>, output: &Path) {
+    let mut validator = Validator::new();
+    if let Some(directory) = bic_directory {
+        validator = validator.with_bic_directory(Box::new(directory));
+    }
+    let mut simulator = FinSimulator::new().with_validator(validator);
+
+    for request in requests {
+        match request {
+            Request::OpenSession(session) => {
+                let _ = session.send(simulator.open_session());
+            }
+            Request::Submit {
+                session,
+                raw,
+                acknowledgement,
+            } => {
+                let submission = simulator.submit(session, &raw);
+                if let Some(message) = submission.output {
+                    let header = &message.basic_header;
+                    let path = output.join(format!(
+                        "{}_{:04}{:06}.fin",
+                        header.logical_terminal, header.session_number, header.sequence_number
+                    ));
+                    if let Err(e) = fs::write(&path, message.to_raw()) {
+                        eprintln!("Cannot write {}: {}", path.display(), e);
+                    }
+                }
+                let _ = acknowledgement.send(submission.acknowledgement.to_raw());
+            }
+        }
+    }
+}
+
+fn open_session(requests: &Sender<Request>) -> Result<u32, io::Error> {
+    let (reply, session) = channel();
+    requests
+        .send(Request::OpenSession(reply))
+        .map_err(io::Error::other)?;
+    return session.recv().map_err(io::Error::other);
+}
+
+fn submit(requests: &Sender<Request>, session: u32, raw: String) -> Result<String, io::Error> {
+    let (reply, acknowledgement) = channel();
+    requests
+        .send(Request::Submit {
+            session,
+            raw,
+            acknowledgement: reply,
+        })
+        .map_err(io::Error::other)?;
+    return acknowledgement.recv().map_err(io::Error::other);
+}
+
+/// Acknowledges the messages of a connection until the client closes it
+fn serve(stream: TcpStream, requests: &Sender<Request>) -> Result<(), io::Error> {
+    let session = open_session(requests)?;
+    let mut writer = stream.try_clone()?;
+    let mut reader = MessageReader::new(stream).with_separator_required();
+
+    while let Some(raw) = reader.next_raw()? {
+        let acknowledgement = submit(requests, session, raw)?;
+        writer.write_all(acknowledgement.as_bytes())?;
+        writer.write_all(b"\r\n$")?;
+        writer.flush()?;
+    }
+
+    return Ok(());
+}
+
+/// Processes the `*.fin` files of the drop directory in the order of their names
+fn watch(
+    drop_dir: &Path,
+    ack_dir: &Path,
+    interval: Duration,
+    requests: &Sender<Request>,
+) -> Result<(), io::Error> {
+    fs::create_dir_all(ack_dir)?;
+    loop {
+        let mut files: Vec<PathBuf> = fs::read_dir(drop_dir)?
+            .filter_map(|entry| entry.ok().map(|e| e.path()))
+            .filter(|path| {
+                path.is_file()
+                    && path.extension().is_some_and(|e| e == "fin")
+                    && is_settled(path, interval)
+            })
+            .collect();
+        files.sort();
+
+        for file in files {
+            let messages = match read_drop_file(&file) {
+                Ok(messages) => messages,
+                Err(e) => {
+                    eprintln!("Rejecting {}: {}", file.display(), e);
+                    fs::rename(&file, file.with_extension("fin.rejected"))?;
+                    continue;
+                }
+            };
+
+            let session = open_session(requests)?;
+            let mut acknowledgements = Vec::new();
+            for raw in messages {
+                acknowledgements.push(submit(requests, session, raw)?);
+            }
+
+            let stem = file.file_stem().unwrap_or_default().to_string_lossy();
+            fs::write(
+                ack_dir.join(format!("{}.ack", stem)),
+                join_rje(&acknowledgements),
+            )?;
+            fs::rename(&file, file.with_extension("fin.done"))?;
+        }
+
+        thread::sleep(interval);
+    }
+}
+
+/// Whether the file was last modified at least the given time ago. A modification time in
+/// the future counts as recent.
+fn is_settled(file: &Path, age: Duration) -> bool {
+    return fs::metadata(file)
+        .and_then(|m| m.modified())
+        .is_ok_and(|modified| modified.elapsed().is_ok_and(|elapsed| elapsed >= age));
+}
+
+/// The messages of a dropped file, read completely before any of them is submitted
+fn read_drop_file(file: &Path) -> Result<Vec<String>, io::Error> {
+    let mut content = fs::read_to_string(file)?;
+    if !content.contains("\r\n") {
+        content = content.replace('\n', "\r\n");
+    }
+
+    let mut messages = Vec::new();
+    let mut reader = MessageReader::new(content.as_bytes());
+    while let Some(raw) = reader.next_raw()? {
+        messages.push(raw);
+    }
+
+    return Ok(messages);
+}
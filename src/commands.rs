@@ -0,0 +1,371 @@
+use rustmsg::swift::bic_directory::FileBicDirectory;
+use rustmsg::swift::mt::model::ApplicationHeader;
+use rustmsg::swift::mt::{
+    diff as diff_messages, join_rje, split_rje, Anonymiser, FieldDefinitions, MessageQuery,
+    MessageStore, StoredMessage, SwiftMtMessage, SwiftMtParser, Validator,
+};
+use serde_json::{json, Value};
+use std::fs;
+use std::io;
+use std::io::ErrorKind::InvalidData;
+use std::path::Path;
+use std::process::ExitCode;
+
+/// Code of the errors printed by `validate` for messages that cannot be parsed
+static PARSE_ERROR: &str = "PARSE";
+
+pub fn parse(file: &Path) -> Result<ExitCode, io::Error> {
+    let messages = parse_messages(file)?;
+    let json = match messages.as_slice() {
+        [message] => serde_json::to_string_pretty(message)?,
+        _ => serde_json::to_string_pretty(&messages)?,
+    };
+
+    println!("{}", json);
+    return Ok(ExitCode::SUCCESS);
+}
+
+pub fn validate(
+    files: &[impl AsRef<Path>],
+    bic_directory: Option<&Path>,
+    reject_test_bics: bool,
+) -> Result<ExitCode, io::Error> {
+    let mut validator = Validator::new();
+    if let Some(path) = bic_directory {
+        validator = validator.with_bic_directory(Box::new(FileBicDirectory::load(path)?));
+    }
+    if reject_test_bics {
+        validator = validator.with_test_bics_rejected();
+    }
+
+    let mut valid = true;
+    for file in files {
+        for error in validation_errors(file.as_ref(), &validator)? {
+            valid = false;
+            println!("{}", error);
+        }
+    }
+
+    return Ok(if valid {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::FAILURE
+    });
+}
+
+/// The JSON lines printed by `validate` for the messages of a file
+fn validation_errors(file: &Path, validator: &Validator) -> Result<Vec<Value>, io::Error> {
+    let mut lines = Vec::new();
+    for (index, raw) in read_messages(file)?.into_iter().enumerate() {
+        let errors = match SwiftMtParser::new().parse(raw) {
+            Ok(message) => validator
+                .validate(&message)
+                .into_iter()
+                .map(|e| (e.code, e.field, e.message))
+                .collect(),
+            Err(e) => vec![(PARSE_ERROR.to_string(), None, e.message)],
+        };
+
+        for (code, field, message) in errors {
+            lines.push(json!({
+                "file": file.display().to_string(),
+                "message": index + 1,
+                "code": code,
+                "field": field,
+                "text": message,
+            }));
+        }
+    }
+
+    return Ok(lines);
+}
+
+pub fn pretty(file: &Path, definitions_file: Option<&Path>) -> Result<ExitCode, io::Error> {
+    let definitions = load_definitions(definitions_file)?;
+    for (index, message) in parse_messages(file)?.iter().enumerate() {
+        if index > 0 {
+            println!();
+        }
+        print!("{}", format_pretty(message, &definitions));
+    }
+
+    return Ok(ExitCode::SUCCESS);
+}
+
+pub fn diff(
+    left: &Path,
+    right: &Path,
+    definitions_file: Option<&Path>,
+    json: bool,
+) -> Result<ExitCode, io::Error> {
+    let definitions = load_definitions(definitions_file)?;
+    let differences = diff_messages(&parse_message(left)?, &parse_message(right)?, &definitions);
+
+    for difference in &differences {
+        if json {
+            println!("{}", serde_json::to_string(difference)?);
+        } else {
+            println!("{}", difference);
+        }
+    }
+
+    return Ok(if differences.is_empty() {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::FAILURE
+    });
+}
+
+pub fn anonymise(
+    file: &Path,
+    secret: &str,
+    replace_bics: bool,
+    replace_amounts: bool,
+    preserve_structure: bool,
+) -> Result<ExitCode, io::Error> {
+    let mut anonymiser = Anonymiser::new().with_secret(secret);
+    if replace_bics {
+        anonymiser = anonymiser.with_bics_replaced();
+    }
+    if replace_amounts {
+        anonymiser = anonymiser.with_amounts_replaced();
+    }
+    if preserve_structure {
+        anonymiser = anonymiser.with_structure_preserved();
+    }
+
+    let messages: Vec<String> = parse_messages(file)?
+        .iter()
+        .map(|message| anonymiser.anonymise(message).to_raw())
+        .collect();
+    print!("{}", join_rje(&messages));
+    return Ok(ExitCode::SUCCESS);
+}
+
+pub fn split(file: &Path, output_dir: &Path) -> Result<ExitCode, io::Error> {
+    let stem = file
+        .file_stem()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_else(|| "message".to_string());
+
+    fs::create_dir_all(output_dir)?;
+    for (index, message) in read_messages(file)?.iter().enumerate() {
+        let path = output_dir.join(format!("{}_{:04}.fin", stem, index + 1));
+        fs::write(&path, message)?;
+        println!("{}", path.display());
+    }
+
+    return Ok(ExitCode::SUCCESS);
+}
+
+pub fn store_add(store: &Path, files: &[impl AsRef<Path>]) -> Result<ExitCode, io::Error> {
+    let mut store = MessageStore::open(store)?;
+    for file in files {
+        for message in parse_messages(file.as_ref())? {
+            let record = store.add(&message)?;
+            println!("{}", serde_json::to_string(record)?);
+        }
+    }
+
+    return Ok(ExitCode::SUCCESS);
+}
+
+/// Prints the matching messages, exiting with status 1 if there are none
+pub fn store_query(
+    store: &Path,
+    query: &MessageQuery,
+    chain: bool,
+    raw: bool,
+) -> Result<ExitCode, io::Error> {
+    let store = MessageStore::open(store)?;
+    let mut records: Vec<&StoredMessage> = store.query(query);
+    if chain {
+        records = records.iter().flat_map(|r| store.chain(r.id)).collect();
+        records.sort_by_key(|r| r.id);
+        records.dedup_by_key(|r| r.id);
+    }
+
+    if raw {
+        let messages = records
+            .iter()
+            .map(|r| store.raw(r))
+            .collect::<Result<Vec<_>, _>>()?;
+        print!("{}", join_rje(&messages));
+    } else {
+        for record in &records {
+            println!("{}", serde_json::to_string(record)?);
+        }
+    }
+
+    return Ok(if records.is_empty() {
+        ExitCode::FAILURE
+    } else {
+        ExitCode::SUCCESS
+    });
+}
+
+/// Reads the messages of a single message file or RJE batch. Files with Unix line endings are
+/// converted to the CrLf line endings of FIN.
+fn read_messages(file: &Path) -> Result<Vec<String>, io::Error> {
+    let mut content = fs::read_to_string(file)?;
+    if !content.contains("\r\n") {
+        content = content.replace('\n', "\r\n");
+    }
+
+    return Ok(split_rje(&content)
+        .into_iter()
+        .map(|m| m.to_string())
+        .collect());
+}
+
+fn parse_messages(file: &Path) -> Result<Vec<SwiftMtMessage>, io::Error> {
+    return read_messages(file)?
+        .into_iter()
+        .enumerate()
+        .map(|(index, raw)| {
+            SwiftMtParser::new().parse(raw).map_err(|e| {
+                io::Error::new(
+                    InvalidData,
+                    format!("{} message {}: {}", file.display(), index + 1, e.message),
+                )
+            })
+        })
+        .collect();
+}
+
+fn parse_message(file: &Path) -> Result<SwiftMtMessage, io::Error> {
+    let mut messages = parse_messages(file)?;
+    if messages.len() != 1 {
+        return Err(io::Error::new(
+            InvalidData,
+            format!(
+                "{} must contain one message but has {}",
+                file.display(),
+                messages.len()
+            ),
+        ));
+    }
+
+    return Ok(messages.remove(0));
+}
+
+/// The built-in field names, extended by the catalogue file if given
+fn load_definitions(file: Option<&Path>) -> Result<FieldDefinitions, io::Error> {
+    let mut definitions = FieldDefinitions::builtin();
+    if let Some(path) = file {
+        definitions.extend(FieldDefinitions::from_catalogue(&fs::read_to_string(
+            path,
+        )?)?);
+    }
+
+    return Ok(definitions);
+}
+
+fn format_pretty(message: &SwiftMtMessage, definitions: &FieldDefinitions) -> String {
+    let message_type = message.application_header.message_type().unwrap_or("");
+    let (sender, receiver) = match &message.application_header {
+        ApplicationHeader::Input { data } => (
+            message.basic_header.logical_terminal.to_string(),
+            data.destination.to_string(),
+        ),
+        ApplicationHeader::Output { data } => (
+            data.sender_address.to_string(),
+            message.basic_header.logical_terminal.to_string(),
+        ),
+        ApplicationHeader::Empty => (
+            message.basic_header.logical_terminal.to_string(),
+            String::new(),
+        ),
+    };
+
+    let mut out = format!("MT{} {} -> {}\n", message_type, sender, receiver);
+    out.push_str(&format!("{}\n", message.basic_header.to_raw()));
+    out.push_str(&format!("{}\n", message.application_header.to_raw()));
+    if !message.user_header.is_empty() {
+        out.push_str(&format!("{}\n", message.user_header.to_raw()));
+    }
+
+    for field in &message.text.fields {
+        let tag = format!(":{}:", field.tag);
+        let name = definitions.name(message_type, &field.tag).unwrap_or("");
+        out.push_str(&format!("{:<6}{}\n", tag, name));
+        for line in field.value.split("\r\n") {
+            out.push_str(&format!("{:<6}{}\n", "", line));
+        }
+    }
+
+    if !message.trailer.is_empty() {
+        out.push_str(&format!("{}\n", message.trailer.to_raw()));
+    }
+    if !message.system_trailer.is_empty() {
+        out.push_str(&format!("{}\n", message.system_trailer.to_raw()));
+    }
+
+    return out;
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::commands::{split, validate, validation_errors, PARSE_ERROR};
+    use rustmsg::swift::mt::Validator;
+    use std::env;
+    use std::fs;
+    use std::path::PathBuf;
+    use std::process::ExitCode;
+
+    static VALID: &str = "{1:F01FOOBARXXAXXX0000000000}{2:I103FOOBARXXAXXXN}{4:\n:20:REF\n:32A:230102EUR1000,\n:71A:SHA\n-}";
+    static INVALID: &str = "{1:F01FOOBARXXAXXX0000000000}{2:I103FOOBARXXAXXXN}{4:\n:20:REF\n:32A:230102JPY1000,50\n:71A:SHA\n-}";
+
+    fn directory(name: &str) -> PathBuf {
+        let path = env::temp_dir().join(format!("rustmsg-cli-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&path);
+        fs::create_dir_all(&path).unwrap();
+        return path;
+    }
+
+    #[test]
+    fn positive_tests() {
+        let directory = directory("positive");
+        let batch = directory.join("batch.fin");
+        fs::write(&batch, format!("{}\n$\n{}", VALID, VALID)).unwrap();
+
+        assert!(validation_errors(&batch, &Validator::new())
+            .unwrap()
+            .is_empty());
+        assert_eq!(validate(&[&batch], None, false).unwrap(), ExitCode::SUCCESS);
+
+        let output = directory.join("split");
+        assert_eq!(split(&batch, &output).unwrap(), ExitCode::SUCCESS);
+        let first = fs::read_to_string(output.join("batch_0001.fin")).unwrap();
+        assert!(first.starts_with("{1:F01FOOBARXXAXXX"));
+        assert!(first.contains(":20:REF\r\n"));
+        assert!(output.join("batch_0002.fin").exists());
+        assert!(!output.join("batch_0003.fin").exists());
+
+        fs::remove_dir_all(&directory).unwrap();
+    }
+
+    #[test]
+    fn negative_tests() {
+        let directory = directory("negative");
+        let batch = directory.join("batch.fin");
+        fs::write(&batch, format!("{}\n$\n{}\n$\n{{4:", VALID, INVALID)).unwrap();
+
+        let errors = validation_errors(&batch, &Validator::new()).unwrap();
+        assert_eq!(errors.len(), 2);
+        assert_eq!(errors[0]["file"], batch.display().to_string());
+        assert_eq!(errors[0]["message"], 2);
+        assert_eq!(errors[0]["field"], "32A");
+        assert!(errors[0]["text"].is_string());
+        assert_eq!(errors[1]["message"], 3);
+        assert_eq!(errors[1]["code"], PARSE_ERROR);
+        assert!(errors[1]["field"].is_null());
+        assert_eq!(validate(&[&batch], None, false).unwrap(), ExitCode::FAILURE);
+
+        // a missing file is an error rather than a validation failure
+        assert!(validate(&[directory.join("missing.fin")], None, false).is_err());
+        assert!(split(&directory.join("missing.fin"), &directory).is_err());
+
+        fs::remove_dir_all(&directory).unwrap();
+    }
+}
@@ -1,6 +1,9 @@
 use std::collections::LinkedList;
 
 use regex::Regex;
+use rustmsg::swift::mt::{FieldDefinition, FieldDefinitions};
+use std::io;
+use std::path::Path;
 use tl::{HTMLTag, NodeHandle};
 
 use crate::definition::cached_http_loader::CachedHttpLoader;
@@ -22,7 +25,7 @@ struct FieldTableIndices {
     link: usize,
 }
 
-fn process_definition(entry: &IndexEntry, downloader: &CachedHttpLoader, config: &SrConfig) {
+fn process_definition(entry: &IndexEntry, downloader: &CachedHttpLoader, config: &SrConfig, definitions: &mut FieldDefinitions) {
     println!("Processing {}", entry.description);
     if Regex::new("MT[0-9]9[0-9]")
         .unwrap()
@@ -31,6 +34,7 @@ fn process_definition(entry: &IndexEntry, downloader: &CachedHttpLoader, config:
     {
         return;
     }
+    let message_type = entry.message_type.trim_start_matches("MT").to_string();
 
     let link = format!("{}/{}", config.base_url, entry.link);
     let html = downloader.download_string(&link).unwrap();
@@ -92,6 +96,12 @@ fn process_definition(entry: &IndexEntry, downloader: &CachedHttpLoader, config:
         }
 
         let name = maybe_name.unwrap_or(tag.clone());
+        definitions.add(FieldDefinition {
+            message_types: vec![message_type.clone()],
+            tag: tag.trim().to_string(),
+            name: name.split_whitespace().collect::<Vec<&str>>().join(" "),
+        });
+
         let link = row.get(indices.link)
             .unwrap()
             .get(parser)
@@ -124,7 +134,11 @@ fn process_field_definition(link: &str, downloader: &CachedHttpLoader, config: &
     let parser = doc.parser();
 }
 
-pub fn process_definitions() {
+/// Scrapes the message definitions of the service releases and writes the field names to the
+/// output file in the catalogue format of `FieldDefinitions`
+pub fn process_definitions(output: &Path) -> Result<(), io::Error> {
+    let mut definitions = FieldDefinitions::new();
+
     let service_releases = [SrConfig {
         sr: "sr2022".into(),
         base_url: "https://www2.swift.com/knowledgecentre/rest/v1/publications/usgf_20220722/2.0/"
@@ -141,6 +155,8 @@ pub fn process_definitions() {
             &downloader,
         )
             .iter()
-            .for_each(|e| process_definition(&e, &downloader, cfg));
+            .for_each(|e| process_definition(&e, &downloader, cfg, &mut definitions));
     }
+
+    return std::fs::write(output, definitions.to_catalogue());
 }
@@ -2,5 +2,6 @@ extern crate num;
 #[macro_use]
 extern crate num_derive;
 
-pub mod swift;
-mod utils;
\ No newline at end of file
+#[macro_use]
+mod utils;
+pub mod swift;
\ No newline at end of file
@@ -1,5 +1,256 @@
+mod commands;
+// the scraper is kept as it was written, with its unused columns and lookups
+#[allow(dead_code, unused_imports, unused_variables)]
+#[allow(clippy::needless_borrow, clippy::useless_conversion)]
 mod definition;
 
-fn main() {
-    definition::process_definitions();
-}
\ No newline at end of file
+use clap::{Args, Parser, Subcommand};
+use rustmsg::swift::amount::Amount;
+use rustmsg::swift::currency::Currency;
+use rustmsg::swift::datetime::SwiftDate;
+use rustmsg::swift::mt::MessageQuery;
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+/// Command line tool for SWIFT MT messages. FIN files may contain a single message or an RJE
+/// batch of messages separated by `$`.
+#[derive(Parser)]
+#[command(name = "rustmsg", version)]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Prints the message of a FIN file as JSON, or an array of messages for RJE batches
+    Parse { file: PathBuf },
+    /// Validates the messages of FIN files, printing one JSON object per error and exiting
+    /// with status 1 if there are any
+    Validate {
+        #[arg(required = true)]
+        files: Vec<PathBuf>,
+        /// BIC directory file checked for unknown, expired and not connected BICs
+        #[arg(long)]
+        bic_directory: Option<PathBuf>,
+        /// Reject test and training BICs (location code ending in 0)
+        #[arg(long)]
+        reject_test_bics: bool,
+    },
+    /// Shows the messages of a FIN file with the names of the fields
+    Pretty {
+        file: PathBuf,
+        /// Field definitions catalogue, e.g. written by `definitions fetch`, taking
+        /// precedence over the built-in field names
+        #[arg(long)]
+        definitions: Option<PathBuf>,
+    },
+    /// Compares two messages header by header and field by field, exiting with status 1 if
+    /// they differ
+    Diff {
+        left: PathBuf,
+        right: PathBuf,
+        /// Field definitions catalogue for the names of the fields, see `pretty`
+        #[arg(long)]
+        definitions: Option<PathBuf>,
+        /// Print one JSON object per difference
+        #[arg(long)]
+        json: bool,
+    },
+    /// Prints the messages of a FIN file with names, addresses, accounts and narrative
+    /// replaced by fake values
+    Anonymise {
+        file: PathBuf,
+        /// Secret mixed into the fake values, so they cannot be matched with guessed originals
+        #[arg(long, default_value = "")]
+        secret: String,
+        /// Replace the BICs of the headers and of the option A, C and G fields
+        #[arg(long)]
+        replace_bics: bool,
+        /// Replace the amounts of the amount and balance fields
+        #[arg(long)]
+        replace_amounts: bool,
+        /// Keep the length of every line instead of using fake names and addresses
+        #[arg(long)]
+        preserve_structure: bool,
+    },
+    /// Writes each message of an RJE batch to a file of its own
+    Split {
+        file: PathBuf,
+        #[arg(long, short, default_value = ".")]
+        output_dir: PathBuf,
+    },
+    /// Local message store for investigations, indexed by UETR, MUR, references and BICs
+    Store {
+        /// Directory of the store, created if it does not exist
+        #[arg(long, short, default_value = "message-store")]
+        store: PathBuf,
+        #[command(subcommand)]
+        command: StoreCommand,
+    },
+    /// Message definitions of the SWIFT standards
+    Definitions {
+        #[command(subcommand)]
+        command: DefinitionsCommand,
+    },
+}
+
+#[derive(Subcommand)]
+enum StoreCommand {
+    /// Adds the messages of FIN files to the store
+    Add {
+        #[arg(required = true)]
+        files: Vec<PathBuf>,
+    },
+    /// Prints the metadata of the messages matching all criteria, one JSON object per line
+    Query {
+        #[command(flatten)]
+        criteria: Box<QueryCriteria>,
+        /// Print the messages linked to the matching ones through references and UETRs as well
+        #[arg(long)]
+        chain: bool,
+        /// Print the messages as an RJE batch instead of their metadata
+        #[arg(long)]
+        raw: bool,
+    },
+}
+
+#[derive(Args)]
+struct QueryCriteria {
+    #[arg(long)]
+    uetr: Option<String>,
+    /// Message user reference (field 108)
+    #[arg(long)]
+    mur: Option<String>,
+    /// Reference (field 20)
+    #[arg(long)]
+    reference: Option<String>,
+    /// Related reference (field 21)
+    #[arg(long)]
+    related_reference: Option<String>,
+    /// BIC in a header or party field, all branches for 8 characters
+    #[arg(long)]
+    bic: Option<String>,
+    #[arg(long)]
+    message_type: Option<String>,
+    /// Earliest value date, e.g. 2023-01-02
+    #[arg(long)]
+    from: Option<SwiftDate>,
+    /// Latest value date
+    #[arg(long)]
+    until: Option<SwiftDate>,
+    #[arg(long)]
+    currency: Option<Currency>,
+    /// Smallest amount, e.g. 1000,
+    #[arg(long)]
+    min_amount: Option<Amount>,
+    /// Largest amount
+    #[arg(long)]
+    max_amount: Option<Amount>,
+}
+
+#[derive(Subcommand)]
+enum DefinitionsCommand {
+    /// Downloads the message definitions from the SWIFT knowledge centre and writes the field
+    /// names to a catalogue file
+    Fetch {
+        #[arg(long, short, default_value = "field_definitions.txt")]
+        output: PathBuf,
+    },
+}
+
+impl QueryCriteria {
+    fn to_query(&self) -> MessageQuery {
+        let mut query = MessageQuery::new();
+        if let Some(uetr) = &self.uetr {
+            query = query.with_uetr(uetr);
+        }
+        if let Some(mur) = &self.mur {
+            query = query.with_message_user_reference(mur);
+        }
+        if let Some(reference) = &self.reference {
+            query = query.with_reference(reference);
+        }
+        if let Some(reference) = &self.related_reference {
+            query = query.with_related_reference(reference);
+        }
+        if let Some(bic) = &self.bic {
+            query = query.with_bic(bic);
+        }
+        if let Some(message_type) = &self.message_type {
+            query = query.with_message_type(message_type);
+        }
+        if let Some(date) = self.from {
+            query = query.with_value_date_from(date);
+        }
+        if let Some(date) = self.until {
+            query = query.with_value_date_until(date);
+        }
+        if let Some(currency) = self.currency {
+            query = query.with_currency(currency);
+        }
+        if let Some(amount) = self.min_amount {
+            query = query.with_min_amount(amount);
+        }
+        if let Some(amount) = self.max_amount {
+            query = query.with_max_amount(amount);
+        }
+        return query;
+    }
+}
+
+fn main() -> ExitCode {
+    let result = match Cli::parse().command {
+        Command::Parse { file } => commands::parse(&file),
+        Command::Validate {
+            files,
+            bic_directory,
+            reject_test_bics,
+        } => commands::validate(&files, bic_directory.as_deref(), reject_test_bics),
+        Command::Pretty { file, definitions } => commands::pretty(&file, definitions.as_deref()),
+        Command::Diff {
+            left,
+            right,
+            definitions,
+            json,
+        } => commands::diff(&left, &right, definitions.as_deref(), json),
+        Command::Anonymise {
+            file,
+            secret,
+            replace_bics,
+            replace_amounts,
+            preserve_structure,
+        } => commands::anonymise(
+            &file,
+            &secret,
+            replace_bics,
+            replace_amounts,
+            preserve_structure,
+        ),
+        Command::Split { file, output_dir } => commands::split(&file, &output_dir),
+        Command::Store {
+            store,
+            command: StoreCommand::Add { files },
+        } => commands::store_add(&store, &files),
+        Command::Store {
+            store,
+            command:
+                StoreCommand::Query {
+                    criteria,
+                    chain,
+                    raw,
+                },
+        } => commands::store_query(&store, &criteria.to_query(), chain, raw),
+        Command::Definitions {
+            command: DefinitionsCommand::Fetch { output },
+        } => definition::process_definitions(&output).map(|_| ExitCode::SUCCESS),
+    };
+
+    return match result {
+        Ok(code) => code,
+        Err(e) => {
+            eprintln!("rustmsg: {}", e);
+            ExitCode::from(2)
+        }
+    };
+}
@@ -0,0 +1,265 @@
+use crate::swift::currency::Currency;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::io;
+use std::io::ErrorKind::InvalidData;
+use std::str::FromStr;
+
+/// Maximum length of an amount in the `15d` format, including the decimal comma
+static MAX_AMOUNT_LENGTH: usize = 15;
+
+/// Non-negative decimal amount, stored exactly as integer units and number of decimals as
+/// written. Amounts compare by value, so `1,50` and `1,5` are equal.
+#[derive(Debug, Clone, Copy)]
+pub struct Amount {
+    units: u128,
+    decimals: u8,
+}
+
+impl Amount {
+    pub fn new(units: u128, decimals: u8) -> Amount {
+        return Amount { units, decimals };
+    }
+
+    /// Parses an amount in the SWIFT `15d` format: digits with a mandatory decimal comma,
+    /// at least one digit before the comma, no leading zeros and at most 15 characters.
+    pub fn parse(value: &str) -> Result<Amount, io::Error> {
+        if value.len() > MAX_AMOUNT_LENGTH {
+            return Err(io::Error::new(
+                InvalidData,
+                format!(
+                    "Amount {} exceeds the maximum length of {} characters",
+                    value, MAX_AMOUNT_LENGTH
+                ),
+            ));
+        }
+
+        let (integer, fraction) = value.split_once(',').ok_or_else(|| {
+            io::Error::new(
+                InvalidData,
+                format!("Amount {} has no decimal comma", value),
+            )
+        })?;
+
+        let all_digits = |s: &str| s.chars().all(|c| c.is_ascii_digit());
+        if integer.is_empty() || !all_digits(integer) || !all_digits(fraction) {
+            return Err(io::Error::new(
+                InvalidData,
+                format!(
+                    "Amount {} must consist of digits and one decimal comma",
+                    value
+                ),
+            ));
+        }
+
+        if integer.len() > 1 && integer.starts_with('0') {
+            return Err(io::Error::new(
+                InvalidData,
+                format!("Amount {} must not have leading zeros", value),
+            ));
+        }
+
+        let units = format!("{}{}", integer, fraction)
+            .parse::<u128>()
+            .map_err(|e| io::Error::new(InvalidData, e))?;
+        return Ok(Amount::new(units, fraction.len() as u8));
+    }
+
+    pub fn units(&self) -> u128 {
+        return self.units;
+    }
+
+    pub fn decimals(&self) -> u8 {
+        return self.decimals;
+    }
+
+    pub fn is_zero(&self) -> bool {
+        return self.units == 0;
+    }
+
+    /// Checks that the amount has no more digits after the comma than the currency allows
+    /// (network error C03)
+    pub fn check_currency(&self, currency: Currency) -> Result<(), io::Error> {
+        if self.decimals > currency.minor_units() {
+            return Err(io::Error::new(
+                InvalidData,
+                format!(
+                    "Amount {} has {} decimals but {} allows at most {}",
+                    self,
+                    self.decimals,
+                    currency,
+                    currency.minor_units()
+                ),
+            ));
+        }
+
+        return Ok(());
+    }
+
+    pub fn checked_add(&self, other: &Amount) -> Option<Amount> {
+        let decimals = self.decimals.max(other.decimals);
+        let left = self.rescaled(decimals)?;
+        let right = other.rescaled(decimals)?;
+        return Some(Amount::new(left.checked_add(right)?, decimals));
+    }
+
+    /// Formats the amount with at least the number of decimals of the currency, e.g. `1000,50`
+    /// for EUR or `1000,` for JPY
+    pub fn format_for(&self, currency: Currency) -> String {
+        let decimals = self.decimals.max(currency.minor_units());
+        return format_units(self.rescaled(decimals).unwrap_or(self.units), decimals);
+    }
+
+    /// Units and decimals without trailing zeros in the decimals
+    fn normalized(&self) -> (u128, u8) {
+        let (mut units, mut decimals) = (self.units, self.decimals);
+        while decimals > 0 && units % 10 == 0 {
+            units /= 10;
+            decimals -= 1;
+        }
+
+        return (units, decimals);
+    }
+
+    fn rescaled(&self, decimals: u8) -> Option<u128> {
+        return 10u128
+            .checked_pow((decimals - self.decimals) as u32)
+            .and_then(|factor| self.units.checked_mul(factor));
+    }
+}
+
+impl FromStr for Amount {
+    type Err = io::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        return Amount::parse(s);
+    }
+}
+
+impl fmt::Display for Amount {
+    /// Formats the amount in the `15d` format with the decimals as written, e.g. `1000,5`
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        return write!(f, "{}", format_units(self.units, self.decimals));
+    }
+}
+
+impl PartialEq for Amount {
+    fn eq(&self, other: &Self) -> bool {
+        return self.normalized() == other.normalized();
+    }
+}
+
+impl Eq for Amount {}
+
+impl Hash for Amount {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.normalized().hash(state);
+    }
+}
+
+impl PartialOrd for Amount {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        return Some(self.cmp(other));
+    }
+}
+
+impl Ord for Amount {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        let decimals = self.decimals.max(other.decimals);
+        return self.rescaled(decimals).cmp(&other.rescaled(decimals));
+    }
+}
+
+fn format_units(units: u128, decimals: u8) -> String {
+    let digits = format!("{:0>width$}", units, width = decimals as usize + 1);
+    let (integer, fraction) = digits.split_at(digits.len() - decimals as usize);
+    return format!("{},{}", integer, fraction);
+}
+
+serde_as_string!(Amount);
+
+#[cfg(test)]
+mod tests {
+    use crate::swift::amount::Amount;
+    use crate::swift::currency::Currency;
+
+    #[test]
+    fn positive_tests() {
+        let amount = Amount::parse("1234,56").unwrap();
+        assert_eq!(amount.units(), 123456);
+        assert_eq!(amount.decimals(), 2);
+        assert_eq!(amount.to_string(), "1234,56");
+
+        assert_eq!(Amount::parse("1000,").unwrap().to_string(), "1000,");
+        assert_eq!(Amount::parse("0,5").unwrap().to_string(), "0,5");
+        assert_eq!(Amount::parse("0,").unwrap().to_string(), "0,");
+        assert_eq!(
+            Amount::parse("1,50").unwrap(),
+            Amount::parse("1,5").unwrap()
+        );
+        assert!(Amount::parse("99999999999999,").is_ok());
+
+        assert_eq!(
+            Amount::parse("1000,5").unwrap().format_for(Currency::EUR),
+            "1000,50"
+        );
+        assert_eq!(
+            Amount::parse("1000,").unwrap().format_for(Currency::JPY),
+            "1000,"
+        );
+        assert_eq!(
+            Amount::parse("0,125").unwrap().format_for(Currency::BHD),
+            "0,125"
+        );
+
+        assert!(Amount::parse("1000,")
+            .unwrap()
+            .check_currency(Currency::JPY)
+            .is_ok());
+        assert!(Amount::parse("1,125")
+            .unwrap()
+            .check_currency(Currency::BHD)
+            .is_ok());
+
+        assert_eq!(
+            Amount::parse("0,75")
+                .unwrap()
+                .checked_add(&Amount::parse("1,255").unwrap())
+                .unwrap()
+                .to_string(),
+            "2,005"
+        );
+        assert!(Amount::parse("10,").unwrap() > Amount::parse("9,99").unwrap());
+
+        assert_eq!(Currency::from_code("EUR"), Some(Currency::EUR));
+        assert_eq!(Currency::EUR.numeric_code(), 978);
+        assert_eq!(Currency::JPY.minor_units(), 0);
+        assert_eq!(Currency::BHD.minor_units(), 3);
+        assert_eq!("USD".parse::<Currency>().unwrap().to_string(), "USD");
+    }
+
+    #[test]
+    fn negative_tests() {
+        assert!(Amount::parse("1000").is_err());
+        assert!(Amount::parse(",5").is_err());
+        assert!(Amount::parse("01,5").is_err());
+        assert!(Amount::parse("1.000,5").is_err());
+        assert!(Amount::parse("1,000,5").is_err());
+        assert!(Amount::parse("-1,5").is_err());
+        assert!(Amount::parse("999999999999999,").is_err());
+
+        assert!(Amount::parse("1,5")
+            .unwrap()
+            .check_currency(Currency::JPY)
+            .is_err());
+        assert!(Amount::parse("1,1255")
+            .unwrap()
+            .check_currency(Currency::BHD)
+            .is_err());
+        assert!(Amount::parse("1000,00")
+            .unwrap()
+            .check_currency(Currency::JPY)
+            .is_err());
+        assert!(Currency::from_code("XXY").is_none());
+    }
+}
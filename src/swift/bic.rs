@@ -0,0 +1,315 @@
+use crate::swift::country::is_valid_country_code;
+use std::fmt;
+use std::io;
+use std::io::ErrorKind::InvalidData;
+use std::str::FromStr;
+
+/// Business identifier code (ISO 9362) in its 8 or 11 character form
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Bic {
+    value: String,
+}
+
+/// 12 character address of a logical terminal as used in the basic and application header:
+/// the BIC8, the logical terminal code and the branch code
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct LogicalTerminalAddress {
+    value: String,
+}
+
+impl Bic {
+    /// Checks the structure of the BIC: 4 character institution code, 2 letter country code,
+    /// 2 character location code and optionally a 3 character branch code. Whether the country
+    /// code is an assigned ISO 3166 code is checked by `validate`.
+    pub fn parse(value: &str) -> Result<Bic, io::Error> {
+        if !value.is_ascii() || (value.len() != 8 && value.len() != 11) {
+            return Err(io::Error::new(
+                InvalidData,
+                format!(
+                    "BIC must have 8 or 11 characters but had {}: {}",
+                    value.len(),
+                    value
+                ),
+            ));
+        }
+
+        check_bic8(value)?;
+        if !is_upper_alphanumeric(&value[8..]) {
+            return Err(io::Error::new(
+                InvalidData,
+                format!("Invalid branch code in BIC {}", value),
+            ));
+        }
+
+        return Ok(Bic {
+            value: value.into(),
+        });
+    }
+
+    pub fn institution_code(&self) -> &str {
+        return &self.value[0..4];
+    }
+
+    pub fn country_code(&self) -> &str {
+        return &self.value[4..6];
+    }
+
+    pub fn location_code(&self) -> &str {
+        return &self.value[6..8];
+    }
+
+    pub fn branch_code(&self) -> Option<&str> {
+        return self.value.get(8..11);
+    }
+
+    /// Test and training BICs have a 0 as the second character of the location code
+    pub fn is_test(&self) -> bool {
+        return self.value.as_bytes()[7] == b'0';
+    }
+
+    pub fn has_valid_country(&self) -> bool {
+        return is_valid_country_code(self.country_code());
+    }
+
+    pub fn validate(&self) -> Result<(), io::Error> {
+        if !self.has_valid_country() {
+            return Err(io::Error::new(
+                InvalidData,
+                format!(
+                    "Unknown country code {} in BIC {}",
+                    self.country_code(),
+                    self.value
+                ),
+            ));
+        }
+
+        return Ok(());
+    }
+
+    pub fn to_bic8(&self) -> Bic {
+        return Bic {
+            value: self.value[0..8].into(),
+        };
+    }
+
+    /// The 11 character form, using the primary office branch code XXX for BIC8s
+    pub fn to_bic11(&self) -> Bic {
+        return Bic {
+            value: format!(
+                "{}{}",
+                &self.value[0..8],
+                self.branch_code().unwrap_or("XXX")
+            ),
+        };
+    }
+
+    pub fn to_logical_terminal(&self, terminal_code: char) -> LogicalTerminalAddress {
+        return LogicalTerminalAddress {
+            value: format!(
+                "{}{}{}",
+                &self.value[0..8],
+                terminal_code,
+                self.branch_code().unwrap_or("XXX")
+            ),
+        };
+    }
+
+    pub fn as_str(&self) -> &str {
+        return &self.value;
+    }
+}
+
+impl FromStr for Bic {
+    type Err = io::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        return Bic::parse(s);
+    }
+}
+
+impl fmt::Display for Bic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        return write!(f, "{}", self.value);
+    }
+}
+
+impl PartialEq<&str> for Bic {
+    fn eq(&self, other: &&str) -> bool {
+        return self.value == *other;
+    }
+}
+
+impl LogicalTerminalAddress {
+    pub fn parse(value: &str) -> Result<LogicalTerminalAddress, io::Error> {
+        if !value.is_ascii() || value.len() != 12 {
+            return Err(io::Error::new(
+                InvalidData,
+                format!(
+                    "Logical terminal address must have 12 characters but had {}: {}",
+                    value.len(),
+                    value
+                ),
+            ));
+        }
+
+        check_bic8(value)?;
+        if !is_upper_alphanumeric(&value[8..]) {
+            return Err(io::Error::new(
+                InvalidData,
+                format!(
+                    "Invalid terminal or branch code in logical terminal address {}",
+                    value
+                ),
+            ));
+        }
+
+        return Ok(LogicalTerminalAddress {
+            value: value.into(),
+        });
+    }
+
+    /// Whether this is the blank address of a header that has not been filled in
+    pub fn is_empty(&self) -> bool {
+        return self.value.trim().is_empty();
+    }
+
+    pub fn terminal_code(&self) -> char {
+        return self.value.as_bytes()[8] as char;
+    }
+
+    pub fn bic8(&self) -> Bic {
+        return Bic {
+            value: self.value[0..8].into(),
+        };
+    }
+
+    pub fn bic11(&self) -> Bic {
+        return Bic {
+            value: format!("{}{}", &self.value[0..8], &self.value[9..12]),
+        };
+    }
+
+    pub fn as_str(&self) -> &str {
+        return &self.value;
+    }
+}
+
+impl Default for LogicalTerminalAddress {
+    fn default() -> Self {
+        return LogicalTerminalAddress {
+            value: " ".repeat(12),
+        };
+    }
+}
+
+/// Unlike `parse`, also accepts the blank address of a header that has not been filled in, so
+/// that it reads back what `Display` and serialization write for it
+impl FromStr for LogicalTerminalAddress {
+    type Err = io::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.trim().is_empty() {
+            return Ok(LogicalTerminalAddress::default());
+        }
+
+        return LogicalTerminalAddress::parse(s);
+    }
+}
+
+impl fmt::Display for LogicalTerminalAddress {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        return write!(f, "{}", self.value);
+    }
+}
+
+impl PartialEq<&str> for LogicalTerminalAddress {
+    fn eq(&self, other: &&str) -> bool {
+        return self.value == *other;
+    }
+}
+
+fn check_bic8(value: &str) -> Result<(), io::Error> {
+    if !is_upper_alphanumeric(&value[0..4]) {
+        return Err(io::Error::new(
+            InvalidData,
+            format!("Invalid institution code in {}", value),
+        ));
+    }
+
+    if !value[4..6].chars().all(|c| c.is_ascii_uppercase()) {
+        return Err(io::Error::new(
+            InvalidData,
+            format!("Invalid country code in {}", value),
+        ));
+    }
+
+    if !is_upper_alphanumeric(&value[6..8]) {
+        return Err(io::Error::new(
+            InvalidData,
+            format!("Invalid location code in {}", value),
+        ));
+    }
+
+    return Ok(());
+}
+
+fn is_upper_alphanumeric(value: &str) -> bool {
+    return value
+        .chars()
+        .all(|c| c.is_ascii_uppercase() || c.is_ascii_digit());
+}
+
+serde_as_string!(Bic);
+serde_as_string!(LogicalTerminalAddress);
+
+#[cfg(test)]
+mod tests {
+    use crate::swift::bic::{Bic, LogicalTerminalAddress};
+
+    #[test]
+    fn positive_tests() {
+        let bic = Bic::parse("DEUTDEFF").unwrap();
+        assert_eq!(bic.institution_code(), "DEUT");
+        assert_eq!(bic.country_code(), "DE");
+        assert_eq!(bic.location_code(), "FF");
+        assert_eq!(bic.branch_code(), None);
+        assert!(!bic.is_test());
+        assert!(bic.validate().is_ok());
+        assert_eq!(bic.to_bic11(), "DEUTDEFFXXX");
+        assert_eq!(bic.to_logical_terminal('A'), "DEUTDEFFAXXX");
+
+        let bic = Bic::parse("DEUTDEF0500").unwrap();
+        assert_eq!(bic.branch_code(), Some("500"));
+        assert!(bic.is_test());
+        assert_eq!(bic.to_bic8(), "DEUTDEF0");
+        assert_eq!(bic.to_logical_terminal('B'), "DEUTDEF0B500");
+
+        let lt: LogicalTerminalAddress = "FOOBARXXAXXX".parse().unwrap();
+        assert_eq!(lt.terminal_code(), 'A');
+        assert_eq!(lt.bic8(), "FOOBARXX");
+        assert_eq!(lt.bic11(), "FOOBARXXXXX");
+        assert_eq!(lt.to_string(), "FOOBARXXAXXX");
+
+        assert!(LogicalTerminalAddress::default().is_empty());
+        let blank: LogicalTerminalAddress = LogicalTerminalAddress::default()
+            .to_string()
+            .parse()
+            .unwrap();
+        assert!(blank.is_empty());
+    }
+
+    #[test]
+    fn negative_tests() {
+        assert!(Bic::parse("DEUTDEF").is_err());
+        assert!(Bic::parse("DEUT1EFF").is_err());
+        assert!(Bic::parse("deutdeff").is_err());
+        assert!(Bic::parse("DEUTDEFF50").is_err());
+        assert!(Bic::parse("DEUTDEFF5-0").is_err());
+        assert!(Bic::parse("ABLRXXXX").unwrap().validate().is_err());
+
+        assert!(LogicalTerminalAddress::parse("FOOBARXXXXX").is_err());
+        assert!(LogicalTerminalAddress::parse("FOOBAR XAXXX").is_err());
+        assert!(LogicalTerminalAddress::parse("FOOBÄRXAXXX").is_err());
+        assert!(LogicalTerminalAddress::parse("            ").is_err());
+    }
+}
@@ -0,0 +1,268 @@
+use crate::swift::bic::Bic;
+use chrono::NaiveDate;
+use std::collections::HashMap;
+use std::fs::File;
+use std::io;
+use std::io::BufRead;
+use std::io::BufReader;
+use std::io::ErrorKind::InvalidData;
+use std::path::Path;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct BicRecord {
+    /// BIC11 of the institution or branch
+    pub bic: Bic,
+    pub institution_name: String,
+    pub city: Option<String>,
+    pub country_code: String,
+    pub expiry_date: Option<NaiveDate>,
+    pub connected: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BicStatus {
+    Active,
+    Unknown,
+    Expired,
+    NotConnected,
+}
+
+/// Source of reference data about BICs, e.g. the SWIFT BIC directory
+pub trait BicDirectory {
+    fn lookup(&self, bic: &Bic) -> Option<&BicRecord>;
+
+    fn institution_name(&self, bic: &Bic) -> Option<&str> {
+        return self.lookup(bic).map(|r| r.institution_name.as_str());
+    }
+
+    fn status(&self, bic: &Bic, date: NaiveDate) -> BicStatus {
+        return match self.lookup(bic) {
+            None => BicStatus::Unknown,
+            Some(record) if record.expiry_date.is_some_and(|expiry| expiry <= date) => {
+                BicStatus::Expired
+            }
+            Some(record) if !record.connected => BicStatus::NotConnected,
+            Some(_) => BicStatus::Active,
+        };
+    }
+}
+
+/// BIC directory loaded from a tab separated flat file with a header row, as delivered for the
+/// BIC directory and BICPlus. Columns are identified by their header:
+///
+/// * `BIC` or `BIC8` together with `BRANCH BIC` (mandatory)
+/// * `INSTITUTION NAME`
+/// * `CITY HEADING` or `CITY`
+/// * `ISO COUNTRY CODE` or `COUNTRY CODE` (defaults to the country of the BIC)
+/// * `MODIFICATION FLAG`, records flagged `D` (deleted) are treated as expired
+/// * `EXPIRY DATE` in the format YYYYMMDD or YYYY-MM-DD
+/// * `CONNECTED` with `Y` or `N` (records are considered connected if the column is missing)
+///
+/// Other columns are ignored.
+pub struct FileBicDirectory {
+    records: HashMap<Bic, BicRecord>,
+}
+
+struct ColumnIndices {
+    bic: Option<usize>,
+    bic8: Option<usize>,
+    branch: Option<usize>,
+    institution_name: Option<usize>,
+    city: Option<usize>,
+    country_code: Option<usize>,
+    modification_flag: Option<usize>,
+    expiry_date: Option<usize>,
+    connected: Option<usize>,
+}
+
+impl FileBicDirectory {
+    pub fn load(path: &Path) -> Result<FileBicDirectory, io::Error> {
+        return FileBicDirectory::from_reader(BufReader::new(File::open(path)?));
+    }
+
+    pub fn from_reader<R: BufRead>(reader: R) -> Result<FileBicDirectory, io::Error> {
+        let mut lines = reader.lines();
+        let header = match lines.next() {
+            Some(line) => line?,
+            None => {
+                return Ok(FileBicDirectory {
+                    records: HashMap::new(),
+                })
+            }
+        };
+
+        let indices = ColumnIndices::from_header(&header)?;
+        let mut records = HashMap::new();
+
+        for (line_number, line) in lines.enumerate() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+
+            let columns: Vec<&str> = line.split('\t').map(|c| c.trim()).collect();
+            let record = indices.read_record(&columns).map_err(|e| {
+                io::Error::new(
+                    InvalidData,
+                    format!("Invalid record in line {}: {}", line_number + 2, e),
+                )
+            })?;
+            records.insert(record.bic.clone(), record);
+        }
+
+        return Ok(FileBicDirectory { records });
+    }
+
+    pub fn len(&self) -> usize {
+        return self.records.len();
+    }
+
+    pub fn is_empty(&self) -> bool {
+        return self.records.is_empty();
+    }
+}
+
+impl BicDirectory for FileBicDirectory {
+    fn lookup(&self, bic: &Bic) -> Option<&BicRecord> {
+        return self.records.get(&bic.to_bic11());
+    }
+}
+
+impl ColumnIndices {
+    fn from_header(header: &str) -> Result<ColumnIndices, io::Error> {
+        let names: Vec<String> = header
+            .split('\t')
+            .map(|c| c.trim().to_uppercase())
+            .collect();
+        let find =
+            |candidates: &[&str]| names.iter().position(|n| candidates.contains(&n.as_str()));
+
+        let indices = ColumnIndices {
+            bic: find(&["BIC", "BIC11", "BIC CODE"]),
+            bic8: find(&["BIC8"]),
+            branch: find(&["BRANCH BIC", "BRANCH CODE"]),
+            institution_name: find(&["INSTITUTION NAME"]),
+            city: find(&["CITY HEADING", "CITY"]),
+            country_code: find(&["ISO COUNTRY CODE", "COUNTRY CODE"]),
+            modification_flag: find(&["MODIFICATION FLAG"]),
+            expiry_date: find(&["EXPIRY DATE"]),
+            connected: find(&["CONNECTED"]),
+        };
+
+        if indices.bic.is_none() && indices.bic8.is_none() {
+            return Err(io::Error::new(
+                InvalidData,
+                "BIC directory must have a BIC or BIC8 column",
+            ));
+        }
+
+        return Ok(indices);
+    }
+
+    fn read_record(&self, columns: &[&str]) -> Result<BicRecord, io::Error> {
+        let column = |index: Option<usize>| -> Option<&str> {
+            index
+                .and_then(|i| columns.get(i))
+                .copied()
+                .filter(|v| !v.is_empty())
+        };
+
+        let raw_bic = match column(self.bic) {
+            Some(bic) => bic.to_string(),
+            None => format!(
+                "{}{}",
+                column(self.bic8).unwrap_or(""),
+                column(self.branch).unwrap_or("XXX")
+            ),
+        };
+        let bic = Bic::parse(&raw_bic)?.to_bic11();
+
+        let expiry_date = match column(self.expiry_date) {
+            Some(date) => Some(
+                NaiveDate::parse_from_str(date, "%Y%m%d")
+                    .or_else(|_| NaiveDate::parse_from_str(date, "%Y-%m-%d"))
+                    .map_err(|e| {
+                        io::Error::new(InvalidData, format!("Invalid expiry date {}: {}", date, e))
+                    })?,
+            ),
+            None if column(self.modification_flag) == Some("D") => Some(NaiveDate::MIN),
+            None => None,
+        };
+
+        return Ok(BicRecord {
+            institution_name: column(self.institution_name).unwrap_or("").to_string(),
+            city: column(self.city).map(|c| c.to_string()),
+            country_code: column(self.country_code)
+                .unwrap_or(bic.country_code())
+                .to_string(),
+            expiry_date,
+            connected: column(self.connected).is_none_or(|c| c.starts_with('Y')),
+            bic,
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::swift::bic::Bic;
+    use crate::swift::bic_directory::{BicDirectory, BicStatus, FileBicDirectory};
+    use chrono::NaiveDate;
+
+    static DIRECTORY: &str = "MODIFICATION FLAG\tBIC8\tBRANCH BIC\tINSTITUTION NAME\tCITY HEADING\tISO COUNTRY CODE\tEXPIRY DATE\tCONNECTED
+A\tDEUTDEFF\tXXX\tDEUTSCHE BANK AG\tFRANKFURT AM MAIN\tDE\t\tY
+A\tDEUTDEFF\t500\tDEUTSCHE BANK AG\tFRANKFURT AM MAIN\tDE\t\tY
+A\tFOOBARXX\tXXX\tFOO BANK\tBUENOS AIRES\tAR\t20200101\tY
+D\tOLDBGB2L\tXXX\tOLD BANK\tLONDON\tGB\t\tY
+A\tNCONCHZZ\tXXX\tNOT CONNECTED BANK\tZURICH\tCH\t\tN
+";
+
+    #[test]
+    fn positive_tests() {
+        let directory = FileBicDirectory::from_reader(DIRECTORY.as_bytes()).unwrap();
+        let today = NaiveDate::from_ymd_opt(2023, 1, 2).unwrap();
+
+        assert_eq!(directory.len(), 5);
+        assert_eq!(
+            directory.institution_name(&Bic::parse("DEUTDEFF").unwrap()),
+            Some("DEUTSCHE BANK AG")
+        );
+        assert_eq!(
+            directory
+                .lookup(&Bic::parse("DEUTDEFF500").unwrap())
+                .unwrap()
+                .city
+                .as_deref(),
+            Some("FRANKFURT AM MAIN")
+        );
+        assert_eq!(
+            directory.status(&Bic::parse("DEUTDEFFXXX").unwrap(), today),
+            BicStatus::Active
+        );
+        assert_eq!(
+            directory.status(&Bic::parse("FOOBARXX").unwrap(), today),
+            BicStatus::Expired
+        );
+        assert_eq!(
+            directory.status(&Bic::parse("OLDBGB2L").unwrap(), today),
+            BicStatus::Expired
+        );
+        assert_eq!(
+            directory.status(&Bic::parse("NCONCHZZ").unwrap(), today),
+            BicStatus::NotConnected
+        );
+        assert_eq!(
+            directory.status(&Bic::parse("DEUTDEFF123").unwrap(), today),
+            BicStatus::Unknown
+        );
+    }
+
+    #[test]
+    fn negative_tests() {
+        assert!(FileBicDirectory::from_reader("NAME\tCITY\nFOO\tBAR\n".as_bytes()).is_err());
+        assert!(FileBicDirectory::from_reader("BIC\tNAME\nFOO\tBAR\n".as_bytes()).is_err());
+        assert!(FileBicDirectory::from_reader(
+            "BIC\tEXPIRY DATE\nDEUTDEFF\t2020-13-01\n".as_bytes()
+        )
+        .is_err());
+    }
+}
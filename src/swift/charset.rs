@@ -0,0 +1,336 @@
+use std::fmt;
+
+/// Character sets of the SWIFT FIN standards. X is used for almost all text fields, Y is
+/// the restricted upper case set and Z the extended set used in e.g. field 77E.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CharacterSet {
+    X,
+    Y,
+    Z,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct InvalidCharacter {
+    pub position: usize,
+    pub character: char,
+}
+
+static X_SPECIAL: &str = "/-?:().,'+ ";
+static Y_SPECIAL: &str = ".,-()/='+:?!\"%&*<>; ";
+static Z_SPECIAL: &str = ".,-()/='+:?!\"%&*<>;{@#_ ";
+
+static LATIN_TRANSLITERATIONS: [(&str, &str); 30] = [
+    ("ÀÁÂÃÄÅĀĂĄ", "A"),
+    ("àáâãäåāăą", "a"),
+    ("ÇĆĈĊČ", "C"),
+    ("çćĉċč", "c"),
+    ("ĎĐÐ", "D"),
+    ("ďđð", "d"),
+    ("ÈÉÊËĒĔĖĘĚ", "E"),
+    ("èéêëēĕėęě", "e"),
+    ("ĜĞĠĢ", "G"),
+    ("ĝğġģ", "g"),
+    ("ÌÍÎÏĨĪĬĮİ", "I"),
+    ("ìíîïĩīĭįı", "i"),
+    ("ĹĻĽĿŁ", "L"),
+    ("ĺļľŀł", "l"),
+    ("ÑŃŅŇ", "N"),
+    ("ñńņň", "n"),
+    ("ÒÓÔÕÖØŌŎŐ", "O"),
+    ("òóôõöøōŏő", "o"),
+    ("ŔŖŘ", "R"),
+    ("ŕŗř", "r"),
+    ("ŚŜŞŠ", "S"),
+    ("śŝşš", "s"),
+    ("ŢŤ", "T"),
+    ("ţť", "t"),
+    ("ÙÚÛÜŨŪŬŮŰŲ", "U"),
+    ("ùúûüũūŭůűų", "u"),
+    ("ÝŸ", "Y"),
+    ("ýÿ", "y"),
+    ("ŹŻŽ", "Z"),
+    ("źżž", "z"),
+];
+
+static SPECIAL_TRANSLITERATIONS: [(char, &str); 8] = [
+    ('Æ', "AE"),
+    ('æ', "ae"),
+    ('Œ', "OE"),
+    ('œ', "oe"),
+    ('ß', "ss"),
+    ('Þ', "TH"),
+    ('þ', "th"),
+    ('&', "+"),
+];
+
+static CYRILLIC_TRANSLITERATIONS: [(char, &str); 33] = [
+    ('а', "a"),
+    ('б', "b"),
+    ('в', "v"),
+    ('г', "g"),
+    ('д', "d"),
+    ('е', "e"),
+    ('ё', "e"),
+    ('ж', "zh"),
+    ('з', "z"),
+    ('и', "i"),
+    ('й', "i"),
+    ('к', "k"),
+    ('л', "l"),
+    ('м', "m"),
+    ('н', "n"),
+    ('о', "o"),
+    ('п', "p"),
+    ('р', "r"),
+    ('с', "s"),
+    ('т', "t"),
+    ('у', "u"),
+    ('ф', "f"),
+    ('х', "kh"),
+    ('ц', "ts"),
+    ('ч', "ch"),
+    ('ш', "sh"),
+    ('щ', "shch"),
+    ('ъ', "ie"),
+    ('ы', "y"),
+    ('ь', ""),
+    ('э', "e"),
+    ('ю', "iu"),
+    ('я', "ia"),
+];
+
+static GREEK_TRANSLITERATIONS: [(char, &str); 25] = [
+    ('α', "a"),
+    ('β', "v"),
+    ('γ', "g"),
+    ('δ', "d"),
+    ('ε', "e"),
+    ('ζ', "z"),
+    ('η', "i"),
+    ('θ', "th"),
+    ('ι', "i"),
+    ('κ', "k"),
+    ('λ', "l"),
+    ('μ', "m"),
+    ('ν', "n"),
+    ('ξ', "x"),
+    ('ο', "o"),
+    ('π', "p"),
+    ('ρ', "r"),
+    ('σ', "s"),
+    ('ς', "s"),
+    ('τ', "t"),
+    ('υ', "y"),
+    ('φ', "f"),
+    ('χ', "ch"),
+    ('ψ', "ps"),
+    ('ω', "o"),
+];
+
+impl CharacterSet {
+    /// Whether the character is part of the set. Carriage return and line feed are accepted
+    /// by every set as they separate the lines of multi-line fields.
+    pub fn contains(&self, c: char) -> bool {
+        if c == '\r' || c == '\n' || c.is_ascii_digit() || c.is_ascii_uppercase() {
+            return true;
+        }
+
+        return match self {
+            CharacterSet::X => c.is_ascii_lowercase() || X_SPECIAL.contains(c),
+            CharacterSet::Y => Y_SPECIAL.contains(c),
+            CharacterSet::Z => c.is_ascii_lowercase() || Z_SPECIAL.contains(c),
+        };
+    }
+
+    pub fn validate(&self, value: &str) -> Result<(), Vec<InvalidCharacter>> {
+        let invalid: Vec<InvalidCharacter> = value
+            .chars()
+            .enumerate()
+            .filter(|(_, c)| !self.contains(*c))
+            .map(|(position, character)| InvalidCharacter {
+                position,
+                character,
+            })
+            .collect();
+
+        return if invalid.is_empty() {
+            Ok(())
+        } else {
+            Err(invalid)
+        };
+    }
+}
+
+impl fmt::Display for CharacterSet {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        return match self {
+            CharacterSet::X => write!(f, "X"),
+            CharacterSet::Y => write!(f, "Y"),
+            CharacterSet::Z => write!(f, "Z"),
+        };
+    }
+}
+
+/// Converts text into the X character set. Latin letters with diacritics are reduced to
+/// their base letter, Cyrillic and Greek letters are transliterated and every other
+/// character outside of the X set is replaced by a full stop. The conversion is lossy,
+/// use `escape` where the original text has to be restored.
+pub fn transliterate(value: &str) -> String {
+    let mut ret = String::with_capacity(value.len());
+
+    for c in value.chars() {
+        if CharacterSet::X.contains(c) {
+            ret.push(c);
+        } else if let Some(replacement) = transliterate_char(c) {
+            ret.push_str(&replacement);
+        } else {
+            ret.push('.');
+        }
+    }
+
+    return ret;
+}
+
+/// Reversibly escapes text into the X character set following the `??` convention of the
+/// CBPR+ translation rules: every character outside of the X set is written as `??`
+/// followed by the four hex digits of its UTF-16 code unit(s). A question mark directly
+/// following another question mark is escaped as well, so `unescape` never mistakes
+/// literal text for an escape sequence.
+pub fn escape(value: &str) -> String {
+    let mut ret = String::with_capacity(value.len());
+    let mut previous: Option<char> = None;
+    let mut buffer = [0u16; 2];
+
+    for c in value.chars() {
+        let needs_escape = !CharacterSet::X.contains(c) || (c == '?' && previous == Some('?'));
+        if needs_escape {
+            for unit in c.encode_utf16(&mut buffer) {
+                ret.push_str(&format!("??{:04X}", unit));
+            }
+        } else {
+            ret.push(c);
+        }
+
+        previous = Some(c);
+    }
+
+    return ret;
+}
+
+/// Restores text escaped by `escape`
+pub fn unescape(value: &str) -> String {
+    let chars: Vec<char> = value.chars().collect();
+    let mut units: Vec<u16> = Vec::with_capacity(chars.len());
+    let mut position = 0;
+
+    while position < chars.len() {
+        if let Some(unit) = escaped_unit_at(&chars, position) {
+            units.push(unit);
+            position += 6;
+        } else {
+            let mut buffer = [0u16; 2];
+            units.extend_from_slice(chars[position].encode_utf16(&mut buffer));
+            position += 1;
+        }
+    }
+
+    return String::from_utf16_lossy(&units);
+}
+
+fn escaped_unit_at(chars: &[char], position: usize) -> Option<u16> {
+    if position + 6 > chars.len() || chars[position] != '?' || chars[position + 1] != '?' {
+        return None;
+    }
+
+    let hex: String = chars[position + 2..position + 6].iter().collect();
+    if !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        return None;
+    }
+
+    return u16::from_str_radix(&hex, 16).ok();
+}
+
+fn transliterate_char(c: char) -> Option<String> {
+    if let Some((_, replacement)) = LATIN_TRANSLITERATIONS
+        .iter()
+        .find(|(chars, _)| chars.contains(c))
+    {
+        return Some(replacement.to_string());
+    }
+
+    if let Some((_, replacement)) = SPECIAL_TRANSLITERATIONS.iter().find(|(l, _)| *l == c) {
+        return Some(replacement.to_string());
+    }
+
+    let lower = c.to_lowercase().next().unwrap_or(c);
+    let replacement = CYRILLIC_TRANSLITERATIONS
+        .iter()
+        .chain(GREEK_TRANSLITERATIONS.iter())
+        .find(|(l, _)| *l == lower)
+        .map(|(_, r)| r.to_string())?;
+
+    if lower == c {
+        return Some(replacement);
+    }
+
+    let mut chars = replacement.chars();
+    return Some(match chars.next() {
+        Some(first) => first.to_ascii_uppercase().to_string() + chars.as_str(),
+        None => replacement,
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::swift::charset::{escape, transliterate, unescape, CharacterSet, InvalidCharacter};
+
+    #[test]
+    fn positive_tests() {
+        assert!(CharacterSet::X
+            .validate("JOHN DOE\r\nMain street 1/2 (rear)")
+            .is_ok());
+        assert!(CharacterSet::Y.validate("ACME CORP. LTD=1").is_ok());
+        assert!(CharacterSet::Z.validate("mail@example.com #1").is_ok());
+
+        assert_eq!(transliterate("Müller & Söhne"), "Muller + Sohne");
+        assert_eq!(transliterate("Straße"), "Strasse");
+        assert_eq!(transliterate("Жуков"), "Zhukov");
+        assert_eq!(transliterate("Αθηνα"), "Athina");
+        assert_eq!(transliterate("50% off"), "50. off");
+
+        for original in [
+            "Müller & Söhne",
+            "a??b",
+            "???",
+            "?é",
+            "price: 5€ @ 😀",
+            "??0041",
+        ] {
+            let escaped = escape(original);
+            assert!(CharacterSet::X.validate(&escaped).is_ok());
+            assert_eq!(unescape(&escaped), original);
+        }
+
+        assert_eq!(escape("Café"), "Caf??00E9");
+        assert_eq!(unescape("Caf??00E9"), "Café");
+    }
+
+    #[test]
+    fn negative_tests() {
+        assert_eq!(
+            CharacterSet::X.validate("AB@C_").unwrap_err(),
+            vec![
+                InvalidCharacter {
+                    position: 2,
+                    character: '@'
+                },
+                InvalidCharacter {
+                    position: 4,
+                    character: '_'
+                }
+            ]
+        );
+        assert!(CharacterSet::Y.validate("lower").is_err());
+        assert!(CharacterSet::Z.validate("é").is_err());
+    }
+}
@@ -0,0 +1,453 @@
+use chrono::{DateTime, Duration, FixedOffset, NaiveDate, NaiveDateTime, NaiveTime, TimeZone, Utc};
+use std::fmt;
+use std::io;
+use std::io::ErrorKind::InvalidData;
+use std::str::FromStr;
+
+/// Two digit years below the pivot belong to the 21st century, the others to the 20th
+pub static DEFAULT_CENTURY_PIVOT: u32 = 80;
+
+/// Calendar date of the SWIFT formats `6!n` (YYMMDD) and `8!n` (YYYYMMDD)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct SwiftDate {
+    date: NaiveDate,
+}
+
+/// Time of day of the SWIFT format `4!n` (HHMM), optionally with seconds (HHMMSS)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct SwiftTime {
+    time: NaiveTime,
+}
+
+/// Offset from UTC as used in fields 13C and 98E, written as sign and HHMM (e.g. `+0100`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct UtcOffset {
+    offset: FixedOffset,
+}
+
+/// Date and time in the local time of the party that recorded it, e.g. the input time of the
+/// sender or the output time of the receiver in the application header. The offset to UTC
+/// is usually not part of the message, so the value can only be converted to UTC when the
+/// offset is known.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct LocalDateTime {
+    pub date_time: NaiveDateTime,
+    pub offset: Option<UtcOffset>,
+}
+
+impl SwiftDate {
+    pub fn new(date: NaiveDate) -> SwiftDate {
+        return SwiftDate { date };
+    }
+
+    /// Parses a `6!n` date using the default century window
+    pub fn parse_yymmdd(value: &str) -> Result<SwiftDate, io::Error> {
+        return SwiftDate::parse_yymmdd_with_pivot(value, DEFAULT_CENTURY_PIVOT);
+    }
+
+    /// Parses a `6!n` date, years below `pivot` are placed in 20YY and all others in 19YY
+    pub fn parse_yymmdd_with_pivot(value: &str, pivot: u32) -> Result<SwiftDate, io::Error> {
+        let digits = parse_digits(value, 6, "date YYMMDD")?;
+        let year = if digits[0] < pivot {
+            2000 + digits[0]
+        } else {
+            1900 + digits[0]
+        };
+        return SwiftDate::from_parts(year, digits[1], digits[2], value);
+    }
+
+    /// Parses an `8!n` date
+    pub fn parse_yyyymmdd(value: &str) -> Result<SwiftDate, io::Error> {
+        let digits = parse_digits(value, 8, "date YYYYMMDD")?;
+        return SwiftDate::from_parts(digits[0] * 100 + digits[1], digits[2], digits[3], value);
+    }
+
+    pub fn date(&self) -> NaiveDate {
+        return self.date;
+    }
+
+    pub fn to_yymmdd(&self) -> String {
+        return self.date.format("%y%m%d").to_string();
+    }
+
+    pub fn to_yyyymmdd(&self) -> String {
+        return self.date.format("%Y%m%d").to_string();
+    }
+
+    fn from_parts(year: u32, month: u32, day: u32, value: &str) -> Result<SwiftDate, io::Error> {
+        return NaiveDate::from_ymd_opt(year as i32, month, day)
+            .map(SwiftDate::new)
+            .ok_or_else(|| io::Error::new(InvalidData, format!("Invalid date {}", value)));
+    }
+}
+
+impl fmt::Display for SwiftDate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        return write!(f, "{}", self.date);
+    }
+}
+
+impl FromStr for SwiftDate {
+    type Err = io::Error;
+
+    /// Parses the ISO 8601 form written by `Display`, e.g. `2023-01-02`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        return NaiveDate::from_str(s)
+            .map(SwiftDate::new)
+            .map_err(|e| io::Error::new(InvalidData, format!("Invalid date {}: {}", s, e)));
+    }
+}
+
+impl SwiftTime {
+    pub fn new(time: NaiveTime) -> SwiftTime {
+        return SwiftTime { time };
+    }
+
+    /// Parses a `4!n` time (HHMM)
+    pub fn parse_hhmm(value: &str) -> Result<SwiftTime, io::Error> {
+        let digits = parse_digits(value, 4, "time HHMM")?;
+        return SwiftTime::from_parts(digits[0], digits[1], 0, value);
+    }
+
+    /// Parses a `6!n` time (HHMMSS)
+    pub fn parse_hhmmss(value: &str) -> Result<SwiftTime, io::Error> {
+        let digits = parse_digits(value, 6, "time HHMMSS")?;
+        return SwiftTime::from_parts(digits[0], digits[1], digits[2], value);
+    }
+
+    pub fn time(&self) -> NaiveTime {
+        return self.time;
+    }
+
+    pub fn to_hhmm(&self) -> String {
+        return self.time.format("%H%M").to_string();
+    }
+
+    pub fn to_hhmmss(&self) -> String {
+        return self.time.format("%H%M%S").to_string();
+    }
+
+    fn from_parts(
+        hour: u32,
+        minute: u32,
+        second: u32,
+        value: &str,
+    ) -> Result<SwiftTime, io::Error> {
+        return NaiveTime::from_hms_opt(hour, minute, second)
+            .map(SwiftTime::new)
+            .ok_or_else(|| io::Error::new(InvalidData, format!("Invalid time {}", value)));
+    }
+}
+
+impl fmt::Display for SwiftTime {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        return write!(f, "{}", self.time);
+    }
+}
+
+impl FromStr for SwiftTime {
+    type Err = io::Error;
+
+    /// Parses the form written by `Display`, e.g. `15:30:00`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        return NaiveTime::parse_from_str(s, "%H:%M:%S%.f")
+            .map(SwiftTime::new)
+            .map_err(|e| io::Error::new(InvalidData, format!("Invalid time {}: {}", s, e)));
+    }
+}
+
+impl UtcOffset {
+    pub fn new(offset: FixedOffset) -> UtcOffset {
+        return UtcOffset { offset };
+    }
+
+    pub fn utc() -> UtcOffset {
+        return UtcOffset::new(FixedOffset::east_opt(0).unwrap());
+    }
+
+    /// Parses an offset written as sign and HHMM. Besides `+` and `-` the sign `N` of field
+    /// 98E is accepted for negative offsets, an offset without sign is positive.
+    pub fn parse(value: &str) -> Result<UtcOffset, io::Error> {
+        let (negative, digits) = match value.chars().next() {
+            Some('-') | Some('N') => (true, &value[1..]),
+            Some('+') => (false, &value[1..]),
+            _ => (false, value),
+        };
+
+        let parts = parse_digits(digits, 4, "offset HHMM")?;
+        if parts[0] > 23 || parts[1] > 59 {
+            return Err(io::Error::new(
+                InvalidData,
+                format!("Invalid offset {}", value),
+            ));
+        }
+
+        let seconds = (parts[0] * 3600 + parts[1] * 60) as i32;
+        let offset = if negative {
+            FixedOffset::west_opt(seconds)
+        } else {
+            FixedOffset::east_opt(seconds)
+        };
+
+        return offset
+            .map(UtcOffset::new)
+            .ok_or_else(|| io::Error::new(InvalidData, format!("Invalid offset {}", value)));
+    }
+
+    pub fn offset(&self) -> FixedOffset {
+        return self.offset;
+    }
+}
+
+impl fmt::Display for UtcOffset {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let seconds = self.offset.local_minus_utc();
+        let sign = if seconds < 0 { '-' } else { '+' };
+        let minutes = seconds.abs() / 60;
+        return write!(f, "{}{:02}{:02}", sign, minutes / 60, minutes % 60);
+    }
+}
+
+impl FromStr for UtcOffset {
+    type Err = io::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        return UtcOffset::parse(s);
+    }
+}
+
+impl LocalDateTime {
+    pub fn new(date_time: NaiveDateTime) -> LocalDateTime {
+        return LocalDateTime {
+            date_time,
+            offset: None,
+        };
+    }
+
+    pub fn from_parts(date: SwiftDate, time: SwiftTime) -> LocalDateTime {
+        return LocalDateTime::new(date.date().and_time(time.time()));
+    }
+
+    /// Parses the `98C` format YYYYMMDDHHMMSS
+    pub fn parse_yyyymmddhhmmss(value: &str) -> Result<LocalDateTime, io::Error> {
+        if value.len() != 14 || !value.is_ascii() {
+            return Err(io::Error::new(
+                InvalidData,
+                format!("Expected date and time YYYYMMDDHHMMSS but got {}", value),
+            ));
+        }
+
+        return Ok(LocalDateTime::from_parts(
+            SwiftDate::parse_yyyymmdd(&value[0..8])?,
+            SwiftTime::parse_hhmmss(&value[8..14])?,
+        ));
+    }
+
+    /// Parses the format YYMMDDHHMMSS with optional hundredths of a second, as used for the
+    /// balance checkpoint (field 423 of the user header)
+    pub fn parse_yymmddhhmmss(value: &str) -> Result<LocalDateTime, io::Error> {
+        if (value.len() != 12 && value.len() != 14) || !value.is_ascii() {
+            return Err(io::Error::new(
+                InvalidData,
+                format!("Expected date and time YYMMDDHHMMSS[ss] but got {}", value),
+            ));
+        }
+
+        let mut date_time = LocalDateTime::from_parts(
+            SwiftDate::parse_yymmdd(&value[0..6])?,
+            SwiftTime::parse_hhmmss(&value[6..12])?,
+        );
+        if value.len() == 14 {
+            let hundredths = parse_digits(&value[12..14], 2, "hundredths of a second")?[0];
+            date_time.date_time += Duration::milliseconds(hundredths as i64 * 10);
+        }
+
+        return Ok(date_time);
+    }
+
+    /// Formats the date and time as YYMMDDHHMMSS, followed by the hundredths of a second if
+    /// they are not zero
+    pub fn to_yymmddhhmmss(&self) -> String {
+        let hundredths = self.date_time.and_utc().timestamp_subsec_millis() / 10;
+        let formatted = self.date_time.format("%y%m%d%H%M%S").to_string();
+        return if hundredths == 0 {
+            formatted
+        } else {
+            format!("{}{:02}", formatted, hundredths)
+        };
+    }
+
+    pub fn with_offset(mut self, offset: UtcOffset) -> LocalDateTime {
+        self.offset = Some(offset);
+        return self;
+    }
+
+    pub fn date(&self) -> SwiftDate {
+        return SwiftDate::new(self.date_time.date());
+    }
+
+    pub fn time(&self) -> SwiftTime {
+        return SwiftTime::new(self.date_time.time());
+    }
+
+    /// The point in time in UTC, if the offset of the local time is known
+    pub fn to_utc(&self) -> Option<DateTime<Utc>> {
+        let offset = self.offset?.offset();
+        return offset
+            .from_local_datetime(&self.date_time)
+            .single()
+            .map(|dt| dt.with_timezone(&Utc));
+    }
+
+    /// Assumes the given offset for a local time without offset
+    pub fn to_utc_assuming(&self, offset: UtcOffset) -> Option<DateTime<Utc>> {
+        return LocalDateTime {
+            date_time: self.date_time,
+            offset: Some(self.offset.unwrap_or(offset)),
+        }
+        .to_utc();
+    }
+}
+
+impl fmt::Display for LocalDateTime {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        return match self.offset {
+            Some(offset) => write!(f, "{}{}", self.date_time, offset),
+            None => write!(f, "{}", self.date_time),
+        };
+    }
+}
+
+impl FromStr for LocalDateTime {
+    type Err = io::Error;
+
+    /// Parses the form written by `Display`, e.g. `2023-01-02 15:30:00` or
+    /// `2023-01-02 15:30:00+0100`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let split = s.len().saturating_sub(5);
+        let (date_time, offset) = match s.get(split..split + 1) {
+            Some("+") | Some("-") => (&s[..split], Some(UtcOffset::parse(&s[split..])?)),
+            _ => (s, None),
+        };
+
+        let date_time =
+            NaiveDateTime::parse_from_str(date_time, "%Y-%m-%d %H:%M:%S%.f").map_err(|e| {
+                io::Error::new(InvalidData, format!("Invalid date and time {}: {}", s, e))
+            })?;
+        return Ok(LocalDateTime { date_time, offset });
+    }
+}
+
+/// Splits a string of `length` digits into groups of two, the first group of an 8 digit date
+/// being the century
+fn parse_digits(value: &str, length: usize, name: &str) -> Result<Vec<u32>, io::Error> {
+    if value.len() != length || !value.chars().all(|c| c.is_ascii_digit()) {
+        return Err(io::Error::new(
+            InvalidData,
+            format!("Expected {} but got {}", name, value),
+        ));
+    }
+
+    return Ok((0..length)
+        .step_by(2)
+        .map(|i| value[i..i + 2].parse::<u32>().unwrap())
+        .collect());
+}
+
+serde_as_string!(SwiftDate);
+serde_as_string!(SwiftTime);
+serde_as_string!(UtcOffset);
+serde_as_string!(LocalDateTime);
+
+#[cfg(test)]
+mod tests {
+    use crate::swift::datetime::{LocalDateTime, SwiftDate, SwiftTime, UtcOffset};
+    use chrono::{NaiveDate, TimeZone, Utc};
+
+    #[test]
+    fn positive_tests() {
+        assert_eq!(
+            SwiftDate::parse_yymmdd("230102").unwrap().date(),
+            NaiveDate::from_ymd_opt(2023, 1, 2).unwrap()
+        );
+        assert_eq!(
+            SwiftDate::parse_yymmdd("991231").unwrap().date(),
+            NaiveDate::from_ymd_opt(1999, 12, 31).unwrap()
+        );
+        assert_eq!(
+            SwiftDate::parse_yymmdd_with_pivot("900101", 95)
+                .unwrap()
+                .date(),
+            NaiveDate::from_ymd_opt(2090, 1, 1).unwrap()
+        );
+        assert_eq!(
+            SwiftDate::parse_yyyymmdd("20240229").unwrap().to_yymmdd(),
+            "240229"
+        );
+        assert_eq!(
+            SwiftDate::parse_yymmdd("010606").unwrap().to_yyyymmdd(),
+            "20010606"
+        );
+
+        assert_eq!(SwiftTime::parse_hhmm("1511").unwrap().to_hhmm(), "1511");
+        assert_eq!(
+            SwiftTime::parse_hhmmss("235959").unwrap().to_hhmmss(),
+            "235959"
+        );
+
+        assert_eq!(UtcOffset::parse("+0100").unwrap().to_string(), "+0100");
+        assert_eq!(UtcOffset::parse("N0530").unwrap().to_string(), "-0530");
+        assert_eq!(UtcOffset::parse("0000").unwrap(), UtcOffset::utc());
+
+        let checkpoint = LocalDateTime::parse_yymmddhhmmss("23010215300012").unwrap();
+        assert_eq!(checkpoint.to_yymmddhhmmss(), "23010215300012");
+        assert_eq!(
+            checkpoint.to_string().parse::<LocalDateTime>().unwrap(),
+            checkpoint
+        );
+        let with_offset = checkpoint.with_offset(UtcOffset::parse("-0130").unwrap());
+        assert_eq!(
+            with_offset.to_string().parse::<LocalDateTime>().unwrap(),
+            with_offset
+        );
+        let date = SwiftDate::parse_yymmdd("230102").unwrap();
+        assert_eq!(date.to_string().parse::<SwiftDate>().unwrap(), date);
+        let time = SwiftTime::parse_hhmm("1530").unwrap();
+        assert_eq!(time.to_string().parse::<SwiftTime>().unwrap(), time);
+        assert_eq!(
+            LocalDateTime::parse_yymmddhhmmss("230102153000")
+                .unwrap()
+                .to_yymmddhhmmss(),
+            "230102153000"
+        );
+
+        let local = LocalDateTime::parse_yyyymmddhhmmss("20230102153000").unwrap();
+        assert_eq!(local.to_utc(), None);
+        assert_eq!(local.date().to_yyyymmdd(), "20230102");
+        assert_eq!(local.time().to_hhmm(), "1530");
+        assert_eq!(
+            local
+                .with_offset(UtcOffset::parse("+0100").unwrap())
+                .to_utc(),
+            Some(Utc.with_ymd_and_hms(2023, 1, 2, 14, 30, 0).unwrap())
+        );
+        assert_eq!(
+            local.to_utc_assuming(UtcOffset::parse("-0200").unwrap()),
+            Some(Utc.with_ymd_and_hms(2023, 1, 2, 17, 30, 0).unwrap())
+        );
+    }
+
+    #[test]
+    fn negative_tests() {
+        assert!(SwiftDate::parse_yymmdd("230230").is_err());
+        assert!(SwiftDate::parse_yymmdd("23010").is_err());
+        assert!(SwiftDate::parse_yymmdd("2301AB").is_err());
+        assert!(SwiftDate::parse_yyyymmdd("20230229").is_err());
+        assert!(SwiftTime::parse_hhmm("2460").is_err());
+        assert!(UtcOffset::parse("+2400").is_err());
+        assert!(UtcOffset::parse("+01").is_err());
+        assert!(LocalDateTime::parse_yyyymmddhhmmss("202301021530").is_err());
+        assert!(LocalDateTime::parse_yymmddhhmmss("2301021530001").is_err());
+        assert!(LocalDateTime::parse_yymmddhhmmss("230102153060").is_err());
+    }
+}
@@ -0,0 +1,313 @@
+use std::fmt;
+use std::io;
+use std::io::ErrorKind::InvalidData;
+use std::str::FromStr;
+
+/// Structure of the BBAN per country following the IBAN registry, a list of
+/// `<length><type>` groups where the type is n (digits), a (upper case letters) or
+/// c (upper case alphanumeric). The IBAN length is 4 plus the length of the BBAN.
+static BBAN_STRUCTURES: [(&str, &str); 72] = [
+    ("AD", "4n4n12c"),
+    ("AE", "3n16n"),
+    ("AL", "8n16c"),
+    ("AT", "5n11n"),
+    ("AZ", "4a20c"),
+    ("BA", "3n3n8n2n"),
+    ("BE", "3n7n2n"),
+    ("BG", "4a4n2n8c"),
+    ("BH", "4a14c"),
+    ("BR", "8n5n10n1a1c"),
+    ("BY", "4c4n16c"),
+    ("CH", "5n12c"),
+    ("CR", "4n14n"),
+    ("CY", "3n5n16c"),
+    ("CZ", "4n6n10n"),
+    ("DE", "8n10n"),
+    ("DK", "4n9n1n"),
+    ("DO", "4c20n"),
+    ("EE", "2n2n11n1n"),
+    ("EG", "4n4n17n"),
+    ("ES", "4n4n1n1n10n"),
+    ("FI", "3n11n"),
+    ("FO", "4n9n1n"),
+    ("FR", "5n5n11c2n"),
+    ("GB", "4a6n8n"),
+    ("GE", "2a16n"),
+    ("GI", "4a15c"),
+    ("GL", "4n9n1n"),
+    ("GR", "3n4n16c"),
+    ("GT", "4c20c"),
+    ("HR", "7n10n"),
+    ("HU", "3n4n1n15n1n"),
+    ("IE", "4a6n8n"),
+    ("IL", "3n3n13n"),
+    ("IQ", "4a3n12n"),
+    ("IS", "4n2n6n10n"),
+    ("IT", "1a5n5n12c"),
+    ("JO", "4a4n18c"),
+    ("KW", "4a22c"),
+    ("KZ", "3n13c"),
+    ("LB", "4n20c"),
+    ("LC", "4a24c"),
+    ("LI", "5n12c"),
+    ("LT", "5n11n"),
+    ("LU", "3n13c"),
+    ("LV", "4a13c"),
+    ("MC", "5n5n11c2n"),
+    ("MD", "2c18c"),
+    ("ME", "3n13n2n"),
+    ("MK", "3n10c2n"),
+    ("MR", "5n5n11n2n"),
+    ("MT", "4a5n18c"),
+    ("MU", "4a2n2n12n3n3a"),
+    ("NL", "4a10n"),
+    ("NO", "4n6n1n"),
+    ("PK", "4a16c"),
+    ("PL", "8n16n"),
+    ("PS", "4a21c"),
+    ("PT", "4n4n11n2n"),
+    ("QA", "4a21c"),
+    ("RO", "4a16c"),
+    ("RS", "3n13n2n"),
+    ("SA", "2n18c"),
+    ("SE", "3n16n1n"),
+    ("SI", "5n8n2n"),
+    ("SK", "4n6n10n"),
+    ("SM", "1a5n5n12c"),
+    ("TN", "2n3n13n2n"),
+    ("TR", "5n1n16c"),
+    ("UA", "6n19c"),
+    ("VA", "3n15n"),
+    ("XK", "4n10n2n"),
+];
+
+/// International bank account number in electronic format (no spaces)
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Iban {
+    value: String,
+}
+
+impl Iban {
+    /// Parses and validates an IBAN in electronic or print format (groups separated by spaces):
+    /// the country must be part of the IBAN registry, the BBAN must match the structure of the
+    /// country and the check digits must satisfy the mod 97 check of ISO 7064.
+    pub fn parse(value: &str) -> Result<Iban, io::Error> {
+        let value: String = value.chars().filter(|c| *c != ' ').collect();
+        if value.len() < 5 || !value.is_ascii() {
+            return Err(io::Error::new(
+                InvalidData,
+                format!("IBAN is too short: {}", value),
+            ));
+        }
+
+        let structure = bban_structure(&value[0..2]).ok_or_else(|| {
+            io::Error::new(
+                InvalidData,
+                format!("Country {} does not use IBANs", &value[0..2]),
+            )
+        })?;
+
+        if !value[2..4].chars().all(|c| c.is_ascii_digit()) {
+            return Err(io::Error::new(
+                InvalidData,
+                format!("Check digits of IBAN {} are not numeric", value),
+            ));
+        }
+
+        check_bban(&value[4..], structure)
+            .map_err(|e| io::Error::new(InvalidData, format!("Invalid IBAN {}: {}", value, e)))?;
+
+        if mod97(&value[4..], &value[0..4]) != 1 {
+            return Err(io::Error::new(
+                InvalidData,
+                format!("Check digits of IBAN {} are wrong", value),
+            ));
+        }
+
+        return Ok(Iban { value });
+    }
+
+    /// Builds the IBAN for a domestic account number, calculating the check digits
+    pub fn from_bban(country_code: &str, bban: &str) -> Result<Iban, io::Error> {
+        let check_digits = 98 - mod97(bban, &format!("{}00", country_code));
+        return Iban::parse(&format!("{}{:02}{}", country_code, check_digits, bban));
+    }
+
+    /// Whether the value starts like an IBAN of a registered country, i.e. whether it should
+    /// be validated as an IBAN when found in an account line
+    pub fn looks_like_iban(value: &str) -> bool {
+        let chars: Vec<char> = value.chars().take(4).collect();
+        return chars.len() == 4
+            && chars[0].is_ascii_uppercase()
+            && chars[1].is_ascii_uppercase()
+            && chars[2].is_ascii_digit()
+            && chars[3].is_ascii_digit()
+            && bban_structure(&value[0..2]).is_some();
+    }
+
+    pub fn country_code(&self) -> &str {
+        return &self.value[0..2];
+    }
+
+    pub fn check_digits(&self) -> &str {
+        return &self.value[2..4];
+    }
+
+    pub fn bban(&self) -> &str {
+        return &self.value[4..];
+    }
+
+    /// The IBAN in groups of four characters separated by spaces
+    pub fn to_print_format(&self) -> String {
+        let chars: Vec<char> = self.value.chars().collect();
+        return chars
+            .chunks(4)
+            .map(|c| c.iter().collect::<String>())
+            .collect::<Vec<String>>()
+            .join(" ");
+    }
+
+    pub fn as_str(&self) -> &str {
+        return &self.value;
+    }
+}
+
+impl FromStr for Iban {
+    type Err = io::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        return Iban::parse(s);
+    }
+}
+
+impl fmt::Display for Iban {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        return write!(f, "{}", self.value);
+    }
+}
+
+impl PartialEq<&str> for Iban {
+    fn eq(&self, other: &&str) -> bool {
+        return self.value == *other;
+    }
+}
+
+/// Structure of the BBAN for the country (see `BBAN_STRUCTURES`)
+pub fn bban_structure(country_code: &str) -> Option<&'static str> {
+    return BBAN_STRUCTURES
+        .iter()
+        .find(|(country, _)| *country == country_code)
+        .map(|(_, structure)| *structure);
+}
+
+fn check_bban(bban: &str, structure: &str) -> Result<(), String> {
+    let mut position = 0;
+    let mut length = 0;
+
+    for c in structure.chars() {
+        if let Some(digit) = c.to_digit(10) {
+            length = length * 10 + digit as usize;
+            continue;
+        }
+
+        let part = bban
+            .get(position..position + length)
+            .ok_or_else(|| format!("BBAN must have {} characters", structure_length(structure)))?;
+        let valid = match c {
+            'n' => part.chars().all(|c| c.is_ascii_digit()),
+            'a' => part.chars().all(|c| c.is_ascii_uppercase()),
+            _ => part
+                .chars()
+                .all(|c| c.is_ascii_digit() || c.is_ascii_uppercase()),
+        };
+
+        if !valid {
+            return Err(format!(
+                "BBAN characters {} to {} must be of type {}",
+                position + 1,
+                position + length,
+                c
+            ));
+        }
+
+        position += length;
+        length = 0;
+    }
+
+    if position != bban.len() {
+        return Err(format!("BBAN must have {} characters", position));
+    }
+
+    return Ok(());
+}
+
+fn structure_length(structure: &str) -> usize {
+    return structure
+        .split(|c: char| c.is_ascii_alphabetic())
+        .filter_map(|n| n.parse::<usize>().ok())
+        .sum();
+}
+
+/// Remainder of the number formed by `bban` followed by `prefix` (letters replaced by 10..35)
+/// divided by 97, calculated piecewise to avoid big integers
+fn mod97(bban: &str, prefix: &str) -> u32 {
+    let mut remainder: u32 = 0;
+
+    for c in bban.chars().chain(prefix.chars()) {
+        let value = c.to_digit(36).unwrap_or(0);
+        remainder = if value >= 10 {
+            (remainder * 100 + value) % 97
+        } else {
+            (remainder * 10 + value) % 97
+        };
+    }
+
+    return remainder;
+}
+
+serde_as_string!(Iban);
+
+#[cfg(test)]
+mod tests {
+    use crate::swift::iban::Iban;
+
+    #[test]
+    fn positive_tests() {
+        let iban = Iban::parse("DE89370400440532013000").unwrap();
+        assert_eq!(iban.country_code(), "DE");
+        assert_eq!(iban.check_digits(), "89");
+        assert_eq!(iban.bban(), "370400440532013000");
+        assert_eq!(iban.to_print_format(), "DE89 3704 0044 0532 0130 00");
+
+        assert_eq!(
+            Iban::parse("GB29 NWBK 6016 1331 9268 19").unwrap(),
+            "GB29NWBK60161331926819"
+        );
+        assert!(Iban::parse("FR1420041010050500013M02606").is_ok());
+        assert!(Iban::parse("CH9300762011623852957").is_ok());
+
+        assert_eq!(
+            Iban::from_bban("DE", "370400440532013000").unwrap(),
+            "DE89370400440532013000"
+        );
+        assert_eq!(
+            Iban::from_bban("GB", "NWBK60161331926819").unwrap(),
+            "GB29NWBK60161331926819"
+        );
+
+        assert!(Iban::looks_like_iban("DE89370400440532013000"));
+        assert!(!Iban::looks_like_iban("12345678"));
+        assert!(!Iban::looks_like_iban("US12345678"));
+    }
+
+    #[test]
+    fn negative_tests() {
+        assert!(Iban::parse("DE88370400440532013000").is_err());
+        assert!(Iban::parse("DE8937040044053201300").is_err());
+        assert!(Iban::parse("DE893704004405320130001").is_err());
+        assert!(Iban::parse("GB29NWBK6016133192681A").is_err());
+        assert!(Iban::parse("US12345678901234").is_err());
+        assert!(Iban::parse("DE").is_err());
+        assert!(Iban::parse("DEXX370400440532013000").is_err());
+    }
+}
@@ -1 +1,12 @@
-pub mod mt;
\ No newline at end of file
+pub mod mt;
+pub mod charset;
+pub mod bic;
+pub mod bic_directory;
+pub mod iban;
+pub mod currency;
+pub mod amount;
+pub mod datetime;
+pub mod screening;
+mod country;
+
+pub use country::is_valid_country_code;
\ No newline at end of file
@@ -0,0 +1,147 @@
+use crate::swift::datetime::{LocalDateTime, SwiftDate, SwiftTime};
+use crate::swift::mt::header_fields::Mir;
+use crate::swift::mt::model::ServiceIdentifier;
+use crate::swift::mt::swift_mt_parser::SwiftMtMessage;
+use std::io;
+use std::io::ErrorKind::InvalidData;
+
+/// Positive (ACK) or negative (NAK) acknowledgement of a message by FIN, i.e. a message with
+/// service identifier 21. The acknowledged message identifies itself by the MIR, which is
+/// made up of the date of field 177 and the address, session and sequence number of the
+/// basic header.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Acknowledgement {
+    pub accepted: bool,
+    /// Error code of field 405 of a NAK, e.g. `T13` optionally followed by the line number
+    pub error_code: Option<String>,
+    pub mir: Mir,
+    /// Date and time of field 177, in the local time of the sender
+    pub date_time: LocalDateTime,
+    pub message_user_reference: Option<String>,
+    /// The acknowledged message, if it is appended to the acknowledgement
+    pub original_message: Option<SwiftMtMessage>,
+}
+
+impl Acknowledgement {
+    pub fn from_message(message: SwiftMtMessage) -> Result<Acknowledgement, io::Error> {
+        if message.basic_header.service_identifier != ServiceIdentifier::MessageAck {
+            return Err(io::Error::new(
+                InvalidData,
+                format!(
+                    "Expected service identifier 21 but got {:?}",
+                    message.basic_header.service_identifier
+                ),
+            ));
+        }
+
+        let field = |tag: &str| message.text.field(tag).map(|f| f.value.clone());
+        let date_time = field("177").ok_or_else(|| {
+            io::Error::new(InvalidData, "Acknowledgement has no date and time (177)")
+        })?;
+        if date_time.len() != 10 || !date_time.is_ascii() {
+            return Err(io::Error::new(
+                InvalidData,
+                format!("Expected YYMMDDHHMM in field 177 but got {}", date_time),
+            ));
+        }
+        let date = SwiftDate::parse_yymmdd(&date_time[0..6])?;
+        let time = SwiftTime::parse_hhmm(&date_time[6..10])?;
+
+        let accepted = match field("451").as_deref() {
+            Some("0") => true,
+            Some("1") => false,
+            other => {
+                return Err(io::Error::new(
+                    InvalidData,
+                    format!("Expected 0 or 1 in field 451 but got {:?}", other),
+                ))
+            }
+        };
+
+        return Ok(Acknowledgement {
+            accepted,
+            error_code: field("405"),
+            mir: Mir {
+                date,
+                logical_terminal: message.basic_header.logical_terminal.clone(),
+                session_number: message.basic_header.session_number,
+                sequence_number: message.basic_header.sequence_number,
+            },
+            date_time: LocalDateTime::from_parts(date, time),
+            message_user_reference: field("108"),
+            original_message: message.original_message.map(|m| *m),
+        });
+    }
+
+    pub fn is_ack(&self) -> bool {
+        return self.accepted;
+    }
+
+    pub fn is_nak(&self) -> bool {
+        return !self.accepted;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::swift::mt::acknowledgement::Acknowledgement;
+    use crate::swift::mt::SwiftMtParser;
+
+    static ORIGINAL: &str = "{1:F01FOOBARXXAXXX0123000456}{2:I103BANKDEFFXXXXN}{3:{108:MUR123}}{4:\r\n:20:REF123\r\n:23B:CRED\r\n:32A:230102EUR1000,\r\n-}{5:{CHK:123456789ABC}}";
+
+    fn parse(ack: &str) -> Acknowledgement {
+        let message = SwiftMtParser::new().parse(ack.to_string()).unwrap();
+        return Acknowledgement::from_message(message).unwrap();
+    }
+
+    #[test]
+    fn positive_tests() {
+        let ack = parse(&format!(
+            "{{1:F21FOOBARXXAXXX0123000456}}{{4:{{177:2301021530}}{{451:0}}{{108:MUR123}}}}{}",
+            ORIGINAL
+        ));
+        assert!(ack.is_ack());
+        assert!(ack.error_code.is_none());
+        assert_eq!(ack.mir.to_string(), "230102FOOBARXXAXXX0123000456");
+        assert_eq!(ack.date_time.time().to_hhmm(), "1530");
+        assert_eq!(ack.message_user_reference.as_deref(), Some("MUR123"));
+
+        let original = ack.original_message.unwrap();
+        assert_eq!(original.application_header.message_type(), Some("103"));
+        assert_eq!(original.text.field("20").unwrap().value, "REF123");
+        assert_eq!(original.trailer.chk.as_deref(), Some("123456789ABC"));
+
+        let nak = parse(&format!(
+            "{{1:F21FOOBARXXAXXX0123000456}}{{4:{{177:2301021530}}{{451:1}}{{405:T13}}}}{}",
+            ORIGINAL
+        ));
+        assert!(nak.is_nak());
+        assert_eq!(nak.error_code.as_deref(), Some("T13"));
+        assert!(nak.original_message.is_some());
+
+        let ack = parse("{1:F21FOOBARXXAXXX0123000456}{4:{177:2301021530}{451:0}}");
+        assert!(ack.original_message.is_none());
+    }
+
+    #[test]
+    fn negative_tests() {
+        let parser = SwiftMtParser::new();
+        let message = parser.parse(ORIGINAL.to_string()).unwrap();
+        assert!(Acknowledgement::from_message(message).is_err());
+
+        let message = SwiftMtParser::new()
+            .parse("{1:F21FOOBARXXAXXX0123000456}{4:{177:2301021530}{451:2}}".to_string())
+            .unwrap();
+        assert!(Acknowledgement::from_message(message).is_err());
+
+        let message = SwiftMtParser::new()
+            .parse("{1:F21FOOBARXXAXXX0123000456}{4:{451:0}}".to_string())
+            .unwrap();
+        assert!(Acknowledgement::from_message(message).is_err());
+
+        assert!(SwiftMtParser::new()
+            .parse(format!("{}{}", ORIGINAL, ORIGINAL))
+            .is_err());
+    }
+}
@@ -0,0 +1,731 @@
+use crate::swift::bic::{Bic, LogicalTerminalAddress};
+use crate::swift::iban::{bban_structure, Iban};
+use crate::swift::mt::fields::PartyField;
+use crate::swift::mt::model::ApplicationHeader;
+use crate::swift::mt::swift_mt_parser::SwiftMtMessage;
+use crate::swift::mt::text::{tag_matches, Field};
+use crate::swift::mt::trailer_fields::set_checksum;
+use sha2::{Digest, Sha256};
+
+/// Fields carrying personal data: ordering and beneficiary customer, the account of statements
+/// and the narrative fields with remittance and regulatory information
+static PERSONAL_FIELDS: [&str; 8] = ["50a", "59a", "25", "70", "72", "77B", "79", "86"];
+
+static FIRST_NAMES: [&str; 16] = [
+    "ANNA", "BEN", "CLARA", "DAVID", "EMMA", "FELIX", "GRACE", "HENRY", "IDA", "JONAS", "KAREN",
+    "LUCAS", "MARIA", "NOAH", "OLIVIA", "PAUL",
+];
+
+static LAST_NAMES: [&str; 16] = [
+    "ADLER", "BAKER", "CARTER", "DIAZ", "EVANS", "FISCHER", "GARCIA", "HANSEN", "IVERSEN",
+    "JANSEN", "KELLER", "LARSEN", "MEYER", "NOVAK", "OLSEN", "PETERS",
+];
+
+static STREETS: [&str; 8] = [
+    "MAIN STREET",
+    "HIGH STREET",
+    "STATION ROAD",
+    "PARK AVENUE",
+    "MILL LANE",
+    "CHURCH STREET",
+    "GARDEN ROAD",
+    "MARKET SQUARE",
+];
+
+static TOWNS: [&str; 8] = [
+    "ANYTOWN",
+    "NORTHVILLE",
+    "SOUTHBURY",
+    "EASTON",
+    "WESTFIELD",
+    "LAKESIDE",
+    "HILLCREST",
+    "RIVERDALE",
+];
+
+/// Maximum length of a line of the party and narrative fields
+static LINE_LENGTH: usize = 35;
+
+/// Replaces personal data in messages by fake values, e.g. to share production messages with
+/// vendors and test teams.
+///
+/// The fake values are derived from a hash of the secret and the original value, so the same
+/// name, account or word is always replaced by the same fake value and messages stay related to
+/// each other. Without a secret, fake values of known originals can be recomputed by anyone.
+///
+/// Replacements are format valid: names and addresses fit the line length, code words like
+/// `/INV/` or `/BNF/` and the numbers and country codes of option F lines are kept, and fake
+/// IBANs have the structure of their country and valid check digits (national check digits
+/// within the BBAN are not recalculated). BICs and amounts are kept by default. A CHK trailer
+/// is recalculated for the changed content.
+#[derive(Debug, Clone)]
+pub struct Anonymiser {
+    secret: String,
+    fields: Vec<String>,
+    keep_bics: bool,
+    keep_amounts: bool,
+    preserve_structure: bool,
+}
+
+impl Default for Anonymiser {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Anonymiser {
+    /// Anonymiser for the fields 50a, 59a, 25, 70, 72, 77B, 79 and 86 without a secret
+    pub fn new() -> Anonymiser {
+        return Anonymiser {
+            secret: String::new(),
+            fields: PERSONAL_FIELDS.iter().map(|t| t.to_string()).collect(),
+            keep_bics: true,
+            keep_amounts: true,
+            preserve_structure: false,
+        };
+    }
+
+    /// Secret mixed into the hash of the original values, so fake values cannot be matched
+    /// with guessed originals by someone who does not know it
+    pub fn with_secret(mut self, secret: &str) -> Anonymiser {
+        self.secret = secret.into();
+        return self;
+    }
+
+    /// Also anonymises the field or tag pattern like `53a`, e.g. field 21 if references
+    /// contain customer data
+    pub fn with_field(mut self, tag: &str) -> Anonymiser {
+        self.fields.push(tag.into());
+        return self;
+    }
+
+    /// Keeps the field or tag pattern that would be anonymised by default
+    pub fn without_field(mut self, tag: &str) -> Anonymiser {
+        self.fields.retain(|t| t != tag);
+        return self;
+    }
+
+    /// Replaces the BICs of the headers and of the option A, C and G fields by fake BICs of
+    /// the same country
+    pub fn with_bics_replaced(mut self) -> Anonymiser {
+        self.keep_bics = false;
+        return self;
+    }
+
+    /// Replaces the amounts of the amount and balance fields by fake amounts with the same
+    /// number of digits. Sums and exchange rates are not recalculated.
+    pub fn with_amounts_replaced(mut self) -> Anonymiser {
+        self.keep_amounts = false;
+        return self;
+    }
+
+    /// Replaces names and addresses character by character instead of by fake names and
+    /// addresses, so every line keeps its length and its mix of letters and digits
+    pub fn with_structure_preserved(mut self) -> Anonymiser {
+        self.preserve_structure = true;
+        return self;
+    }
+
+    /// Copy of the message with the personal data replaced, including the original message
+    /// appended to an acknowledgement
+    pub fn anonymise(&self, message: &SwiftMtMessage) -> SwiftMtMessage {
+        let mut anonymised = message.clone();
+        for field in anonymised.text.fields.iter_mut() {
+            field.value = self.anonymise_field(field);
+        }
+
+        if !self.keep_bics {
+            let header = &mut anonymised.basic_header;
+            header.logical_terminal = self.fake_logical_terminal(&header.logical_terminal);
+            match &mut anonymised.application_header {
+                ApplicationHeader::Input { data } => {
+                    data.destination = self.fake_logical_terminal(&data.destination)
+                }
+                ApplicationHeader::Output { data } => {
+                    data.sender_address = self.fake_logical_terminal(&data.sender_address)
+                }
+                ApplicationHeader::Empty => {}
+            }
+            if let Some(mir) = &mut anonymised.user_header.mir {
+                mir.logical_terminal = self.fake_logical_terminal(&mir.logical_terminal);
+            }
+        }
+
+        if anonymised.trailer.chk.is_some() {
+            set_checksum(&mut anonymised);
+        }
+
+        anonymised.original_message = message
+            .original_message
+            .as_ref()
+            .map(|original| Box::new(self.anonymise(original)));
+        return anonymised;
+    }
+
+    fn anonymise_field(&self, field: &Field) -> String {
+        if self
+            .fields
+            .iter()
+            .any(|pattern| tag_matches(pattern, &field.tag))
+        {
+            if PartyField::is_party_field(&field.tag) {
+                return self.anonymise_party(field);
+            }
+            if field.tag == "25" {
+                return self.anonymise_account(&field.value);
+            }
+            return self.anonymise_narrative(&field.value);
+        }
+
+        if !self.keep_bics && PartyField::has_identifier_code(&field.tag) {
+            return join_lines(field.lines().iter().map(|line| {
+                if line.starts_with('/') {
+                    line.to_string()
+                } else {
+                    self.anonymise_bic(line)
+                }
+            }));
+        }
+
+        if let Some(start) = amount_start(&field.tag).filter(|_| !self.keep_amounts) {
+            if let Some(amount) = field.value.get(start..) {
+                return format!("{}{}", &field.value[..start], self.fake_amount(amount));
+            }
+        }
+
+        return field.value.clone();
+    }
+
+    /// Replaces the account line, the name and address lines, the numbered lines of option F
+    /// and, if BICs are replaced, the identifier code of the options with one
+    fn anonymise_party(&self, field: &Field) -> String {
+        let option = field.tag.chars().nth(2);
+        let mut position = 0;
+
+        let lines = field.lines().into_iter().enumerate().map(|(index, line)| {
+            if index == 0 && line.starts_with('/') {
+                return self.anonymise_account_line(line);
+            }
+            if index == 0 && field.tag == "50F" {
+                return self.mask_segments(line, 2);
+            }
+
+            position += 1;
+            return match option {
+                _ if PartyField::has_identifier_code(&field.tag) => self.anonymise_bic(line),
+                Some('F') => self.anonymise_numbered_line(line, position),
+                None | Some('D') | Some('H') | Some('K') => match position {
+                    1 => self.fake_name(line, LINE_LENGTH),
+                    2 => self.fake_street(line, LINE_LENGTH),
+                    _ => self.fake_town_line(line),
+                },
+                _ => self.mask(line),
+            };
+        });
+
+        return join_lines(lines);
+    }
+
+    /// Numbered line of option F: name (1), address (2), country and town (3), date (4) and
+    /// place (5) of birth, identification (6, 7) and continuation (8)
+    fn anonymise_numbered_line(&self, line: &str, position: usize) -> String {
+        let (number, content) = match line.split_once('/') {
+            Some(parts) => parts,
+            None => return self.mask(line),
+        };
+        let length = LINE_LENGTH - number.len() - 1;
+
+        let content = match number {
+            "1" if position == 1 => self.fake_name(content, length),
+            "2" => self.fake_street(content, length),
+            "3" | "5" => match content.split_once('/') {
+                Some((country, town)) => {
+                    format!("{}/{}", country, self.fake_town(town, length - 3))
+                }
+                None => content.to_string(),
+            },
+            "4" => self.fake_date(content),
+            "6" | "7" => self.mask_segments(content, 1),
+            _ => self.mask(content),
+        };
+
+        return format!("{}/{}", number, content);
+    }
+
+    /// Account line with the optional debit/credit mark of the party fields
+    fn anonymise_account_line(&self, line: &str) -> String {
+        let prefix = ["/C/", "/D/"]
+            .iter()
+            .find(|mark| line.starts_with(**mark) && line.len() > 3)
+            .map(|mark| mark.len())
+            .unwrap_or(1);
+
+        return format!(
+            "{}{}",
+            &line[..prefix],
+            self.anonymise_account(&line[prefix..])
+        );
+    }
+
+    fn anonymise_account(&self, account: &str) -> String {
+        return match Iban::parse(account) {
+            Ok(iban) if Iban::looks_like_iban(account) => self.fake_iban(&iban).to_string(),
+            _ => self.mask(account),
+        };
+    }
+
+    /// Narrative lines with the code words at the start of the line kept
+    fn anonymise_narrative(&self, value: &str) -> String {
+        return join_lines(value.split("\r\n").map(|line| {
+            let (code, text) = line.split_at(code_length(line));
+            format!("{}{}", code, self.mask(text))
+        }));
+    }
+
+    fn anonymise_bic(&self, line: &str) -> String {
+        if self.keep_bics {
+            return line.to_string();
+        }
+
+        return match Bic::parse(line) {
+            Ok(bic) => self.fake_bic(&bic).to_string(),
+            Err(_) => self.mask(line),
+        };
+    }
+
+    fn fake_name(&self, name: &str, length: usize) -> String {
+        if self.preserve_structure {
+            return self.mask(name);
+        }
+
+        let mut choices = self.choices("name", name);
+        let first_name = choices.pick(&FIRST_NAMES);
+        let last_name = choices.pick(&LAST_NAMES);
+        return fit(format!("{} {}", first_name, last_name), length);
+    }
+
+    fn fake_street(&self, street: &str, length: usize) -> String {
+        if self.preserve_structure {
+            return self.mask(street);
+        }
+
+        let mut choices = self.choices("street", street);
+        let number = 1 + choices.next(199);
+        return fit(format!("{} {}", number, choices.pick(&STREETS)), length);
+    }
+
+    fn fake_town(&self, town: &str, length: usize) -> String {
+        if self.preserve_structure {
+            return self.mask(town);
+        }
+
+        return fit(self.choices("town", town).pick(&TOWNS).to_string(), length);
+    }
+
+    /// Address line after the street, usually post code and town
+    fn fake_town_line(&self, line: &str) -> String {
+        if self.preserve_structure {
+            return self.mask(line);
+        }
+
+        let mut choices = self.choices("town", line);
+        let post_code = 10000 + choices.next(90000);
+        return format!("{} {}", post_code, choices.pick(&TOWNS));
+    }
+
+    /// Date of birth `YYYYMMDD` between 1940 and 1999
+    fn fake_date(&self, date: &str) -> String {
+        if date.len() != 8 || !date.chars().all(|c| c.is_ascii_digit()) {
+            return self.mask(date);
+        }
+
+        let mut choices = self.choices("date", date);
+        let year = 1940 + choices.next(60);
+        let month = 1 + choices.next(12);
+        let day = 1 + choices.next(28);
+        return format!("{:04}{:02}{:02}", year, month, day);
+    }
+
+    /// IBAN of the same country with a random BBAN of the country's structure
+    fn fake_iban(&self, iban: &Iban) -> Iban {
+        let mut choices = self.choices("IBAN", iban.as_str());
+        let structure = bban_structure(iban.country_code()).unwrap_or("");
+        let mut bban = String::new();
+        let mut length = 0;
+
+        for c in structure.chars() {
+            if let Some(digit) = c.to_digit(10) {
+                length = length * 10 + digit as usize;
+                continue;
+            }
+
+            for _ in 0..length {
+                bban.push(match c {
+                    'n' => choices.digit(),
+                    'a' => choices.letter(),
+                    _ => choices.alphanumeric(),
+                });
+            }
+            length = 0;
+        }
+
+        return Iban::from_bban(iban.country_code(), &bban)
+            .expect("Fake BBAN does not match the structure of the country");
+    }
+
+    /// BIC of the same country with a fake institution code and location code (keeping the
+    /// second character, which marks test and passive participants). The branch is kept if it
+    /// is the primary office.
+    fn fake_bic(&self, bic: &Bic) -> Bic {
+        let mut choices = self.choices("BIC", &bic.as_str()[0..8]);
+        let institution: String = (0..4).map(|_| choices.letter()).collect();
+        let mut value = format!(
+            "{}{}{}{}",
+            institution,
+            bic.country_code(),
+            choices.letter(),
+            &bic.location_code()[1..]
+        );
+
+        match bic.branch_code() {
+            Some("XXX") => value.push_str("XXX"),
+            Some(_) => {
+                let mut choices = self.choices("branch", bic.as_str());
+                value.push((b'A' + choices.next(23) as u8) as char);
+                value.push(choices.alphanumeric());
+                value.push(choices.alphanumeric());
+            }
+            None => {}
+        }
+
+        return Bic::parse(&value).expect("Fake BIC is invalid");
+    }
+
+    fn fake_logical_terminal(&self, address: &LogicalTerminalAddress) -> LogicalTerminalAddress {
+        if address.is_empty() {
+            return address.clone();
+        }
+
+        return self
+            .fake_bic(&address.bic11())
+            .to_logical_terminal(address.terminal_code());
+    }
+
+    /// Amount in the `15d` format with the same number of digits before and after the comma
+    fn fake_amount(&self, amount: &str) -> String {
+        if !amount.chars().all(|c| c.is_ascii_digit() || c == ',') {
+            return amount.to_string();
+        }
+
+        let mut choices = self.choices("amount", amount);
+        return amount
+            .chars()
+            .enumerate()
+            .map(|(index, c)| match c {
+                '0' if index == 0 => '0',
+                ',' => ',',
+                _ if index == 0 => (b'1' + choices.next(9) as u8) as char,
+                _ => choices.digit(),
+            })
+            .collect();
+    }
+
+    /// Keeps the first segments of a value separated by slashes, e.g. the code and country of
+    /// the party identifier `CUST/DE/ABC/1234`, and masks the others
+    fn mask_segments(&self, value: &str, kept: usize) -> String {
+        return value
+            .split('/')
+            .enumerate()
+            .map(|(index, segment)| {
+                if index < kept {
+                    segment.to_string()
+                } else {
+                    self.mask(segment)
+                }
+            })
+            .collect::<Vec<String>>()
+            .join("/");
+    }
+
+    /// Replaces every letter by an ASCII letter of the same case and every digit by a digit,
+    /// word by word, so the same word is always masked the same way. Letters outside of ASCII
+    /// like the umlauts of `MÜLLER` are part of the word and are replaced as well.
+    fn mask(&self, text: &str) -> String {
+        let mut masked = String::new();
+        let mut word = String::new();
+
+        for c in text.chars() {
+            if c.is_alphanumeric() {
+                word.push(c);
+                continue;
+            }
+
+            masked.push_str(&self.mask_word(&word));
+            masked.push(c);
+            word.clear();
+        }
+
+        masked.push_str(&self.mask_word(&word));
+        return masked;
+    }
+
+    fn mask_word(&self, word: &str) -> String {
+        let mut choices = self.choices("word", word);
+        return word
+            .chars()
+            .map(|c| {
+                if c.is_numeric() {
+                    choices.digit()
+                } else if c.is_lowercase() {
+                    choices.letter().to_ascii_lowercase()
+                } else {
+                    choices.letter()
+                }
+            })
+            .collect();
+    }
+
+    fn choices(&self, kind: &str, value: &str) -> Choices {
+        let mut hasher = Sha256::new();
+        for part in [self.secret.as_str(), kind, value] {
+            hasher.update((part.len() as u64).to_le_bytes());
+            hasher.update(part.as_bytes());
+        }
+
+        let mut hash = [0u8; 32];
+        hash.copy_from_slice(&hasher.finalize());
+        return Choices { hash, position: 0 };
+    }
+}
+
+/// Deterministic sequence of pseudo-random choices read from a hash, which is hashed again
+/// when all its bytes are used
+struct Choices {
+    hash: [u8; 32],
+    position: usize,
+}
+
+impl Choices {
+    /// Number below the bound
+    fn next(&mut self, bound: usize) -> usize {
+        if self.position + 2 > self.hash.len() {
+            let hash = Sha256::digest(self.hash);
+            self.hash.copy_from_slice(&hash);
+            self.position = 0;
+        }
+
+        let value = u16::from_be_bytes([self.hash[self.position], self.hash[self.position + 1]]);
+        self.position += 2;
+        return value as usize % bound;
+    }
+
+    fn digit(&mut self) -> char {
+        return (b'0' + self.next(10) as u8) as char;
+    }
+
+    fn letter(&mut self) -> char {
+        return (b'A' + self.next(26) as u8) as char;
+    }
+
+    fn alphanumeric(&mut self) -> char {
+        let value = self.next(36) as u8;
+        return if value < 10 {
+            (b'0' + value) as char
+        } else {
+            (b'A' + value - 10) as char
+        };
+    }
+
+    fn pick<'a>(&mut self, values: &[&'a str]) -> &'a str {
+        return values[self.next(values.len())];
+    }
+}
+
+
+/// Start of the amount in the amount fields and the balance fields of statements
+fn amount_start(tag: &str) -> Option<usize> {
+    return match tag {
+        "32B" | "33B" | "71F" | "71G" => Some(3),
+        "32A" | "32C" | "32D" => Some(9),
+        "60F" | "60M" | "62F" | "62M" | "64" | "65" => Some(10),
+        _ => None,
+    };
+}
+
+/// Length of the code words at the start of a narrative line: a continuation `//` or a code
+/// like `/INV/`, optionally followed by a country code as in `/ORDERRES/DE//`
+fn code_length(line: &str) -> usize {
+    if line.starts_with("//") {
+        return 2;
+    }
+
+    let code = match line.strip_prefix('/').and_then(|rest| rest.find('/')) {
+        Some(end) if (1..=8).contains(&end) => &line[1..end + 1],
+        _ => return 0,
+    };
+    if !code.chars().all(|c| c.is_ascii_uppercase()) {
+        return 0;
+    }
+
+    let length = code.len() + 2;
+    let country = &line[length..];
+    if country
+        .get(..2)
+        .is_some_and(|c| c.chars().all(|c| c.is_ascii_uppercase()))
+        && country.get(2..4) == Some("//")
+    {
+        return length + 4;
+    }
+
+    return length;
+}
+
+/// Shortens the value to the length without leaving a trailing space
+fn fit(value: String, length: usize) -> String {
+    return value
+        .chars()
+        .take(length)
+        .collect::<String>()
+        .trim_end()
+        .to_string();
+}
+
+fn join_lines<I: Iterator<Item = String>>(lines: I) -> String {
+    return lines.collect::<Vec<String>>().join("\r\n");
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::swift::iban::Iban;
+    use crate::swift::mt::anonymiser::Anonymiser;
+    use crate::swift::mt::{verify_checksum, SwiftMtMessage, SwiftMtParser, Validator};
+
+    fn parse(text: &str) -> SwiftMtMessage {
+        let raw = format!(
+            "{{1:F01FOOBARXXAXXX0123000456}}{{2:I103BANKDEFFXXXXN}}{{4:\r\n{}\r\n-}}{{5:{{CHK:123456789ABC}}}}",
+            text
+        );
+        return SwiftMtParser::new().parse(raw).unwrap();
+    }
+
+    fn field<'a>(message: &'a SwiftMtMessage, tag: &str) -> &'a str {
+        return &message.text.field(tag).unwrap().value;
+    }
+
+    #[test]
+    fn positive_tests() {
+        let message = parse(
+            ":20:REF123\r\n:23B:CRED\r\n:32A:230102EUR1234,56\r\n:50K:/DE89370400440532013000\r\nJOHN SMITH\r\nELM STREET 5\r\n12345 SPRINGFIELD\r\n:52A:BANKDEFF\r\n:59F:/GB29NWBK60161331926819\r\n1/JANE DOE\r\n2/OAK ROAD 7\r\n3/GB/LONDON\r\n4/19800312\r\n:70:/INV/4711 JOHN SMITH\r\n:71A:SHA\r\n:72:/ACC/CALL JOHN\r\n//SMITH",
+        );
+        let anonymiser = Anonymiser::new().with_secret("secret");
+        let anonymised = anonymiser.anonymise(&message);
+
+        assert_eq!(anonymiser.anonymise(&message), anonymised);
+        assert!(!anonymised.to_raw().contains("SMITH"));
+        assert!(!anonymised.to_raw().contains("DOE"));
+        assert!(Validator::new().validate(&anonymised).is_empty());
+        assert!(verify_checksum(&anonymised).is_ok());
+
+        assert_eq!(field(&anonymised, "20"), "REF123");
+        assert_eq!(field(&anonymised, "32A"), "230102EUR1234,56");
+        assert_eq!(field(&anonymised, "52A"), "BANKDEFF");
+        assert_eq!(
+            anonymised.basic_header.logical_terminal,
+            message.basic_header.logical_terminal
+        );
+
+        let ordering: Vec<&str> = field(&anonymised, "50K").split("\r\n").collect();
+        assert_eq!(ordering.len(), 4);
+        let iban = Iban::parse(&ordering[0][1..]).unwrap();
+        assert_eq!(iban.country_code(), "DE");
+        assert_ne!(iban, "DE89370400440532013000");
+
+        let beneficiary: Vec<&str> = field(&anonymised, "59F").split("\r\n").collect();
+        assert!(Iban::parse(&beneficiary[0][1..])
+            .unwrap()
+            .as_str()
+            .starts_with("GB"));
+        assert!(beneficiary[1].starts_with("1/"));
+        assert!(beneficiary[3].starts_with("3/GB/"));
+        assert_ne!(beneficiary[4], "4/19800312");
+
+        let remittance = field(&anonymised, "70");
+        assert!(remittance.starts_with("/INV/"));
+        assert_eq!(remittance.len(), field(&message, "70").len());
+        assert!(field(&anonymised, "72").starts_with("/ACC/"));
+        assert!(field(&anonymised, "72").contains("\r\n//"));
+
+        let other_secret = Anonymiser::new().with_secret("other").anonymise(&message);
+        assert_ne!(field(&other_secret, "50K"), field(&anonymised, "50K"));
+
+        let anonymised = Anonymiser::new()
+            .with_bics_replaced()
+            .with_amounts_replaced()
+            .with_structure_preserved()
+            .without_field("72")
+            .anonymise(&message);
+        assert!(Validator::new().validate(&anonymised).is_empty());
+        assert_eq!(field(&anonymised, "72"), field(&message, "72"));
+
+        let amount = field(&anonymised, "32A");
+        assert!(amount.starts_with("230102EUR"));
+        assert_ne!(amount, "230102EUR1234,56");
+        assert_eq!(amount.len(), 16);
+
+        let bic = field(&anonymised, "52A");
+        assert_eq!(&bic[4..6], "DE");
+        assert_ne!(bic, "BANKDEFF");
+        assert_ne!(
+            anonymised.basic_header.logical_terminal,
+            message.basic_header.logical_terminal
+        );
+
+        for (original, replaced) in field(&message, "50K")
+            .split("\r\n")
+            .zip(field(&anonymised, "50K").split("\r\n"))
+        {
+            assert_eq!(original.len(), replaced.len());
+            assert_ne!(original, replaced);
+        }
+    }
+
+    #[test]
+    fn negative_tests() {
+        let message =
+            parse(":20:REF123\r\n:50K:/12-345 6\r\nNAME\r\n:59:/DE00370400440532013000\r\nNAME");
+        let anonymised = Anonymiser::new().anonymise(&message);
+
+        let account = field(&anonymised, "50K").split("\r\n").next().unwrap();
+        assert_eq!(account.len(), "/12-345 6".len());
+        assert!(account.starts_with('/') && account.contains('-'));
+        assert_ne!(account, "/12-345 6");
+
+        let account = field(&anonymised, "59").split("\r\n").next().unwrap();
+        assert_ne!(account, "/DE00370400440532013000");
+        assert_eq!(account.len(), "/DE00370400440532013000".len());
+
+        // narratives outside of the character set are replaced without failing
+        let message = parse(":20:REF123\r\n:70:/INV/Aé/TEST\r\n/RFB/é\r\n:72:/ACC/ÄÖ//X");
+        let anonymised = Anonymiser::new().anonymise(&message);
+        assert!(field(&anonymised, "70").starts_with("/INV/"));
+        assert_ne!(field(&anonymised, "70"), field(&message, "70"));
+        assert!(field(&anonymised, "72").starts_with("/ACC/"));
+        for tag in ["70", "72"] {
+            let value = field(&anonymised, tag);
+            assert!(value.is_ascii(), "{}", value);
+        }
+
+        // letters outside of ASCII are masked with the rest of their word
+        let message = parse(":20:REF123\r\n:59:/DE00370400440532013000\r\nJOSÉ MÜLLER\r\nstraße 1");
+        let anonymised = Anonymiser::new()
+            .with_structure_preserved()
+            .anonymise(&message);
+        let lines: Vec<&str> = field(&anonymised, "59").split("\r\n").collect();
+        assert_eq!(lines[1].len(), "JOSE MULLER".len());
+        assert_eq!(lines[1].find(' '), Some(4));
+        assert!(lines[1].chars().all(|c| c == ' ' || c.is_ascii_uppercase()));
+        assert!(lines.iter().all(|line| line.is_ascii()));
+        assert_eq!(lines[2].len(), "straße 1".chars().count());
+        assert!(lines[2][..6].chars().all(|c| c.is_ascii_lowercase()));
+    }
+}
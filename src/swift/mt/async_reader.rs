@@ -0,0 +1,103 @@
+use crate::swift::mt::reader::{parse, MessageFramer, CHUNK_SIZE, MAX_MESSAGE_SIZE};
+use crate::swift::mt::swift_mt_parser::SwiftMtMessage;
+use std::io;
+use tokio::io::{AsyncRead, AsyncReadExt};
+
+/// Asynchronous version of `MessageReader` for tokio, e.g. for messages arriving on a socket.
+/// Messages are framed like the synchronous reader does and parsed by `SwiftMtParser`. Data
+/// is only read while the next message is awaited, so a slow consumer applies backpressure to
+/// the connection.
+pub struct AsyncMessageReader<R: AsyncRead + Unpin> {
+    reader: R,
+    framer: MessageFramer,
+    end_of_stream: bool,
+}
+
+impl<R: AsyncRead + Unpin> AsyncMessageReader<R> {
+    pub fn new(reader: R) -> AsyncMessageReader<R> {
+        return AsyncMessageReader {
+            reader,
+            framer: MessageFramer::new(MAX_MESSAGE_SIZE),
+            end_of_stream: false,
+        };
+    }
+
+    /// Limit for the size of a single message, see `MessageReader::with_max_message_size`
+    pub fn with_max_message_size(mut self, size: usize) -> AsyncMessageReader<R> {
+        self.framer.max_message_size = size;
+        return self;
+    }
+
+    /// Ends messages only at `$`, see `MessageReader::with_separator_required`
+    pub fn with_separator_required(mut self) -> AsyncMessageReader<R> {
+        self.framer.separator_required = true;
+        return self;
+    }
+
+    /// The next message in FIN format without parsing it, None at the end of the stream
+    pub async fn next_raw(&mut self) -> Result<Option<String>, io::Error> {
+        let mut chunk = vec![0u8; CHUNK_SIZE];
+        loop {
+            if let Some(message) = self.framer.next_message(self.end_of_stream)? {
+                return Ok(Some(message));
+            }
+            if self.end_of_stream {
+                return Ok(None);
+            }
+
+            match self.reader.read(&mut chunk).await? {
+                0 => self.end_of_stream = true,
+                n => self.framer.push(&chunk[..n]),
+            }
+        }
+    }
+
+    /// The next message, None at the end of the stream. Messages that cannot be parsed are
+    /// returned as errors and reading can continue with the following message.
+    pub async fn next_message(&mut self) -> Option<Result<SwiftMtMessage, io::Error>> {
+        return match self.next_raw().await {
+            Ok(Some(raw)) => Some(parse(raw)),
+            Ok(None) => None,
+            Err(e) => Some(Err(e)),
+        };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::swift::mt::async_reader::AsyncMessageReader;
+    use tokio::io::AsyncWriteExt;
+
+    static FIRST: &str = "{1:F01FOOBARXXAXXX0123000456}{2:I103BANKDEFFXXXXN}{4:\r\n:20:REF1\r\n-}";
+    static SECOND: &str = "{1:F01FOOBARXXAXXX0123000457}{2:I202BANKDEFFXXXXN}{4:\r\n:20:REF2\r\n-}";
+
+    #[tokio::test]
+    async fn positive_tests() {
+        let (mut writer, reader) = tokio::io::duplex(16);
+        let writing = tokio::spawn(async move {
+            for message in [FIRST, SECOND] {
+                writer.write_all(message.as_bytes()).await.unwrap();
+            }
+        });
+
+        let mut reader = AsyncMessageReader::new(reader);
+        let first = reader.next_message().await.unwrap().unwrap();
+        assert_eq!(first.to_raw(), FIRST);
+        assert_eq!(reader.next_raw().await.unwrap().as_deref(), Some(SECOND));
+        assert!(reader.next_message().await.is_none());
+        writing.await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn negative_tests() {
+        let stream = format!("{}$garbage${}", FIRST, SECOND);
+        let mut reader = AsyncMessageReader::new(stream.as_bytes());
+        assert!(reader.next_message().await.unwrap().is_ok());
+        assert!(reader.next_message().await.unwrap().is_err());
+        assert!(reader.next_message().await.unwrap().is_ok());
+        assert!(reader.next_message().await.is_none());
+
+        let mut reader = AsyncMessageReader::new(FIRST.as_bytes()).with_max_message_size(30);
+        assert!(reader.next_raw().await.is_err());
+    }
+}
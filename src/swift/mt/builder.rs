@@ -0,0 +1,228 @@
+use crate::swift::bic::{Bic, LogicalTerminalAddress};
+use crate::swift::mt::header_fields::{ServiceTypeIdentifier, Uetr, ValidationFlag};
+use crate::swift::mt::model::{
+    ApplicationHeader, BasicHeader, InputData, SystemTrailer, Trailer, UserHeader,
+};
+use crate::swift::mt::swift_mt_parser::SwiftMtMessage;
+use crate::swift::mt::text::{Field, MessageText};
+use crate::swift::mt::validator::{ValidationError, Validator, HEADER_ERROR};
+
+/// Message priority of the application header
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Priority {
+    Normal,
+    Urgent,
+    System,
+}
+
+impl Priority {
+    pub fn code(&self) -> &'static str {
+        return match self {
+            Priority::Normal => "N",
+            Priority::Urgent => "U",
+            Priority::System => "S",
+        };
+    }
+}
+
+/// Builder for outgoing (input) user messages. Unless a UETR is set explicitly or disabled
+/// with `without_uetr`, a new UETR is generated by `build`.
+pub struct MessageBuilder {
+    message_type: String,
+    sender: LogicalTerminalAddress,
+    receiver: LogicalTerminalAddress,
+    priority: Priority,
+    message_user_reference: Option<String>,
+    validation_flag: Option<ValidationFlag>,
+    service_type_identifier: Option<ServiceTypeIdentifier>,
+    uetr: Option<Uetr>,
+    generate_uetr: bool,
+    fields: Vec<Field>,
+    validator: Validator,
+}
+
+impl MessageBuilder {
+    /// Starts a message of the type (e.g. `103`) from the logical terminal of the sender to
+    /// the BIC of the receiver
+    pub fn new(message_type: &str, sender: LogicalTerminalAddress, receiver: &Bic) -> Self {
+        return MessageBuilder {
+            message_type: message_type.to_string(),
+            sender,
+            receiver: receiver.to_logical_terminal('X'),
+            priority: Priority::Normal,
+            message_user_reference: None,
+            validation_flag: None,
+            service_type_identifier: None,
+            uetr: None,
+            generate_uetr: true,
+            fields: Vec::new(),
+            validator: Validator::new(),
+        };
+    }
+
+    pub fn priority(mut self, priority: Priority) -> Self {
+        self.priority = priority;
+        return self;
+    }
+
+    /// Message user reference (field 108 of the user header)
+    pub fn message_user_reference(mut self, reference: &str) -> Self {
+        self.message_user_reference = Some(reference.to_string());
+        return self;
+    }
+
+    pub fn validation_flag(mut self, flag: ValidationFlag) -> Self {
+        self.validation_flag = Some(flag);
+        return self;
+    }
+
+    pub fn service_type_identifier(mut self, identifier: ServiceTypeIdentifier) -> Self {
+        self.service_type_identifier = Some(identifier);
+        return self;
+    }
+
+    pub fn uetr(mut self, uetr: Uetr) -> Self {
+        self.uetr = Some(uetr);
+        return self;
+    }
+
+    /// Builds the message without UETR, e.g. for message types that do not carry one
+    pub fn without_uetr(mut self) -> Self {
+        self.uetr = None;
+        self.generate_uetr = false;
+        return self;
+    }
+
+    /// Appends a field to the message text, lines of the value are separated by CrLf
+    pub fn field(mut self, tag: &str, value: &str) -> Self {
+        self.fields.push(Field::new(tag, value));
+        return self;
+    }
+
+    /// Replaces the default validator, which checks the network rules and the X character set
+    pub fn validator(mut self, validator: Validator) -> Self {
+        self.validator = validator;
+        return self;
+    }
+
+    /// Builds the message and validates it, returning all validation errors if there are any
+    pub fn build(self) -> Result<SwiftMtMessage, Vec<ValidationError>> {
+        if self.message_type.len() != 3 || !self.message_type.chars().all(|c| c.is_ascii_digit()) {
+            return Err(vec![ValidationError::new(
+                HEADER_ERROR,
+                None,
+                format!("Message type must have 3 digits: {}", self.message_type),
+            )]);
+        }
+
+        let mut basic_header = BasicHeader::new();
+        basic_header.logical_terminal = self.sender;
+
+        let mut user_header = UserHeader::new();
+        user_header.message_user_reference = self.message_user_reference;
+        user_header.validation_flag = self.validation_flag;
+        user_header.service_type_identifier = self.service_type_identifier;
+        user_header.uetr = match self.uetr {
+            Some(uetr) => Some(uetr),
+            None if self.generate_uetr => Some(Uetr::generate()),
+            None => None,
+        };
+
+        let message = SwiftMtMessage {
+            application_header: ApplicationHeader::Input {
+                data: InputData {
+                    message_type: self.message_type,
+                    destination: self.receiver,
+                    priority: self.priority.code().to_string(),
+                    delivery_monitoring: String::new(),
+                    obsolescence_period: String::new(),
+                },
+            },
+            basic_header,
+            user_header,
+            text: MessageText {
+                fields: self.fields,
+            },
+            trailer: Trailer::new(),
+            system_trailer: SystemTrailer::new(),
+            original_message: None,
+        };
+
+        let errors = self.validator.validate(&message);
+        if !errors.is_empty() {
+            return Err(errors);
+        }
+
+        return Ok(message);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::swift::bic::{Bic, LogicalTerminalAddress};
+    use crate::swift::mt::builder::{MessageBuilder, Priority};
+    use crate::swift::mt::header_fields::{Uetr, ValidationFlag};
+    use crate::swift::mt::{SwiftMtParser, HEADER_ERROR};
+
+    fn builder() -> MessageBuilder {
+        return MessageBuilder::new(
+            "103",
+            LogicalTerminalAddress::parse("FOOBARXXAXXX").unwrap(),
+            &Bic::parse("DEUTDEFF").unwrap(),
+        );
+    }
+
+    #[test]
+    fn positive_tests() {
+        let message = builder()
+            .priority(Priority::Urgent)
+            .message_user_reference("MUR123")
+            .validation_flag(ValidationFlag::Stp)
+            .uetr(Uetr::parse("eb6305c9-1f7f-49de-aed0-16487c27b42d").unwrap())
+            .field("20", "REF123")
+            .field("23B", "CRED")
+            .field("32A", "230102EUR1000,")
+            .field("50K", "/12345678\r\nJOHN DOE")
+            .field("59", "/DE89370400440532013000\r\nJANE DOE")
+            .field("71A", "SHA")
+            .build()
+            .unwrap();
+
+        let raw = message.to_raw();
+        assert_eq!(
+            raw,
+            "{1:F01FOOBARXXAXXX0000000000}{2:I103DEUTDEFFXXXXU}{3:{108:MUR123}{119:STP}{121:eb6305c9-1f7f-49de-aed0-16487c27b42d}}{4:\r\n:20:REF123\r\n:23B:CRED\r\n:32A:230102EUR1000,\r\n:50K:/12345678\r\nJOHN DOE\r\n:59:/DE89370400440532013000\r\nJANE DOE\r\n:71A:SHA\r\n-}"
+        );
+        let parsed = SwiftMtParser::new().parse(raw).unwrap();
+        assert_eq!(
+            parsed.text.field("50K").unwrap().value,
+            "/12345678\r\nJOHN DOE"
+        );
+
+        let message = builder().field("20", "REF123").build().unwrap();
+        assert!(message.user_header.uetr.is_some());
+
+        let message = builder().without_uetr().field("20", "REF").build().unwrap();
+        assert!(message.user_header.uetr.is_none());
+    }
+
+    #[test]
+    fn negative_tests() {
+        let errors = builder()
+            .field("20", "REF123")
+            .field("32A", "230102JPY1000,5")
+            .build()
+            .unwrap_err();
+        assert_eq!(errors[0].code, "C03");
+
+        let errors = MessageBuilder::new(
+            "1034",
+            LogicalTerminalAddress::parse("FOOBARXXAXXX").unwrap(),
+            &Bic::parse("DEUTDEFF").unwrap(),
+        )
+        .build()
+        .unwrap_err();
+        assert_eq!(errors[0].code, HEADER_ERROR);
+    }
+}
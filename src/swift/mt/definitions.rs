@@ -0,0 +1,207 @@
+use crate::swift::mt::text::tag_matches;
+use std::io;
+use std::io::ErrorKind::InvalidData;
+
+/// Names of the fields of the message text, one definition per line in the catalogue format
+/// `message types|tag|name`, see `FieldDefinitions::from_catalogue`.
+static FIELD_NAMES: &str = "\
+|13C|Time Indication
+|13D|Date/Time Indication
+|20|Transaction Reference Number
+|21|Related Reference
+|23B|Bank Operation Code
+|23E|Instruction Code
+|25|Account Identification
+|26T|Transaction Type Code
+|28C|Statement Number/Sequence Number
+|32A|Value Date/Currency/Interbank Settled Amount
+|32B|Currency/Transaction Amount
+|33B|Currency/Instructed Amount
+|36|Exchange Rate
+|50a|Ordering Customer
+|52a|Ordering Institution
+|53a|Sender's Correspondent
+|54a|Receiver's Correspondent
+|56a|Intermediary Institution
+|57a|Account With Institution
+|58a|Beneficiary Institution
+|59a|Beneficiary Customer
+|60F|Opening Balance
+|60M|Intermediate Opening Balance
+|61|Statement Line
+|62F|Closing Balance
+|62M|Intermediate Closing Balance
+|64|Closing Available Balance
+|65|Forward Available Balance
+|70|Remittance Information
+|71A|Details of Charges
+|71F|Sender's Charges
+|71G|Receiver's Charges
+|72|Sender to Receiver Information
+|76|Answers
+|77B|Regulatory Reporting
+|77E|Envelope Contents
+|77T|Envelope Contents
+|79|Narrative
+|86|Information to Account Owner
+103,103STP,103REMIT|20|Sender's Reference
+202,202COV,205,205COV|32A|Value Date, Currency Code, Amount
+";
+
+/// Name of a field of the message text, for the message types given (all types if empty)
+#[derive(Debug, Clone, PartialEq)]
+pub struct FieldDefinition {
+    pub message_types: Vec<String>,
+    /// Tag or tag pattern like `50a`, see `tag_matches`
+    pub tag: String,
+    pub name: String,
+}
+
+/// Catalogue of field names, e.g. for showing messages to humans
+#[derive(Debug, Clone, Default)]
+pub struct FieldDefinitions {
+    definitions: Vec<FieldDefinition>,
+}
+
+impl FieldDefinitions {
+    pub fn new() -> FieldDefinitions {
+        return FieldDefinitions {
+            definitions: Vec::new(),
+        };
+    }
+
+    /// The names of common fields shipped with the crate
+    pub fn builtin() -> FieldDefinitions {
+        return FieldDefinitions::from_catalogue(FIELD_NAMES)
+            .expect("Built-in field definitions are invalid");
+    }
+
+    /// Reads definitions from the line based catalogue format `message types|tag|name` where
+    /// message types is a comma separated list (empty for all types). Empty lines and lines
+    /// starting with # are ignored.
+    pub fn from_catalogue(catalogue: &str) -> Result<FieldDefinitions, io::Error> {
+        let mut definitions = FieldDefinitions::new();
+
+        for line in catalogue.lines().map(|l| l.trim()) {
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let columns: Vec<&str> = line.splitn(3, '|').map(|c| c.trim()).collect();
+            if columns.len() != 3 || columns[1].is_empty() {
+                return Err(io::Error::new(
+                    InvalidData,
+                    format!("Field definition must have 3 columns and a tag: {}", line),
+                ));
+            }
+
+            definitions.add(FieldDefinition {
+                message_types: columns[0]
+                    .split(',')
+                    .map(|mt| mt.trim())
+                    .filter(|mt| !mt.is_empty())
+                    .map(|mt| mt.to_string())
+                    .collect(),
+                tag: columns[1].to_string(),
+                name: columns[2].to_string(),
+            });
+        }
+
+        return Ok(definitions);
+    }
+
+    /// Formats the definitions in the catalogue format
+    pub fn to_catalogue(&self) -> String {
+        return self
+            .definitions
+            .iter()
+            .map(|d| format!("{}|{}|{}\n", d.message_types.join(","), d.tag, d.name))
+            .collect();
+    }
+
+    pub fn add(&mut self, definition: FieldDefinition) {
+        self.definitions.push(definition);
+    }
+
+    /// Adds all definitions of the other catalogue, which take precedence over the ones of
+    /// this catalogue for the same tag
+    pub fn extend(&mut self, other: FieldDefinitions) {
+        let mut definitions = other.definitions;
+        definitions.append(&mut self.definitions);
+        self.definitions = definitions;
+    }
+
+    pub fn definitions(&self) -> &[FieldDefinition] {
+        return &self.definitions;
+    }
+
+    /// Name of the field in a message of the type. Definitions for the message type take
+    /// precedence over definitions for all types, and exact tags over tag patterns.
+    pub fn name(&self, message_type: &str, tag: &str) -> Option<&str> {
+        let specific = |d: &&FieldDefinition| d.message_types.iter().any(|mt| mt == message_type);
+        let generic = |d: &&FieldDefinition| d.message_types.is_empty();
+        let exact = |d: &&FieldDefinition| d.tag == tag;
+        let pattern = |d: &&FieldDefinition| tag_matches(&d.tag, tag);
+
+        return self
+            .find(|d| specific(d) && exact(d))
+            .or_else(|| self.find(|d| specific(d) && pattern(d)))
+            .or_else(|| self.find(|d| generic(d) && exact(d)))
+            .or_else(|| self.find(|d| generic(d) && pattern(d)))
+            .map(|d| d.name.as_str());
+    }
+
+    fn find<P: Fn(&&FieldDefinition) -> bool>(&self, predicate: P) -> Option<&FieldDefinition> {
+        return self.definitions.iter().find(predicate);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::swift::mt::definitions::FieldDefinitions;
+
+    #[test]
+    fn positive_tests() {
+        let definitions = FieldDefinitions::builtin();
+        assert_eq!(definitions.name("103", "20"), Some("Sender's Reference"));
+        assert_eq!(
+            definitions.name("202", "20"),
+            Some("Transaction Reference Number")
+        );
+        assert_eq!(definitions.name("103", "50K"), Some("Ordering Customer"));
+        assert_eq!(definitions.name("103", "50"), Some("Ordering Customer"));
+        assert_eq!(
+            definitions.name("202", "32A"),
+            Some("Value Date, Currency Code, Amount")
+        );
+
+        let mut definitions = FieldDefinitions::builtin();
+        definitions.extend(
+            FieldDefinitions::from_catalogue("# comment\n\n103|50K|Ordering Customer (Name)\n")
+                .unwrap(),
+        );
+        assert_eq!(
+            definitions.name("103", "50K"),
+            Some("Ordering Customer (Name)")
+        );
+        assert_eq!(definitions.name("103", "50F"), Some("Ordering Customer"));
+
+        let catalogue = FieldDefinitions::builtin().to_catalogue();
+        assert_eq!(
+            FieldDefinitions::from_catalogue(&catalogue)
+                .unwrap()
+                .definitions(),
+            FieldDefinitions::builtin().definitions()
+        );
+    }
+
+    #[test]
+    fn negative_tests() {
+        let definitions = FieldDefinitions::builtin();
+        assert_eq!(definitions.name("103", "99"), None);
+        assert_eq!(definitions.name("103", "50k"), None);
+
+        assert!(FieldDefinitions::from_catalogue("103|20").is_err());
+        assert!(FieldDefinitions::from_catalogue("103||Name").is_err());
+    }
+}
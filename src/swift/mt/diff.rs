@@ -0,0 +1,515 @@
+use crate::swift::mt::definitions::FieldDefinitions;
+use crate::swift::mt::model::ApplicationHeader;
+use crate::swift::mt::sequences::sequence_paths;
+use crate::swift::mt::swift_mt_parser::SwiftMtMessage;
+use crate::swift::mt::text::header_block_fields;
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+
+/// Fields with free text, which are compared ignoring where the lines are broken and repeated
+/// spaces
+static NARRATIVE_TAGS: [&str; 9] = ["70", "72", "75", "76", "77A", "77B", "77T", "79", "86"];
+
+static USER_HEADER_NAMES: [(&str, &str); 13] = [
+    ("103", "Service identifier"),
+    ("106", "Message input reference"),
+    ("108", "Message user reference"),
+    ("111", "Service type identifier"),
+    ("113", "Banking priority"),
+    ("115", "Addressee information"),
+    ("119", "Validation flag"),
+    ("121", "Unique end-to-end transaction reference"),
+    ("165", "Payment release information"),
+    ("423", "Balance checkpoint date and time"),
+    ("424", "Related reference"),
+    ("433", "Screening information"),
+    ("434", "Payment controls information"),
+];
+
+static TRAILER_NAMES: [(&str, &str); 10] = [
+    ("PAC", "Proprietary authentication code"),
+    ("CHK", "Checksum"),
+    ("SYS", "System originated message"),
+    ("TNG", "Training"),
+    ("PDE", "Possible duplicate emission"),
+    ("PDM", "Possible duplicate message"),
+    ("DLM", "Delayed message"),
+    ("MRF", "Message reference"),
+    ("SAC", "Successful authentication"),
+    ("COP", "Copy"),
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum DifferenceKind {
+    Added,
+    Removed,
+    Changed,
+}
+
+/// A header element or field that differs between two messages
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Difference {
+    pub kind: DifferenceKind,
+    /// Where the difference is: the header (e.g. `Basic header`), the header or trailer field
+    /// (e.g. `User header/121`) or the field of the message text, prefixed by its sequence and
+    /// followed by the occurrence if the tag repeats (e.g. `B[2]/32B` or `GENL[1]/20C[2]`)
+    pub location: String,
+    /// Name of the header element or field, if known
+    pub name: Option<String>,
+    /// Value in the left message, unless the element was added
+    pub left: Option<String>,
+    /// Value in the right message, unless the element was removed
+    pub right: Option<String>,
+}
+
+impl fmt::Display for Difference {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let sign = match self.kind {
+            DifferenceKind::Added => '+',
+            DifferenceKind::Removed => '-',
+            DifferenceKind::Changed => '~',
+        };
+        write!(f, "{} {}", sign, self.location)?;
+        if let Some(name) = &self.name {
+            write!(f, " ({})", name)?;
+        }
+
+        let value = |v: &Option<String>| v.as_deref().unwrap_or("").replace("\r\n", "\\n");
+        return match self.kind {
+            DifferenceKind::Added => write!(f, ": {}", value(&self.right)),
+            DifferenceKind::Removed => write!(f, ": {}", value(&self.left)),
+            DifferenceKind::Changed => {
+                write!(f, ": {} -> {}", value(&self.left), value(&self.right))
+            }
+        };
+    }
+}
+
+struct Entry {
+    location: String,
+    name: Option<String>,
+    /// Tag of the field, or the name of the header element
+    tag: String,
+    narrative: bool,
+    value: String,
+}
+
+/// Compares two messages header element by header element and field by field. Fields are
+/// matched by their sequence, tag and occurrence, where sequences are the blocks of 16R/16S
+/// and the repetitive sequences of common message types. Narrative fields that only differ in
+/// where their lines are broken, including in the middle of a word, and trailing spaces of
+/// lines are ignored. Field names are taken from the definitions.
+pub fn diff(
+    left: &SwiftMtMessage,
+    right: &SwiftMtMessage,
+    definitions: &FieldDefinitions,
+) -> Vec<Difference> {
+    let left_entries = entries(left, definitions);
+    let right_entries = entries(right, definitions);
+
+    // names depend on the message type, so a field renamed by the definitions still matches
+    let key = |e: &Entry| (e.location.clone(), e.tag.clone());
+    let right_by_key: HashMap<(String, String), &Entry> =
+        right_entries.iter().map(|e| (key(e), e)).collect();
+    let left_by_key: HashMap<(String, String), &Entry> =
+        left_entries.iter().map(|e| (key(e), e)).collect();
+
+    let mut differences = Vec::new();
+    for entry in &left_entries {
+        match right_by_key.get(&key(entry)) {
+            Some(other) if !values_equal(entry.narrative, &entry.value, &other.value) => {
+                differences.push(Difference {
+                    kind: DifferenceKind::Changed,
+                    location: entry.location.clone(),
+                    name: entry.name.clone(),
+                    left: Some(entry.value.clone()),
+                    right: Some(other.value.clone()),
+                })
+            }
+            Some(_) => {}
+            None => differences.push(Difference {
+                kind: DifferenceKind::Removed,
+                location: entry.location.clone(),
+                name: entry.name.clone(),
+                left: Some(entry.value.clone()),
+                right: None,
+            }),
+        }
+    }
+
+    for entry in &right_entries {
+        if !left_by_key.contains_key(&key(entry)) {
+            differences.push(Difference {
+                kind: DifferenceKind::Added,
+                location: entry.location.clone(),
+                name: entry.name.clone(),
+                left: None,
+                right: Some(entry.value.clone()),
+            });
+        }
+    }
+
+    return differences;
+}
+
+fn entries(message: &SwiftMtMessage, definitions: &FieldDefinitions) -> Vec<Entry> {
+    let mut entries = Vec::new();
+    let mut header = |location: &str, name: &str, value: String| {
+        entries.push(Entry {
+            location: location.to_string(),
+            name: Some(name.to_string()),
+            tag: name.to_string(),
+            narrative: false,
+            value,
+        });
+    };
+
+    let basic_header = &message.basic_header;
+    header(
+        "Basic header",
+        "Application identifier",
+        basic_header.application_identifier.clone(),
+    );
+    header(
+        "Basic header",
+        "Service identifier",
+        format!("{:02}", basic_header.service_identifier as u32),
+    );
+    header(
+        "Basic header",
+        "Logical terminal",
+        basic_header.logical_terminal.to_string(),
+    );
+    header(
+        "Basic header",
+        "Session number",
+        format!("{:04}", basic_header.session_number),
+    );
+    header(
+        "Basic header",
+        "Sequence number",
+        format!("{:06}", basic_header.sequence_number),
+    );
+
+    match &message.application_header {
+        ApplicationHeader::Input { data } => {
+            header("Application header", "Direction", "Input".to_string());
+            header(
+                "Application header",
+                "Message type",
+                data.message_type.clone(),
+            );
+            header(
+                "Application header",
+                "Receiver",
+                data.destination.to_string(),
+            );
+            header("Application header", "Priority", data.priority.clone());
+            header(
+                "Application header",
+                "Delivery monitoring",
+                data.delivery_monitoring.clone(),
+            );
+            header(
+                "Application header",
+                "Obsolescence period",
+                data.obsolescence_period.clone(),
+            );
+        }
+        ApplicationHeader::Output { data } => {
+            header("Application header", "Direction", "Output".to_string());
+            header(
+                "Application header",
+                "Message type",
+                data.message_type.clone(),
+            );
+            header(
+                "Application header",
+                "Input time",
+                data.sender_datetime.to_string(),
+            );
+            header(
+                "Application header",
+                "Sender",
+                data.sender_address.to_string(),
+            );
+            header(
+                "Application header",
+                "Session number",
+                data.session_number.clone(),
+            );
+            header(
+                "Application header",
+                "Sequence number",
+                data.sequence_number.clone(),
+            );
+            header(
+                "Application header",
+                "Output time",
+                data.receiver_datetime.to_string(),
+            );
+            header(
+                "Application header",
+                "Priority",
+                data.message_priority.clone(),
+            );
+        }
+        ApplicationHeader::Empty => {}
+    }
+
+    let blocks = [
+        (
+            "User header",
+            message.user_header.to_raw(),
+            &USER_HEADER_NAMES[..],
+        ),
+        ("Trailer", message.trailer.to_raw(), &TRAILER_NAMES[..]),
+        (
+            "System trailer",
+            message.system_trailer.to_raw(),
+            &TRAILER_NAMES[..],
+        ),
+    ];
+    for (block, raw, names) in blocks {
+        for field in header_block_fields(&raw) {
+            entries.push(Entry {
+                location: format!("{}/{}", block, field.tag),
+                name: names
+                    .iter()
+                    .find(|(tag, _)| *tag == field.tag)
+                    .map(|(_, name)| name.to_string()),
+                tag: field.tag,
+                narrative: false,
+                value: field.value,
+            });
+        }
+    }
+
+    let message_type = message.application_header.message_type().unwrap_or("");
+    for (path, index) in sequence_paths(message_type, &message.text) {
+        let field = &message.text.fields[index];
+        let prefix = if path.is_empty() {
+            String::new()
+        } else {
+            format!("{}/", path)
+        };
+        let location = format!("{}{}", prefix, field.tag);
+        let occurrence = entries
+            .iter()
+            .filter(|e| e.location == location || e.location.starts_with(&format!("{}[", location)))
+            .count();
+
+        entries.push(Entry {
+            location: if occurrence == 0 {
+                location
+            } else {
+                format!("{}[{}]", location, occurrence + 1)
+            },
+            name: definitions
+                .name(message_type, &field.tag)
+                .map(|n| n.to_string()),
+            tag: field.tag.clone(),
+            narrative: NARRATIVE_TAGS.contains(&field.tag.as_str()),
+            value: field.value.clone(),
+        });
+    }
+
+    return entries;
+}
+
+fn values_equal(narrative: bool, left: &str, right: &str) -> bool {
+    if narrative {
+        let (left, right) = (narrative_chars(left), narrative_chars(right));
+        return narratives_match(&left, &right, 0, 0, &mut HashSet::new());
+    }
+
+    return left
+        .split("\r\n")
+        .map(|l| l.trim_end())
+        .eq(right.split("\r\n").map(|l| l.trim_end()));
+}
+
+/// The characters of a narrative with repeated spaces collapsed into one. A line break in a
+/// word is `None`, as the line may have been wrapped between two words or, at 35 characters,
+/// in the middle of a word.
+fn narrative_chars(value: &str) -> Vec<Option<char>> {
+    let mut chars: Vec<Option<char>> = Vec::new();
+    for (index, line) in value.split("\r\n").enumerate() {
+        let breaks = (index > 0).then_some(None);
+        for c in breaks.into_iter().chain(line.chars().map(Some)) {
+            let c = c.map(|c| if c.is_whitespace() { ' ' } else { c });
+            match (chars.last(), c) {
+                (None | Some(Some(' ')), None | Some(' ')) => {}
+                (Some(None), None) => {}
+                (Some(None), Some(' ')) => *chars.last_mut().unwrap() = Some(' '),
+                _ => chars.push(c),
+            }
+        }
+    }
+
+    while matches!(chars.last(), Some(None | Some(' '))) {
+        chars.pop();
+    }
+    return chars;
+}
+
+/// Whether the narratives match from the positions, taking each line break in a word as
+/// either a space or nothing. Positions known not to match are remembered in `failed`.
+fn narratives_match(
+    left: &[Option<char>],
+    right: &[Option<char>],
+    mut i: usize,
+    mut j: usize,
+    failed: &mut HashSet<(usize, usize)>,
+) -> bool {
+    while let (Some(Some(l)), Some(Some(r))) = (left.get(i), right.get(j)) {
+        if l != r {
+            return false;
+        }
+        i += 1;
+        j += 1;
+    }
+    if failed.contains(&(i, j)) {
+        return false;
+    }
+
+    let space = |c: Option<&Option<char>>| matches!(c, Some(None | Some(' ')));
+    let matched = match (left.get(i), right.get(j)) {
+        (None, None) => true,
+        (Some(None), _) if narratives_match(left, right, i + 1, j, failed) => true,
+        (_, Some(None)) if narratives_match(left, right, i, j + 1, failed) => true,
+        (l, r) if space(l) && space(r) => narratives_match(left, right, i + 1, j + 1, failed),
+        _ => false,
+    };
+    if !matched {
+        failed.insert((i, j));
+    }
+    return matched;
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::swift::mt::diff::{diff, DifferenceKind};
+    use crate::swift::mt::{FieldDefinitions, SwiftMtMessage, SwiftMtParser};
+
+    fn parse(user_header: &str, text: &str) -> SwiftMtMessage {
+        let raw = format!(
+            "{{1:F01FOOBARXXAXXX0123000456}}{{2:I101BANKDEFFXXXXN}}{}{{4:\r\n{}\r\n-}}",
+            user_header, text
+        );
+        return SwiftMtParser::new().parse(raw).unwrap();
+    }
+
+    #[test]
+    fn positive_tests() {
+        let definitions = FieldDefinitions::builtin();
+        let original = parse(
+            "{3:{108:MUR1}}",
+            ":20:REF1\r\n:30:230102\r\n:21:TX1\r\n:32B:EUR100,\r\n:70:PAYMENT FOR\r\nINVOICE 1\r\n:21:TX2\r\n:32B:EUR200,",
+        );
+        let amended = parse(
+            "{3:{108:MUR2}}",
+            ":20:REF1\r\n:30:230102\r\n:21:TX1\r\n:32B:EUR100,\r\n:70:PAYMENT\r\nFOR INVOICE 1\r\n:21:TX2\r\n:32B:EUR250,\r\n:21:TX3\r\n:32B:EUR300,",
+        );
+
+        assert!(diff(&original, &original, &definitions).is_empty());
+
+        let differences = diff(&original, &amended, &definitions);
+        assert_eq!(differences.len(), 4, "{:?}", differences);
+
+        assert_eq!(differences[0].kind, DifferenceKind::Changed);
+        assert_eq!(differences[0].location, "User header/108");
+        assert_eq!(
+            differences[0].name.as_deref(),
+            Some("Message user reference")
+        );
+
+        assert_eq!(differences[1].location, "B[2]/32B");
+        assert_eq!(differences[1].left.as_deref(), Some("EUR200,"));
+        assert_eq!(differences[1].right.as_deref(), Some("EUR250,"));
+        assert_eq!(
+            differences[1].to_string(),
+            "~ B[2]/32B (Currency/Transaction Amount): EUR200, -> EUR250,"
+        );
+
+        assert_eq!(differences[2].kind, DifferenceKind::Added);
+        assert_eq!(differences[2].location, "B[3]/21");
+        assert_eq!(differences[3].location, "B[3]/32B");
+
+        let securities = |reference: &str| {
+            let raw = format!("{{1:F01FOOBARXXAXXX0123000456}}{{2:I540BANKDEFFXXXXN}}{{4:\r\n:16R:GENL\r\n:20C::SEME//{}\r\n:16R:LINK\r\n:20C::PREV//A\r\n:16S:LINK\r\n:16R:LINK\r\n:20C::PREV//B\r\n:16S:LINK\r\n:16S:GENL\r\n-}}", reference);
+            return SwiftMtParser::new().parse(raw).unwrap();
+        };
+        let differences = diff(&securities("REF1"), &securities("REF2"), &definitions);
+        assert_eq!(differences.len(), 1);
+        assert_eq!(differences[0].location, "GENL[1]/20C");
+
+        // a narrative rewrapped at 35 characters in the middle of a word is unchanged
+        let narrative = |text: &str| parse("", &format!(":20:REF1\r\n:79:{}", text));
+        assert!(diff(
+            &narrative("PLEASE CREDIT THE BENEFICIARY IMMED\r\nIATELY AND ADVISE BY PHONE"),
+            &narrative("PLEASE CREDIT THE\r\nBENEFICIARY IMMEDIATELY AND\r\nADVISE BY PHONE"),
+            &definitions
+        )
+        .is_empty());
+        assert!(diff(
+            &narrative("PAYMENT OF INVOICE 12345 DATED 0\r\n1 JANUARY"),
+            &narrative("PAYMENT OF INVOICE 12345 DATED\r\n01 JANUARY"),
+            &definitions
+        )
+        .is_empty());
+
+        // fields are matched by tag, not by their name for the message type
+        let named = FieldDefinitions::from_catalogue("101|20|Sender's Reference\n").unwrap();
+        let other_type = SwiftMtParser::new()
+            .parse(
+                "{1:F01FOOBARXXAXXX0123000456}{2:I102BANKDEFFXXXXN}{4:\r\n:20:REF2\r\n-}"
+                    .to_string(),
+            )
+            .unwrap();
+        let differences = diff(&parse("", ":20:REF1"), &other_type, &named);
+        assert_eq!(differences.len(), 2, "{:?}", differences);
+        assert_eq!(differences[0].name.as_deref(), Some("Message type"));
+        assert_eq!(differences[1].kind, DifferenceKind::Changed);
+        assert_eq!(differences[1].location, "20");
+        assert_eq!(differences[1].name.as_deref(), Some("Sender's Reference"));
+    }
+
+    #[test]
+    fn negative_tests() {
+        let definitions = FieldDefinitions::builtin();
+        let original = parse("", ":20:REF1\r\n:72:/ACC/LINE 1\r\n:21:TX1");
+        let amended = parse("", ":20:REF1\r\n:21:TX1\r\n:32B:EUR1,");
+
+        let differences = diff(&original, &amended, &definitions);
+        assert_eq!(differences.len(), 2);
+        assert_eq!(differences[0].kind, DifferenceKind::Removed);
+        assert_eq!(differences[0].location, "72");
+        assert_eq!(
+            differences[0].name.as_deref(),
+            Some("Sender to Receiver Information")
+        );
+        assert_eq!(differences[1].kind, DifferenceKind::Added);
+        assert_eq!(differences[1].location, "B[1]/32B");
+
+        let mut output = original.clone();
+        output.basic_header.sequence_number = 457;
+        let differences = diff(&original, &output, &definitions);
+        assert_eq!(differences.len(), 1);
+        assert_eq!(differences[0].location, "Basic header");
+        assert_eq!(differences[0].name.as_deref(), Some("Sequence number"));
+        assert_eq!(differences[0].right.as_deref(), Some("000457"));
+
+        // joining the lines of a narrative must not hide a changed word
+        let narrative = |text: &str| parse("", &format!(":20:REF1\r\n:79:{}", text));
+        let differences = diff(
+            &narrative("PAYMENT FOR\r\nINVOICE 1"),
+            &narrative("PAYMENT FOR\r\nINVOICE 2"),
+            &definitions,
+        );
+        assert_eq!(differences.len(), 1);
+        assert_eq!(differences[0].location, "79");
+    }
+}
@@ -0,0 +1,298 @@
+use crate::swift::amount::Amount;
+use crate::swift::bic::Bic;
+use crate::swift::currency::Currency;
+use crate::swift::datetime::SwiftDate;
+use crate::swift::iban::Iban;
+use crate::swift::mt::text::Field;
+use std::io;
+use std::io::ErrorKind::InvalidData;
+
+/// Party or institution field of the 50a to 59a range, e.g. 50K, 52A or 59F
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PartyField {
+    pub tag: String,
+    /// Account from the optional first line starting with a slash, without the slash(es)
+    pub account: Option<String>,
+    /// The account if it is an IBAN
+    pub iban: Option<Iban>,
+    /// Party identifier of field 50F when it is not an account (e.g. `CUST/DE/ABC/1234`)
+    pub party_identifier: Option<String>,
+    /// Identifier code of the options with one, see `has_identifier_code`
+    pub bic: Option<Bic>,
+    pub name: Option<String>,
+    pub address: Vec<String>,
+    /// Country of option F (line 3/CC/town)
+    pub country_code: Option<String>,
+}
+
+impl PartyField {
+    pub fn is_party_field(tag: &str) -> bool {
+        return tag.len() >= 2
+            && tag.starts_with('5')
+            && tag[1..2].chars().all(|c| c.is_ascii_digit());
+    }
+
+    /// Whether the option of the field carries an identifier code: options A and G, and option
+    /// C of field 50. Option C of the institution fields 52C to 57C is only an account.
+    pub fn has_identifier_code(tag: &str) -> bool {
+        return PartyField::is_party_field(tag)
+            && match tag.chars().nth(2) {
+                Some('A') | Some('G') => true,
+                Some('C') => tag.starts_with("50"),
+                _ => false,
+            };
+    }
+
+    /// Reads the account line, the BIC of the options with one, the numbered lines of option F
+    /// and the name and address lines of the other options. Accounts that look like an IBAN
+    /// of a registered country are validated as IBAN.
+    pub fn from_field(field: &Field) -> Result<PartyField, io::Error> {
+        if !PartyField::is_party_field(&field.tag) {
+            return Err(io::Error::new(
+                InvalidData,
+                format!("Field {} is not a party field", field.tag),
+            ));
+        }
+
+        let option = field.tag.chars().nth(2);
+        let mut lines: Vec<&str> = field.lines();
+        let mut party = PartyField {
+            tag: field.tag.clone(),
+            account: None,
+            iban: None,
+            party_identifier: None,
+            bic: None,
+            name: None,
+            address: Vec::new(),
+            country_code: None,
+        };
+
+        if lines.first().is_some_and(|l| l.starts_with('/')) {
+            let account = read_account(lines.remove(0));
+            if Iban::looks_like_iban(&account) {
+                party.iban = Some(Iban::parse(&account).map_err(|e| {
+                    io::Error::new(
+                        InvalidData,
+                        format!("Invalid IBAN in field {}: {}", field.tag, e),
+                    )
+                })?);
+            }
+            party.account = Some(account);
+        } else if field.tag == "50F" && !lines.is_empty() {
+            party.party_identifier = Some(lines.remove(0).to_string());
+        }
+
+        match option {
+            _ if PartyField::has_identifier_code(&field.tag) => {
+                let bic = lines.last().ok_or_else(|| {
+                    io::Error::new(
+                        InvalidData,
+                        format!("Field {} has no identifier code", field.tag),
+                    )
+                })?;
+                party.bic = Some(Bic::parse(bic)?);
+            }
+            Some('F') => read_numbered_lines(&mut party, &lines)?,
+            Some('C') => {}
+            _ => {
+                party.name = lines.first().map(|l| l.to_string());
+                party.address = lines.iter().skip(1).map(|l| l.to_string()).collect();
+            }
+        }
+
+        return Ok(party);
+    }
+}
+
+/// Currency and amount fields such as 32B, 33B and 71F, and the fields 32A, 32C and 32D
+/// which start with the value date (`6!n3!a15d`)
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct AmountField {
+    pub tag: String,
+    pub value_date: Option<SwiftDate>,
+    pub currency: Currency,
+    pub amount: Amount,
+}
+
+impl AmountField {
+    pub fn is_amount_field(tag: &str) -> bool {
+        return ["32A", "32B", "32C", "32D", "33B", "71F", "71G"].contains(&tag);
+    }
+
+    /// Reads the field, checking the currency code and the `15d` format of the amount. Whether
+    /// the amount has too many decimals for the currency is checked by `check_decimals`.
+    pub fn from_field(field: &Field) -> Result<AmountField, io::Error> {
+        let has_date = ["32A", "32C", "32D"].contains(&field.tag.as_str());
+        let value = field.value.as_str();
+        let currency_start = if has_date { 6 } else { 0 };
+
+        if !value.is_ascii() || value.len() < currency_start + 4 {
+            return Err(io::Error::new(
+                InvalidData,
+                format!("Field {} is too short: {}", field.tag, value),
+            ));
+        }
+
+        let value_date = if has_date {
+            Some(SwiftDate::parse_yymmdd(&value[0..6]).map_err(|e| {
+                io::Error::new(
+                    InvalidData,
+                    format!("Invalid date in field {}: {}", field.tag, e),
+                )
+            })?)
+        } else {
+            None
+        };
+
+        return Ok(AmountField {
+            tag: field.tag.clone(),
+            value_date,
+            currency: value[currency_start..currency_start + 3].parse()?,
+            amount: Amount::parse(&value[currency_start + 3..])?,
+        });
+    }
+
+    /// Network error C03 if the amount has more decimals than the currency allows
+    pub fn check_decimals(&self) -> Result<(), io::Error> {
+        return self.amount.check_currency(self.currency);
+    }
+}
+
+/// Account of the optional account line of a party field
+pub(crate) fn account_line(field: &Field) -> Option<String> {
+    return field
+        .lines()
+        .first()
+        .filter(|l| l.starts_with('/'))
+        .map(|l| read_account(l));
+}
+
+/// Strips the slash and the optional debit/credit mark (`/C/`, `/D/`) of an account line
+fn read_account(line: &str) -> String {
+    let account = &line[1..];
+    for mark in ["C/", "D/"] {
+        if let Some(stripped) = account.strip_prefix(mark) {
+            return stripped.to_string();
+        }
+    }
+
+    return account.to_string();
+}
+
+fn read_numbered_lines(party: &mut PartyField, lines: &[&str]) -> Result<(), io::Error> {
+    for line in lines {
+        let (number, content) = line.split_once('/').ok_or_else(|| {
+            io::Error::new(
+                InvalidData,
+                format!("Line {} of field {} is not numbered", line, party.tag),
+            )
+        })?;
+
+        match number {
+            "1" => {
+                party.name = Some(match &party.name {
+                    Some(name) => format!("{} {}", name, content),
+                    None => content.to_string(),
+                })
+            }
+            "3" => {
+                let (country, town) = content.split_once('/').unwrap_or((content, ""));
+                party.country_code = Some(country.to_string());
+                if !town.is_empty() {
+                    party.address.push(town.to_string());
+                }
+            }
+            _ => party.address.push(content.to_string()),
+        }
+    }
+
+    return Ok(());
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::swift::currency::Currency;
+    use crate::swift::mt::fields::{AmountField, PartyField};
+    use crate::swift::mt::text::Field;
+    use chrono::NaiveDate;
+
+    #[test]
+    fn positive_tests() {
+        let party = PartyField::from_field(&Field::new(
+            "59",
+            "/DE89370400440532013000\r\nJOHN DOE\r\nMAIN STREET 1\r\nBERLIN",
+        ))
+        .unwrap();
+        assert_eq!(party.account.as_deref(), Some("DE89370400440532013000"));
+        assert_eq!(party.iban.unwrap(), "DE89370400440532013000");
+        assert_eq!(party.name.as_deref(), Some("JOHN DOE"));
+        assert_eq!(party.address, vec!["MAIN STREET 1", "BERLIN"]);
+
+        let party = PartyField::from_field(&Field::new(
+            "59F",
+            "/12345678\r\n1/JOHN\r\n1/DOE\r\n2/MAIN STREET 1\r\n3/US/NEW YORK",
+        ))
+        .unwrap();
+        assert_eq!(party.account.as_deref(), Some("12345678"));
+        assert!(party.iban.is_none());
+        assert_eq!(party.name.as_deref(), Some("JOHN DOE"));
+        assert_eq!(party.address, vec!["MAIN STREET 1", "NEW YORK"]);
+        assert_eq!(party.country_code.as_deref(), Some("US"));
+
+        let party = PartyField::from_field(&Field::new(
+            "50F",
+            "CUST/DE/ABC/1234\r\n1/JOHN DOE\r\n3/DE/BERLIN",
+        ))
+        .unwrap();
+        assert_eq!(party.party_identifier.as_deref(), Some("CUST/DE/ABC/1234"));
+        assert!(party.account.is_none());
+
+        let party = PartyField::from_field(&Field::new("57A", "/C/123456\r\nDEUTDEFF")).unwrap();
+        assert_eq!(party.account.as_deref(), Some("123456"));
+        assert_eq!(party.bic.unwrap(), "DEUTDEFF");
+
+        let party = PartyField::from_field(&Field::new("50C", "DEUTDEFF")).unwrap();
+        assert_eq!(party.bic.unwrap(), "DEUTDEFF");
+
+        let party = PartyField::from_field(&Field::new("57C", "//FW123456789")).unwrap();
+        assert_eq!(party.account.as_deref(), Some("/FW123456789"));
+        assert!(party.bic.is_none());
+        assert!(party.name.is_none());
+
+        let amount = AmountField::from_field(&Field::new("32A", "230102EUR1000,5")).unwrap();
+        assert_eq!(
+            amount.value_date.map(|d| d.date()),
+            NaiveDate::from_ymd_opt(2023, 1, 2)
+        );
+        assert_eq!(amount.currency, Currency::EUR);
+        assert_eq!(amount.amount.to_string(), "1000,5");
+        assert!(amount.check_decimals().is_ok());
+
+        let amount = AmountField::from_field(&Field::new("71F", "JPY500,")).unwrap();
+        assert_eq!(amount.value_date, None);
+        assert_eq!(amount.currency, Currency::JPY);
+    }
+
+    #[test]
+    fn negative_tests() {
+        assert!(
+            PartyField::from_field(&Field::new("59", "/DE88370400440532013000\r\nJOHN DOE"))
+                .is_err()
+        );
+        assert!(PartyField::from_field(&Field::new("57A", "/123456\r\nDEUT")).is_err());
+        assert!(PartyField::from_field(&Field::new("50C", "/123456")).is_err());
+        assert!(PartyField::from_field(&Field::new("59F", "1/JOHN\r\nDOE")).is_err());
+        assert!(PartyField::from_field(&Field::new("20", "REF")).is_err());
+
+        assert!(AmountField::from_field(&Field::new("32A", "231301EUR1000,")).is_err());
+        assert!(AmountField::from_field(&Field::new("33B", "XYZ1000,")).is_err());
+        assert!(AmountField::from_field(&Field::new("33B", "EUR1000")).is_err());
+        assert!(AmountField::from_field(&Field::new("33B", "EUR")).is_err());
+        assert!(AmountField::from_field(&Field::new("71G", "JPY5,5"))
+            .unwrap()
+            .check_decimals()
+            .is_err());
+    }
+}
@@ -0,0 +1,359 @@
+use crate::swift::mt::model::ApplicationHeader;
+use crate::swift::mt::swift_mt_parser::SwiftMtMessage;
+use sha2::{Digest, Sha256};
+use std::collections::HashSet;
+use std::fmt;
+use std::fs::{File, OpenOptions};
+use std::io;
+use std::io::ErrorKind::InvalidData;
+use std::io::{BufRead, BufReader, Write};
+use std::path::Path;
+use std::str::FromStr;
+
+/// Which parts of a message take part in its fingerprint
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Strictness {
+    /// Blocks 1 to 4 as serialised, except for the session and sequence numbers of the basic
+    /// header. These and the trailers are left out, so a message re-sent with a PDE trailer in
+    /// a later session has the same fingerprint as the original.
+    Exact,
+    /// Message type, sender and receiver BIC, message user reference (108), UETR (121) and
+    /// message text. Session and sequence numbers, priority, the other user header fields and
+    /// the trailers are ignored.
+    BusinessEqual,
+    /// Message type and message text only, e.g. to find payments sent twice through different
+    /// connections
+    HeaderInsensitive,
+}
+
+/// SHA-256 hash of the canonical form of a message, written as 64 hexadecimal characters
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Fingerprint {
+    hash: [u8; 32],
+}
+
+impl Fingerprint {
+    /// Fingerprint of the message at the given strictness. Field values are compared with
+    /// trailing spaces of each line removed.
+    pub fn of(message: &SwiftMtMessage, strictness: Strictness) -> Fingerprint {
+        let mut hasher = Sha256::new();
+        let mut add = |part: &str| {
+            hasher.update((part.len() as u64).to_le_bytes());
+            hasher.update(part.as_bytes());
+        };
+
+        add(&format!("{:?}", strictness));
+        match strictness {
+            Strictness::Exact => {
+                let mut basic_header = message.basic_header.clone();
+                basic_header.session_number = 0;
+                basic_header.sequence_number = 0;
+                add(&basic_header.to_raw());
+                add(&message.application_header.to_raw());
+                add(&message.user_header.to_raw());
+                add(&message.text.to_raw());
+            }
+            Strictness::BusinessEqual | Strictness::HeaderInsensitive => {
+                add(message.application_header.message_type().unwrap_or(""));
+                if strictness == Strictness::BusinessEqual {
+                    add(message.basic_header.logical_terminal.bic11().as_str());
+                    add(&counterparty(&message.application_header));
+                    let user_header = &message.user_header;
+                    let uetr = user_header.uetr.as_ref().map(|u| u.to_string());
+                    add(user_header.message_user_reference.as_deref().unwrap_or(""));
+                    add(uetr.as_deref().unwrap_or(""));
+                }
+
+                for field in &message.text.fields {
+                    add(&field.tag);
+                    add(&normalize(&field.value));
+                }
+            }
+        }
+
+        let mut hash = [0u8; 32];
+        hash.copy_from_slice(&hasher.finalize());
+        return Fingerprint { hash };
+    }
+
+    pub fn as_bytes(&self) -> &[u8; 32] {
+        return &self.hash;
+    }
+}
+
+impl FromStr for Fingerprint {
+    type Err = io::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.len() != 64 || !s.is_ascii() {
+            return Err(io::Error::new(
+                InvalidData,
+                format!("Fingerprint must have 64 hexadecimal characters: {}", s),
+            ));
+        }
+
+        let mut hash = [0u8; 32];
+        for (i, byte) in hash.iter_mut().enumerate() {
+            *byte = u8::from_str_radix(&s[i * 2..i * 2 + 2], 16).map_err(|e| {
+                io::Error::new(InvalidData, format!("Invalid fingerprint {}: {}", s, e))
+            })?;
+        }
+
+        return Ok(Fingerprint { hash });
+    }
+}
+
+impl fmt::Display for Fingerprint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for byte in &self.hash {
+            write!(f, "{:02x}", byte)?;
+        }
+
+        return Ok(());
+    }
+}
+
+/// Set of fingerprints seen before, for detecting duplicates in a stream of messages
+pub trait SeenSet {
+    fn contains(&self, fingerprint: &Fingerprint) -> bool;
+
+    /// Adds the fingerprint, returning whether it was not seen before
+    fn insert(&mut self, fingerprint: Fingerprint) -> Result<bool, io::Error>;
+
+    /// Adds the fingerprint of the message, returning whether the message is new
+    fn check_message(
+        &mut self,
+        message: &SwiftMtMessage,
+        strictness: Strictness,
+    ) -> Result<bool, io::Error> {
+        return self.insert(Fingerprint::of(message, strictness));
+    }
+}
+
+/// Seen-set kept in memory
+#[derive(Debug, Default)]
+pub struct MemorySeenSet {
+    seen: HashSet<Fingerprint>,
+}
+
+/// Seen-set persisted in a file with one fingerprint per line. New fingerprints are appended
+/// as they are inserted, so the set survives restarts.
+pub struct FileSeenSet {
+    seen: HashSet<Fingerprint>,
+    file: File,
+}
+
+impl MemorySeenSet {
+    pub fn new() -> MemorySeenSet {
+        return MemorySeenSet {
+            seen: HashSet::new(),
+        };
+    }
+
+    pub fn len(&self) -> usize {
+        return self.seen.len();
+    }
+
+    pub fn is_empty(&self) -> bool {
+        return self.seen.is_empty();
+    }
+}
+
+impl SeenSet for MemorySeenSet {
+    fn contains(&self, fingerprint: &Fingerprint) -> bool {
+        return self.seen.contains(fingerprint);
+    }
+
+    fn insert(&mut self, fingerprint: Fingerprint) -> Result<bool, io::Error> {
+        return Ok(self.seen.insert(fingerprint));
+    }
+}
+
+impl FileSeenSet {
+    /// Opens the file, creating it if it does not exist, and loads the fingerprints in it
+    pub fn open(path: &Path) -> Result<FileSeenSet, io::Error> {
+        let mut seen = HashSet::new();
+        if path.exists() {
+            for line in BufReader::new(File::open(path)?).lines() {
+                let line = line?;
+                if !line.trim().is_empty() {
+                    seen.insert(line.trim().parse::<Fingerprint>()?);
+                }
+            }
+        }
+
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+        return Ok(FileSeenSet { seen, file });
+    }
+
+    pub fn len(&self) -> usize {
+        return self.seen.len();
+    }
+
+    pub fn is_empty(&self) -> bool {
+        return self.seen.is_empty();
+    }
+}
+
+impl SeenSet for FileSeenSet {
+    fn contains(&self, fingerprint: &Fingerprint) -> bool {
+        return self.seen.contains(fingerprint);
+    }
+
+    fn insert(&mut self, fingerprint: Fingerprint) -> Result<bool, io::Error> {
+        if self.seen.contains(&fingerprint) {
+            return Ok(false);
+        }
+
+        writeln!(self.file, "{}", fingerprint)?;
+        self.file.flush()?;
+        self.seen.insert(fingerprint);
+        return Ok(true);
+    }
+}
+
+/// BIC11 of the receiver of an input message or of the sender of an output message
+fn counterparty(application_header: &ApplicationHeader) -> String {
+    return match application_header {
+        ApplicationHeader::Input { data } => data.destination.bic11().to_string(),
+        ApplicationHeader::Output { data } => data.sender_address.bic11().to_string(),
+        ApplicationHeader::Empty => String::new(),
+    };
+}
+
+fn normalize(value: &str) -> String {
+    return value
+        .split("\r\n")
+        .map(|line| line.trim_end())
+        .collect::<Vec<&str>>()
+        .join("\r\n");
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::swift::mt::fingerprint::{
+        FileSeenSet, Fingerprint, MemorySeenSet, SeenSet, Strictness,
+    };
+    use crate::swift::mt::{SwiftMtMessage, SwiftMtParser};
+    use std::env;
+    use std::fs;
+
+    fn parse(basic_header: &str, user_header: &str, text: &str) -> SwiftMtMessage {
+        let raw = format!(
+            "{{1:F01{}}}{{2:I103BANKDEFFXXXXN}}{}{{4:\r\n{}\r\n-}}{{5:{{PDE:}}}}",
+            basic_header, user_header, text
+        );
+        return SwiftMtParser::new().parse(raw).unwrap();
+    }
+
+    #[test]
+    fn positive_tests() {
+        let original = parse(
+            "FOOBARXXAXXX0123000456",
+            "{3:{108:MUR1}}",
+            ":20:REF123\r\n:32A:230102EUR1000,",
+        );
+        let resent = parse(
+            "FOOBARXXBXXX0124000789",
+            "{3:{108:MUR1}}",
+            ":20:REF123 \r\n:32A:230102EUR1000,",
+        );
+        let mut copy = original.clone();
+        copy.trailer.pde = None;
+
+        assert_eq!(
+            Fingerprint::of(&original, Strictness::Exact),
+            Fingerprint::of(&copy, Strictness::Exact)
+        );
+        assert_ne!(
+            Fingerprint::of(&original, Strictness::Exact),
+            Fingerprint::of(&resent, Strictness::Exact)
+        );
+
+        // a possible duplicate emission is re-sent in a later session with another sequence
+        let mut resent_with_pde = copy.clone();
+        resent_with_pde.basic_header.session_number = 124;
+        resent_with_pde.basic_header.sequence_number = 789;
+        resent_with_pde.trailer.pde = Some("1234230102FOOBARXXAXXX0123000456".to_string());
+        assert_eq!(
+            Fingerprint::of(&resent_with_pde, Strictness::Exact),
+            Fingerprint::of(&original, Strictness::Exact)
+        );
+
+        assert_eq!(
+            Fingerprint::of(&original, Strictness::BusinessEqual),
+            Fingerprint::of(&resent, Strictness::BusinessEqual)
+        );
+
+        let fingerprint = Fingerprint::of(&original, Strictness::HeaderInsensitive);
+        assert_eq!(
+            fingerprint.to_string().parse::<Fingerprint>().unwrap(),
+            fingerprint
+        );
+
+        let mut seen = MemorySeenSet::new();
+        assert!(seen
+            .check_message(&original, Strictness::BusinessEqual)
+            .unwrap());
+        assert!(!seen
+            .check_message(&resent, Strictness::BusinessEqual)
+            .unwrap());
+        assert_eq!(seen.len(), 1);
+
+        let path = env::temp_dir().join(format!("rustmsg-seen-{}.txt", std::process::id()));
+        let _ = fs::remove_file(&path);
+        let mut seen = FileSeenSet::open(&path).unwrap();
+        assert!(seen.insert(fingerprint).unwrap());
+        assert!(!seen.insert(fingerprint).unwrap());
+
+        let seen = FileSeenSet::open(&path).unwrap();
+        assert_eq!(seen.len(), 1);
+        assert!(seen.contains(&fingerprint));
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn negative_tests() {
+        let original = parse("FOOBARXXAXXX0123000456", "", ":20:REF123");
+        let other_receiver = SwiftMtParser::new()
+            .parse(
+                "{1:F01FOOBARXXAXXX0123000456}{2:I103BANKGB2LXXXXN}{4:\r\n:20:REF123\r\n-}"
+                    .to_string(),
+            )
+            .unwrap();
+        assert_ne!(
+            Fingerprint::of(&original, Strictness::BusinessEqual),
+            Fingerprint::of(&other_receiver, Strictness::BusinessEqual)
+        );
+        assert_eq!(
+            Fingerprint::of(&original, Strictness::HeaderInsensitive),
+            Fingerprint::of(&other_receiver, Strictness::HeaderInsensitive)
+        );
+
+        // another MUR or UETR is another payment of the same amount
+        let payment =
+            |user_header: &str| parse("FOOBARXXAXXX0123000456", user_header, ":20:REF123");
+        let uetr = "{121:eb6305c9-1f7f-49de-aed0-16487c27b42d}";
+        for (left, right) in [
+            ("{3:{108:MUR1}}", "{3:{108:MUR2}}"),
+            ("{3:{108:MUR1}}", ""),
+            (&format!("{{3:{}}}", uetr)[..], ""),
+            (
+                &format!("{{3:{}}}", uetr)[..],
+                "{3:{121:4f9a4e2c-8a34-4b57-9c6d-7e3f0a1b2c3d}}",
+            ),
+        ] {
+            assert_ne!(
+                Fingerprint::of(&payment(left), Strictness::BusinessEqual),
+                Fingerprint::of(&payment(right), Strictness::BusinessEqual)
+            );
+            assert_eq!(
+                Fingerprint::of(&payment(left), Strictness::HeaderInsensitive),
+                Fingerprint::of(&payment(right), Strictness::HeaderInsensitive)
+            );
+        }
+
+        assert!("abc".parse::<Fingerprint>().is_err());
+        assert!("zz".repeat(32).parse::<Fingerprint>().is_err());
+    }
+}
@@ -0,0 +1,638 @@
+use crate::swift::amount::Amount;
+use crate::swift::bic::{Bic, LogicalTerminalAddress};
+use crate::swift::currency::Currency;
+use crate::swift::datetime::{LocalDateTime, SwiftDate, SwiftTime, UtcOffset};
+use crate::swift::mt::builder::MessageBuilder;
+use crate::swift::mt::fields::AmountField;
+use crate::swift::mt::header_fields::{ServiceTypeIdentifier, Uetr};
+use crate::swift::mt::model::ApplicationHeader;
+use crate::swift::mt::swift_mt_parser::SwiftMtMessage;
+use crate::swift::mt::validator::{ValidationError, GPI_ERROR};
+use chrono::Local;
+use std::fmt;
+use std::io;
+use std::io::ErrorKind::InvalidData;
+
+/// BIC of the gpi Tracker, the receiver of status confirmations
+pub static TRACKER_BIC: &str = "TRCKCHZZ";
+
+/// Reasons of the pending status ACSP: credited or forwarded to the next gpi agent (G000),
+/// forwarded to a non-gpi agent (G001), credit may not be confirmed the same day (G002) and
+/// credit pending documents (G003) or funds (G004)
+pub static PENDING_REASONS: [&str; 5] = ["G000", "G001", "G002", "G003", "G004"];
+
+/// Status of a payment reported to the gpi Tracker
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum TransactionStatus {
+    /// Accepted settlement in process, forwarded or pending
+    Acsp,
+    /// Accepted settlement completed, credited to the beneficiary
+    Accc,
+    /// Rejected
+    Rjct,
+}
+
+/// Currency and amount of a confirmation line
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct GpiAmount {
+    pub currency: Currency,
+    pub amount: Amount,
+}
+
+/// Status confirmation of a gpi payment sent to the Tracker as MT199 (customer transfers) or
+/// MT299 (cover payments) with service type identifier 001 and the UETR of the payment. Field
+/// 21 holds the reference of the confirmed payment and field 79 the status in lines of the
+/// form:
+///
+/// ```text
+/// //2301021530+0100      date and time of the status with the offset from UTC
+/// //ACSP/G000            status, followed by the reason for ACSP and RJCT
+/// //BANKDEFFXXX/BANKGB2L status originator, followed by the agent forwarded to
+/// //EUR1000,             confirmed amount
+/// //EUR5,                charges deducted, one line per charge amount (optional)
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct GpiConfirmation {
+    pub message_type: String,
+    pub uetr: Uetr,
+    /// Field 20
+    pub reference: String,
+    /// Field 21, the reference of the confirmed payment
+    pub related_reference: String,
+    pub date_time: LocalDateTime,
+    pub status: TransactionStatus,
+    /// Reason code, one of `PENDING_REASONS` for ACSP or an ISO 20022 code like `AC01` for RJCT
+    pub reason: Option<String>,
+    pub originator: Bic,
+    pub forwarded_to: Option<Bic>,
+    pub amount: GpiAmount,
+    pub charges: Vec<GpiAmount>,
+}
+
+impl TransactionStatus {
+    pub fn parse(value: &str) -> Result<TransactionStatus, io::Error> {
+        return match value {
+            "ACSP" => Ok(TransactionStatus::Acsp),
+            "ACCC" => Ok(TransactionStatus::Accc),
+            "RJCT" => Ok(TransactionStatus::Rjct),
+            _ => Err(io::Error::new(
+                InvalidData,
+                format!("Unknown gpi transaction status {}", value),
+            )),
+        };
+    }
+
+    pub fn code(&self) -> &'static str {
+        return match self {
+            TransactionStatus::Acsp => "ACSP",
+            TransactionStatus::Accc => "ACCC",
+            TransactionStatus::Rjct => "RJCT",
+        };
+    }
+
+    /// Whether no further confirmations follow the status
+    pub fn is_final(&self) -> bool {
+        return *self != TransactionStatus::Acsp;
+    }
+}
+
+impl fmt::Display for TransactionStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        return write!(f, "{}", self.code());
+    }
+}
+
+impl GpiAmount {
+    pub fn new(currency: Currency, amount: Amount) -> GpiAmount {
+        return GpiAmount { currency, amount };
+    }
+
+    /// Parses currency and amount like `EUR1000,`
+    pub fn parse(value: &str) -> Result<GpiAmount, io::Error> {
+        if !value.is_ascii() || value.len() < 4 {
+            return Err(io::Error::new(
+                InvalidData,
+                format!("Expected currency and amount but got {}", value),
+            ));
+        }
+
+        return Ok(GpiAmount {
+            currency: value[0..3].parse()?,
+            amount: Amount::parse(&value[3..])?,
+        });
+    }
+}
+
+impl fmt::Display for GpiAmount {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        return write!(f, "{}{}", self.currency, self.amount);
+    }
+}
+
+impl GpiConfirmation {
+    /// Confirmation of a received gpi payment (MT103, MT202 or MT205 with a UETR) by its
+    /// receiver, confirming the interbank settlement amount of field 32A at the current time.
+    /// The reference is the confirmation's own field 20, the reference of the payment goes to
+    /// field 21.
+    pub fn for_payment(
+        payment: &SwiftMtMessage,
+        reference: &str,
+        status: TransactionStatus,
+    ) -> Result<GpiConfirmation, io::Error> {
+        let message_type = match payment.application_header.message_type() {
+            Some("103") => "199",
+            Some("202") | Some("205") => "299",
+            other => {
+                return Err(io::Error::new(
+                    InvalidData,
+                    format!("Expected an MT103, MT202 or MT205 but got {:?}", other),
+                ))
+            }
+        };
+
+        let uetr =
+            payment.user_header.uetr.clone().ok_or_else(|| {
+                io::Error::new(InvalidData, "Payment has no UETR (121) to confirm")
+            })?;
+        let related_reference = payment
+            .text
+            .field("20")
+            .map(|f| f.value.clone())
+            .ok_or_else(|| io::Error::new(InvalidData, "Payment has no reference (20)"))?;
+        let settlement = payment
+            .text
+            .field("32A")
+            .ok_or_else(|| io::Error::new(InvalidData, "Payment has no settlement amount (32A)"))
+            .and_then(AmountField::from_field)?;
+
+        let receiver = match &payment.application_header {
+            ApplicationHeader::Input { data } => &data.destination,
+            _ => &payment.basic_header.logical_terminal,
+        };
+        let now = Local::now();
+        let offset = UtcOffset::new(*now.offset());
+
+        return Ok(GpiConfirmation {
+            message_type: message_type.to_string(),
+            uetr,
+            reference: reference.to_string(),
+            related_reference,
+            date_time: LocalDateTime::new(now.naive_local()).with_offset(offset),
+            status,
+            reason: None,
+            originator: receiver.bic11(),
+            forwarded_to: None,
+            amount: GpiAmount::new(settlement.currency, settlement.amount),
+            charges: Vec::new(),
+        });
+    }
+
+    pub fn with_reference(mut self, reference: &str) -> GpiConfirmation {
+        self.reference = reference.to_string();
+        return self;
+    }
+
+    pub fn with_reason(mut self, reason: &str) -> GpiConfirmation {
+        self.reason = Some(reason.to_string());
+        return self;
+    }
+
+    /// Date and time of the status, in UTC if it has no offset
+    pub fn with_date_time(mut self, date_time: LocalDateTime) -> GpiConfirmation {
+        self.date_time = date_time;
+        return self;
+    }
+
+    pub fn with_originator(mut self, originator: Bic) -> GpiConfirmation {
+        self.originator = originator;
+        return self;
+    }
+
+    /// Agent the payment was forwarded to, for ACSP
+    pub fn with_forwarded_to(mut self, agent: Bic) -> GpiConfirmation {
+        self.forwarded_to = Some(agent);
+        return self;
+    }
+
+    /// Confirmed amount, if it differs from the settlement amount of the payment, e.g. after
+    /// a currency conversion
+    pub fn with_amount(mut self, amount: GpiAmount) -> GpiConfirmation {
+        self.amount = amount;
+        return self;
+    }
+
+    /// Adds charges deducted from the amount
+    pub fn with_charges(mut self, charges: GpiAmount) -> GpiConfirmation {
+        self.charges.push(charges);
+        return self;
+    }
+
+    /// Reads the confirmation from an MT199 or MT299 with service type identifier 001
+    pub fn from_message(message: &SwiftMtMessage) -> Result<GpiConfirmation, io::Error> {
+        let message_type = message.application_header.message_type().unwrap_or("");
+        if message_type != "199" && message_type != "299" {
+            return Err(io::Error::new(
+                InvalidData,
+                format!("Expected an MT199 or MT299 but got MT{}", message_type),
+            ));
+        }
+        if !message
+            .user_header
+            .service_type_identifier
+            .as_ref()
+            .is_some_and(|s| s.is_gpi())
+        {
+            return Err(io::Error::new(
+                InvalidData,
+                "Confirmation has no gpi service type identifier (111:001)",
+            ));
+        }
+
+        let uetr = message
+            .user_header
+            .uetr
+            .clone()
+            .ok_or_else(|| io::Error::new(InvalidData, "Confirmation has no UETR (121)"))?;
+        let field = |tag: &str| {
+            message
+                .text
+                .field(tag)
+                .map(|f| f.value.clone())
+                .ok_or_else(|| {
+                    io::Error::new(InvalidData, format!("Confirmation has no field {}", tag))
+                })
+        };
+
+        let status_lines = field("79")?;
+        let lines: Vec<&str> = status_lines
+            .lines()
+            .map(|line| {
+                line.strip_prefix("//").ok_or_else(|| {
+                    io::Error::new(
+                        InvalidData,
+                        format!("Status line does not start with //: {}", line),
+                    )
+                })
+            })
+            .collect::<Result<_, _>>()?;
+        if lines.len() < 4 {
+            return Err(io::Error::new(
+                InvalidData,
+                format!("Expected at least 4 status lines but got {}", lines.len()),
+            ));
+        }
+
+        let (status, reason) = match lines[1].split_once('/') {
+            Some((status, reason)) => (status, Some(reason.to_string())),
+            None => (lines[1], None),
+        };
+        let (originator, forwarded_to) = match lines[2].split_once('/') {
+            Some((originator, agent)) => (originator, Some(Bic::parse(agent)?)),
+            None => (lines[2], None),
+        };
+
+        let confirmation = GpiConfirmation {
+            message_type: message_type.to_string(),
+            uetr,
+            reference: field("20")?,
+            related_reference: field("21")?,
+            date_time: parse_date_time(lines[0])?,
+            status: TransactionStatus::parse(status)?,
+            reason,
+            originator: Bic::parse(originator)?,
+            forwarded_to,
+            amount: GpiAmount::parse(lines[3])?,
+            charges: lines[4..]
+                .iter()
+                .map(|line| GpiAmount::parse(line))
+                .collect::<Result<_, _>>()?,
+        };
+        confirmation.check()?;
+
+        return Ok(confirmation);
+    }
+
+    /// The confirmation as a message from the logical terminal to the Tracker
+    pub fn to_message(
+        &self,
+        sender: LogicalTerminalAddress,
+    ) -> Result<SwiftMtMessage, Vec<ValidationError>> {
+        if let Err(e) = self.check() {
+            return Err(vec![ValidationError::new(
+                GPI_ERROR,
+                Some("79"),
+                e.to_string(),
+            )]);
+        }
+
+        let tracker = Bic::parse(TRACKER_BIC).expect("Tracker BIC is invalid");
+        return MessageBuilder::new(&self.message_type, sender, &tracker)
+            .service_type_identifier(ServiceTypeIdentifier::gpi())
+            .uetr(self.uetr.clone())
+            .field("20", &self.reference)
+            .field("21", &self.related_reference)
+            .field("79", &self.status_lines().join("\r\n"))
+            .build();
+    }
+
+    /// The lines of field 79
+    pub fn status_lines(&self) -> Vec<String> {
+        let offset = self.date_time.offset.unwrap_or_else(UtcOffset::utc);
+        let mut lines = vec![
+            format!(
+                "//{}{}{}",
+                self.date_time.date().to_yymmdd(),
+                self.date_time.time().to_hhmm(),
+                offset
+            ),
+            match &self.reason {
+                Some(reason) => format!("//{}/{}", self.status, reason),
+                None => format!("//{}", self.status),
+            },
+            match &self.forwarded_to {
+                Some(agent) => format!("//{}/{}", self.originator, agent),
+                None => format!("//{}", self.originator),
+            },
+            format!("//{}", self.amount),
+        ];
+        lines.extend(self.charges.iter().map(|c| format!("//{}", c)));
+
+        return lines;
+    }
+
+    /// Checks the reason against the status: ACSP needs one of the `PENDING_REASONS`, RJCT a
+    /// reason code of 4 characters and ACCC none. Only ACSP may name the agent forwarded to.
+    fn check(&self) -> Result<(), io::Error> {
+        let valid = match (self.status, self.reason.as_deref()) {
+            (TransactionStatus::Acsp, Some(reason)) => PENDING_REASONS.contains(&reason),
+            (TransactionStatus::Rjct, Some(reason)) => {
+                reason.len() == 4 && reason.chars().all(|c| c.is_ascii_alphanumeric())
+            }
+            (TransactionStatus::Accc, None) => true,
+            _ => false,
+        };
+
+        if !valid {
+            return Err(io::Error::new(
+                InvalidData,
+                format!(
+                    "Invalid reason {:?} for status {}",
+                    self.reason, self.status
+                ),
+            ));
+        }
+        if self.forwarded_to.is_some() && self.status != TransactionStatus::Acsp {
+            return Err(io::Error::new(
+                InvalidData,
+                format!(
+                    "Only ACSP can be forwarded to an agent, not {}",
+                    self.status
+                ),
+            ));
+        }
+        if self.message_type != "199" && self.message_type != "299" {
+            return Err(io::Error::new(
+                InvalidData,
+                format!(
+                    "Confirmations are MT199 or MT299, not MT{}",
+                    self.message_type
+                ),
+            ));
+        }
+
+        return Ok(());
+    }
+}
+
+/// Checks that the messages of a payment chain, e.g. an MT103, the MT202COV covering it and
+/// the confirmations of the agents, all carry the same UETR. Confirmations must refer to one
+/// of the payments of the chain and no confirmation may follow a final status (ACCC or RJCT)
+/// of the same originator.
+pub fn check_uetr_chain(messages: &[SwiftMtMessage]) -> Vec<ValidationError> {
+    let mut errors = Vec::new();
+    let describe = |index: usize, message: &SwiftMtMessage| {
+        format!(
+            "Message {} (MT{} {})",
+            index + 1,
+            message.application_header.message_type().unwrap_or(""),
+            message.text.field("20").map_or("", |f| f.value.as_str())
+        )
+    };
+
+    let mut expected: Option<&Uetr> = None;
+    for (index, message) in messages.iter().enumerate() {
+        match (&message.user_header.uetr, expected) {
+            (None, _) => errors.push(ValidationError::new(
+                GPI_ERROR,
+                Some("121"),
+                format!("{} has no UETR", describe(index, message)),
+            )),
+            (Some(uetr), Some(first)) if uetr != first => errors.push(ValidationError::new(
+                GPI_ERROR,
+                Some("121"),
+                format!(
+                    "{} has UETR {} instead of {}",
+                    describe(index, message),
+                    uetr,
+                    first
+                ),
+            )),
+            (Some(uetr), _) => expected = expected.or(Some(uetr)),
+        }
+    }
+
+    let payment_references: Vec<&str> = messages
+        .iter()
+        .filter(|m| {
+            ["103", "202", "205"].contains(&m.application_header.message_type().unwrap_or(""))
+        })
+        .filter_map(|m| m.text.field("20").map(|f| f.value.as_str()))
+        .collect();
+    let mut final_statuses: Vec<Bic> = Vec::new();
+
+    for (index, message) in messages.iter().enumerate() {
+        let Ok(confirmation) = GpiConfirmation::from_message(message) else {
+            continue;
+        };
+
+        if !payment_references.is_empty()
+            && !payment_references.contains(&confirmation.related_reference.as_str())
+        {
+            errors.push(ValidationError::new(
+                GPI_ERROR,
+                Some("21"),
+                format!(
+                    "{} confirms {} which is not a payment of the chain",
+                    describe(index, message),
+                    confirmation.related_reference
+                ),
+            ));
+        }
+        if final_statuses.contains(&confirmation.originator) {
+            errors.push(ValidationError::new(
+                GPI_ERROR,
+                Some("79"),
+                format!(
+                    "{} follows a final status of {}",
+                    describe(index, message),
+                    confirmation.originator
+                ),
+            ));
+        }
+        if confirmation.status.is_final() {
+            final_statuses.push(confirmation.originator);
+        }
+    }
+
+    return errors;
+}
+
+/// Parses `YYMMDDHHMM` followed by the offset from UTC
+fn parse_date_time(value: &str) -> Result<LocalDateTime, io::Error> {
+    if value.len() != 15 || !value.is_ascii() {
+        return Err(io::Error::new(
+            InvalidData,
+            format!("Expected YYMMDDHHMM+HHMM but got {}", value),
+        ));
+    }
+
+    return Ok(LocalDateTime::from_parts(
+        SwiftDate::parse_yymmdd(&value[0..6])?,
+        SwiftTime::parse_hhmm(&value[6..10])?,
+    )
+    .with_offset(UtcOffset::parse(&value[10..15])?));
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::swift::amount::Amount;
+    use crate::swift::bic::{Bic, LogicalTerminalAddress};
+    use crate::swift::currency::Currency;
+    use crate::swift::datetime::{LocalDateTime, UtcOffset};
+    use crate::swift::mt::gpi::{check_uetr_chain, GpiAmount, GpiConfirmation, TransactionStatus};
+    use crate::swift::mt::{SwiftMtMessage, SwiftMtParser};
+
+    static UETR: &str = "eb6305c9-1f7f-49de-aed0-16487c27b42d";
+    static PAYMENT: &str = "{1:F01BANKDEFFAXXX0123000456}{2:O1031530230102FOOBARXXAXXX01230004562301021530N}{3:{111:001}{121:eb6305c9-1f7f-49de-aed0-16487c27b42d}}{4:\r\n:20:PAY1\r\n:23B:CRED\r\n:32A:230102EUR1000,\r\n:50K:JOHN DOE\r\n:59:JANE DOE\r\n:71A:SHA\r\n-}";
+
+    fn parse(raw: &str) -> SwiftMtMessage {
+        return SwiftMtParser::new().parse(raw.to_string()).unwrap();
+    }
+
+    fn confirmation(status: TransactionStatus) -> GpiConfirmation {
+        return GpiConfirmation::for_payment(&parse(PAYMENT), "CONF1", status)
+            .unwrap()
+            .with_date_time(
+                LocalDateTime::parse_yyyymmddhhmmss("20230102160000")
+                    .unwrap()
+                    .with_offset(UtcOffset::parse("+0100").unwrap()),
+            );
+    }
+
+    fn sender() -> LogicalTerminalAddress {
+        return LogicalTerminalAddress::parse("BANKDEFFAXXX").unwrap();
+    }
+
+    #[test]
+    fn positive_tests() {
+        let accc = confirmation(TransactionStatus::Accc)
+            .with_charges(GpiAmount::new(Currency::EUR, Amount::parse("5,").unwrap()));
+        assert_eq!(accc.reference, "CONF1");
+        assert_eq!(accc.related_reference, "PAY1");
+        assert_eq!(accc.originator.as_str(), "BANKDEFFXXX");
+        assert_eq!(
+            accc.status_lines(),
+            vec![
+                "//2301021600+0100",
+                "//ACCC",
+                "//BANKDEFFXXX",
+                "//EUR1000,",
+                "//EUR5,"
+            ]
+        );
+
+        let message = accc.to_message(sender()).unwrap();
+        let raw = message.to_raw();
+        assert!(raw.contains(&format!("{{3:{{111:001}}{{121:{}}}}}", UETR)));
+        assert!(raw.contains("{2:I199TRCKCHZZXXXXN}"));
+        assert_eq!(GpiConfirmation::from_message(&parse(&raw)).unwrap(), accc);
+
+        let acsp = confirmation(TransactionStatus::Acsp)
+            .with_reason("G000")
+            .with_forwarded_to(Bic::parse("BANKGB2L").unwrap());
+        assert_eq!(acsp.status_lines()[1], "//ACSP/G000");
+        assert_eq!(acsp.status_lines()[2], "//BANKDEFFXXX/BANKGB2L");
+        let acsp_message = acsp.to_message(sender()).unwrap();
+        assert_eq!(GpiConfirmation::from_message(&acsp_message).unwrap(), acsp);
+
+        let chain = vec![parse(PAYMENT), acsp_message, message];
+        assert!(check_uetr_chain(&chain).is_empty());
+    }
+
+    #[test]
+    fn negative_tests() {
+        let without_uetr = PAYMENT.replace(&format!("{{121:{}}}", UETR), "");
+        assert!(GpiConfirmation::for_payment(
+            &parse(&without_uetr),
+            "CONF1",
+            TransactionStatus::Accc
+        )
+        .is_err());
+        assert!(confirmation(TransactionStatus::Acsp)
+            .to_message(sender())
+            .is_err());
+        assert!(confirmation(TransactionStatus::Rjct)
+            .with_reason("AC01")
+            .to_message(sender())
+            .is_ok());
+        assert!(confirmation(TransactionStatus::Acsp)
+            .with_reason("G009")
+            .to_message(sender())
+            .is_err());
+        for (status, reason) in [
+            (TransactionStatus::Accc, None),
+            (TransactionStatus::Rjct, Some("AC01")),
+        ] {
+            let mut forwarded =
+                confirmation(status).with_forwarded_to(Bic::parse("BANKGB2L").unwrap());
+            forwarded.reason = reason.map(|r| r.to_string());
+            assert!(forwarded.to_message(sender()).is_err());
+        }
+
+        let valid = confirmation(TransactionStatus::Accc)
+            .to_message(sender())
+            .unwrap()
+            .to_raw();
+        assert!(GpiConfirmation::from_message(&parse(&valid.replace("{111:001}", ""))).is_err());
+        assert!(GpiConfirmation::from_message(&parse(&valid.replace("//ACCC", "//DONE"))).is_err());
+        assert!(
+            GpiConfirmation::from_message(&parse(&valid.replace("//EUR1000,", "EUR1000,")))
+                .is_err()
+        );
+        assert!(GpiConfirmation::from_message(&parse(PAYMENT)).is_err());
+        assert!(GpiConfirmation::from_message(&parse(
+            &valid.replace("//BANKDEFFXXX", "//BANKDEFFXXX/BANKGB2L")
+        ))
+        .is_err());
+
+        let other_uetr = "0f5a7c3e-2b1d-4e8f-9a6b-3c2d1e0f9a8b";
+        let chain = vec![
+            parse(PAYMENT),
+            parse(&valid.replace(UETR, other_uetr)),
+            parse(&valid.replace(":21:PAY1", ":21:PAY9")),
+            parse(&valid),
+            parse(&without_uetr),
+        ];
+        let errors = check_uetr_chain(&chain);
+        let messages: Vec<&str> = errors.iter().map(|e| e.message.as_str()).collect();
+        assert_eq!(errors.len(), 5, "{:?}", messages);
+        assert!(messages[0].contains("Message 2 (MT199 CONF1) has UETR"));
+        assert!(messages[1].contains("has no UETR"));
+        assert!(messages[2].contains("confirms PAY9"));
+        assert!(messages[3].contains("Message 3 (MT199 CONF1) follows a final status"));
+        assert!(messages[4].contains("Message 4 (MT199 CONF1) follows a final status"));
+    }
+}
@@ -0,0 +1,438 @@
+use crate::swift::bic::LogicalTerminalAddress;
+use crate::swift::datetime::SwiftDate;
+use std::fmt;
+use std::io;
+use std::io::ErrorKind::InvalidData;
+use std::str::FromStr;
+
+/// Service type identifier of SWIFT gpi (field 111)
+pub static GPI_SERVICE_TYPE: &str = "001";
+
+/// Unique end-to-end transaction reference (field 121), a UUID of version 4 in lower case
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Uetr {
+    value: String,
+}
+
+/// Validation flag (field 119) requesting validation against the rules of a message variant
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum ValidationFlag {
+    /// MT 103 STP
+    Stp,
+    /// MT 103 REMIT
+    Remit,
+    /// MT 104 request for direct debit
+    Rfdd,
+    /// MT 202 COV and MT 205 COV
+    Cov,
+    /// Any other flag, kept as it is and reported by the `Validator`
+    Other(String),
+}
+
+/// Service type identifier (field 111)
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ServiceTypeIdentifier {
+    value: String,
+}
+
+/// Message input reference (field 106): input date, sender LT address, session and sequence
+/// number of the message in the form it was sent
+#[derive(Debug, Clone, PartialEq)]
+pub struct Mir {
+    pub date: SwiftDate,
+    pub logical_terminal: LogicalTerminalAddress,
+    pub session_number: u32,
+    pub sequence_number: u32,
+}
+
+/// Code word of the sanctions screening information (field 433)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ScreeningCodeWord {
+    /// Message passed the screening
+    Aok,
+    /// Message was found to be a false positive
+    Fpo,
+    /// Message did not pass the screening
+    Nok,
+}
+
+/// Sanctions screening information for the receiver (field 433), `/3!a/[20x]`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScreeningInformation {
+    pub code_word: ScreeningCodeWord,
+    pub additional_information: Option<String>,
+}
+
+impl Uetr {
+    /// Parses a UETR in the format xxxxxxxx-xxxx-4xxx-yxxx-xxxxxxxxxxxx with lower case
+    /// hexadecimal digits, where y is one of 8, 9, a or b
+    pub fn parse(value: &str) -> Result<Uetr, io::Error> {
+        let chars: Vec<char> = value.chars().collect();
+        let valid = chars.len() == 36
+            && chars.iter().enumerate().all(|(i, c)| match i {
+                8 | 13 | 18 | 23 => *c == '-',
+                _ => c.is_ascii_digit() || ('a'..='f').contains(c),
+            })
+            && chars[14] == '4'
+            && ['8', '9', 'a', 'b'].contains(&chars[19]);
+
+        if !valid {
+            return Err(io::Error::new(
+                InvalidData,
+                format!("UETR {} is not a version 4 UUID in lower case", value),
+            ));
+        }
+
+        return Ok(Uetr {
+            value: value.to_string(),
+        });
+    }
+
+    /// Generates a new random UETR
+    pub fn generate() -> Uetr {
+        return Uetr {
+            value: uuid::Uuid::new_v4().hyphenated().to_string(),
+        };
+    }
+
+    pub fn as_str(&self) -> &str {
+        return &self.value;
+    }
+}
+
+impl FromStr for Uetr {
+    type Err = io::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        return Uetr::parse(s);
+    }
+}
+
+impl fmt::Display for Uetr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        return write!(f, "{}", self.value);
+    }
+}
+
+impl PartialEq<&str> for Uetr {
+    fn eq(&self, other: &&str) -> bool {
+        return self.value == *other;
+    }
+}
+
+impl ValidationFlag {
+    /// Parses a validation flag, keeping unknown flags as `Other`. Only an empty flag is an
+    /// error.
+    pub fn parse(value: &str) -> Result<ValidationFlag, io::Error> {
+        return match value {
+            "" => Err(io::Error::new(InvalidData, "Validation flag is empty")),
+            "STP" => Ok(ValidationFlag::Stp),
+            "REMIT" => Ok(ValidationFlag::Remit),
+            "RFDD" => Ok(ValidationFlag::Rfdd),
+            "COV" => Ok(ValidationFlag::Cov),
+            _ => Ok(ValidationFlag::Other(value.to_string())),
+        };
+    }
+
+    pub fn code(&self) -> &str {
+        return match self {
+            ValidationFlag::Stp => "STP",
+            ValidationFlag::Remit => "REMIT",
+            ValidationFlag::Rfdd => "RFDD",
+            ValidationFlag::Cov => "COV",
+            ValidationFlag::Other(code) => code,
+        };
+    }
+
+    /// Whether the flag is one of the flags defined by SWIFT
+    pub fn is_known(&self) -> bool {
+        return !matches!(self, ValidationFlag::Other(_));
+    }
+}
+
+impl FromStr for ValidationFlag {
+    type Err = io::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        return ValidationFlag::parse(s);
+    }
+}
+
+impl fmt::Display for ValidationFlag {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        return write!(f, "{}", self.code());
+    }
+}
+
+impl ServiceTypeIdentifier {
+    /// Parses a service type identifier in the format `3!n`
+    pub fn parse(value: &str) -> Result<ServiceTypeIdentifier, io::Error> {
+        if value.len() != 3 || !value.chars().all(|c| c.is_ascii_digit()) {
+            return Err(io::Error::new(
+                InvalidData,
+                format!("Service type identifier must have 3 digits: {}", value),
+            ));
+        }
+
+        return Ok(ServiceTypeIdentifier {
+            value: value.to_string(),
+        });
+    }
+
+    pub fn gpi() -> ServiceTypeIdentifier {
+        return ServiceTypeIdentifier {
+            value: GPI_SERVICE_TYPE.to_string(),
+        };
+    }
+
+    pub fn is_gpi(&self) -> bool {
+        return self.value == GPI_SERVICE_TYPE;
+    }
+
+    pub fn as_str(&self) -> &str {
+        return &self.value;
+    }
+}
+
+impl FromStr for ServiceTypeIdentifier {
+    type Err = io::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        return ServiceTypeIdentifier::parse(s);
+    }
+}
+
+impl fmt::Display for ServiceTypeIdentifier {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        return write!(f, "{}", self.value);
+    }
+}
+
+impl PartialEq<&str> for ServiceTypeIdentifier {
+    fn eq(&self, other: &&str) -> bool {
+        return self.value == *other;
+    }
+}
+
+impl Mir {
+    /// Parses a MIR in the format `6!n12!x4!n6!n` (YYMMDD, LT address, session, sequence)
+    pub fn parse(value: &str) -> Result<Mir, io::Error> {
+        if value.len() != 28 || !value.is_ascii() {
+            return Err(io::Error::new(
+                InvalidData,
+                format!("MIR must have 28 characters: {}", value),
+            ));
+        }
+
+        let number = |part: &str| {
+            part.parse::<u32>().map_err(|e| {
+                io::Error::new(
+                    InvalidData,
+                    format!("Invalid number in MIR {}: {}", value, e),
+                )
+            })
+        };
+
+        return Ok(Mir {
+            date: SwiftDate::parse_yymmdd(&value[0..6])?,
+            logical_terminal: LogicalTerminalAddress::parse(&value[6..18])?,
+            session_number: number(&value[18..22])?,
+            sequence_number: number(&value[22..28])?,
+        });
+    }
+}
+
+impl FromStr for Mir {
+    type Err = io::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        return Mir::parse(s);
+    }
+}
+
+impl fmt::Display for Mir {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        return write!(
+            f,
+            "{}{}{:04}{:06}",
+            self.date.to_yymmdd(),
+            self.logical_terminal,
+            self.session_number,
+            self.sequence_number
+        );
+    }
+}
+
+impl ScreeningCodeWord {
+    pub fn parse(value: &str) -> Result<ScreeningCodeWord, io::Error> {
+        return match value {
+            "AOK" => Ok(ScreeningCodeWord::Aok),
+            "FPO" => Ok(ScreeningCodeWord::Fpo),
+            "NOK" => Ok(ScreeningCodeWord::Nok),
+            _ => Err(io::Error::new(
+                InvalidData,
+                format!("Unknown screening code word: {}", value),
+            )),
+        };
+    }
+
+    pub fn code(&self) -> &'static str {
+        return match self {
+            ScreeningCodeWord::Aok => "AOK",
+            ScreeningCodeWord::Fpo => "FPO",
+            ScreeningCodeWord::Nok => "NOK",
+        };
+    }
+}
+
+impl FromStr for ScreeningCodeWord {
+    type Err = io::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        return ScreeningCodeWord::parse(s);
+    }
+}
+
+impl fmt::Display for ScreeningCodeWord {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        return write!(f, "{}", self.code());
+    }
+}
+
+impl ScreeningInformation {
+    pub fn new(code_word: ScreeningCodeWord) -> ScreeningInformation {
+        return ScreeningInformation {
+            code_word,
+            additional_information: None,
+        };
+    }
+
+    pub fn with_additional_information(mut self, information: &str) -> Self {
+        self.additional_information = Some(information.to_string());
+        return self;
+    }
+
+    /// Parses the code word between slashes followed by at most 20 characters of information
+    pub fn parse(value: &str) -> Result<ScreeningInformation, io::Error> {
+        let (code_word, information) = value
+            .strip_prefix('/')
+            .and_then(|v| v.split_once('/'))
+            .ok_or_else(|| {
+                io::Error::new(
+                    InvalidData,
+                    format!(
+                        "Screening information must start with /code word/: {}",
+                        value
+                    ),
+                )
+            })?;
+
+        if information.chars().count() > 20 {
+            return Err(io::Error::new(
+                InvalidData,
+                format!(
+                    "Additional screening information exceeds 20 characters: {}",
+                    information
+                ),
+            ));
+        }
+
+        return Ok(ScreeningInformation {
+            code_word: ScreeningCodeWord::parse(code_word)?,
+            additional_information: Some(information)
+                .filter(|i| !i.is_empty())
+                .map(|i| i.to_string()),
+        });
+    }
+}
+
+impl FromStr for ScreeningInformation {
+    type Err = io::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        return ScreeningInformation::parse(s);
+    }
+}
+
+impl fmt::Display for ScreeningInformation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        return write!(
+            f,
+            "/{}/{}",
+            self.code_word,
+            self.additional_information.as_deref().unwrap_or("")
+        );
+    }
+}
+
+serde_as_string!(Uetr);
+serde_as_string!(ValidationFlag);
+serde_as_string!(ServiceTypeIdentifier);
+serde_as_string!(Mir);
+serde_as_string!(ScreeningCodeWord);
+serde_as_string!(ScreeningInformation);
+
+#[cfg(test)]
+mod tests {
+    use crate::swift::mt::header_fields::{
+        Mir, ScreeningCodeWord, ScreeningInformation, ServiceTypeIdentifier, Uetr, ValidationFlag,
+    };
+    use chrono::NaiveDate;
+
+    #[test]
+    fn positive_tests() {
+        let uetr = Uetr::parse("eb6305c9-1f7f-49de-aed0-16487c27b42d").unwrap();
+        assert_eq!(uetr, "eb6305c9-1f7f-49de-aed0-16487c27b42d");
+        assert!(Uetr::parse(Uetr::generate().as_str()).is_ok());
+
+        assert_eq!(ValidationFlag::parse("STP").unwrap(), ValidationFlag::Stp);
+        assert_eq!(ValidationFlag::Cov.to_string(), "COV");
+
+        assert!(ServiceTypeIdentifier::parse("001").unwrap().is_gpi());
+        assert!(!ServiceTypeIdentifier::parse("002").unwrap().is_gpi());
+
+        let mir = Mir::parse("230102FOOBARXXAXXX0123000456").unwrap();
+        assert_eq!(
+            mir.date.date(),
+            NaiveDate::from_ymd_opt(2023, 1, 2).unwrap()
+        );
+        assert_eq!(mir.logical_terminal, "FOOBARXXAXXX");
+        assert_eq!(mir.session_number, 123);
+        assert_eq!(mir.sequence_number, 456);
+        assert_eq!(mir.to_string(), "230102FOOBARXXAXXX0123000456");
+
+        let screening = ScreeningInformation::parse("/FPO/CHECKED BY JD").unwrap();
+        assert_eq!(screening.code_word, ScreeningCodeWord::Fpo);
+        assert_eq!(
+            screening.additional_information.as_deref(),
+            Some("CHECKED BY JD")
+        );
+        assert_eq!(screening.to_string(), "/FPO/CHECKED BY JD");
+
+        let screening = ScreeningInformation::parse("/AOK/").unwrap();
+        assert_eq!(screening, ScreeningInformation::new(ScreeningCodeWord::Aok));
+        assert_eq!(screening.to_string(), "/AOK/");
+    }
+
+    #[test]
+    fn negative_tests() {
+        assert!(Uetr::parse("EB6305C9-1F7F-49DE-AED0-16487C27B42D").is_err());
+        assert!(Uetr::parse("eb6305c9-1f7f-39de-aed0-16487c27b42d").is_err());
+        assert!(Uetr::parse("eb6305c9-1f7f-49de-ced0-16487c27b42d").is_err());
+        assert!(Uetr::parse("eb6305c91f7f49deaed016487c27b42d").is_err());
+
+        assert_eq!(
+            ValidationFlag::parse("stp").unwrap(),
+            ValidationFlag::Other("stp".to_string())
+        );
+        assert!(!ValidationFlag::parse("stp").unwrap().is_known());
+        assert!(ValidationFlag::parse("").is_err());
+        assert!(ServiceTypeIdentifier::parse("01").is_err());
+        assert!(Mir::parse("230102FOOBARXXAXXX012300045").is_err());
+        assert!(Mir::parse("231302FOOBARXXAXXX0123000456").is_err());
+
+        assert!(ScreeningInformation::parse("AOK").is_err());
+        assert!(ScreeningInformation::parse("/XYZ/").is_err());
+        assert!(ScreeningInformation::parse("/NOK/THIS TEXT IS TOO LONG FOR IT").is_err());
+    }
+}
@@ -1,4 +1,69 @@
 mod swift_mt_parser;
-mod model;
+pub mod model;
+mod text;
+mod rules;
+mod validator;
+mod fields;
+mod header_fields;
+mod acknowledgement;
+mod system_messages;
+mod builder;
+mod trailer_fields;
+mod fingerprint;
+mod definitions;
+mod rje;
+mod diff;
+mod sequences;
+mod path;
+mod anonymiser;
+mod screening;
+mod reader;
+mod simulator;
+mod store;
+mod gpi;
+#[cfg(feature = "tokio")]
+mod async_reader;
+#[cfg(feature = "router")]
+mod router;
 
-pub use swift_mt_parser::SwiftMtParser;
\ No newline at end of file
+pub use swift_mt_parser::{ParsingError, SwiftMtMessage, SwiftMtParser};
+pub use text::{tag_matches, Field, MessageText};
+pub use rules::{Condition, Operand, Rule, RuleSet, RuleViolation};
+pub use validator::{
+    ValidationError, Validator, AMOUNT_ERROR, BIC_ERROR, CHARSET_ERROR, DECIMALS_ERROR,
+    GPI_ERROR, HEADER_ERROR, IBAN_ERROR,
+};
+pub use fields::{AmountField, PartyField};
+pub use acknowledgement::Acknowledgement;
+pub use system_messages::{
+    AbortNotification, DeliveryNotification, SenderNotification, ServiceAcknowledgement,
+    ServiceRequest, SystemMessage, UndeliveredMessage, UndeliveredMessageReport,
+};
+pub use header_fields::{
+    Mir, ScreeningCodeWord, ScreeningInformation, ServiceTypeIdentifier, Uetr, ValidationFlag,
+    GPI_SERVICE_TYPE,
+};
+pub use builder::{MessageBuilder, Priority};
+pub use trailer_fields::{
+    compute_checksum, is_possible_duplicate, set_checksum, set_possible_duplicate_emission,
+    set_possible_duplicate_message, verify_checksum, PossibleDuplicate,
+};
+pub use fingerprint::{FileSeenSet, Fingerprint, MemorySeenSet, SeenSet, Strictness};
+pub use definitions::{FieldDefinition, FieldDefinitions};
+pub use rje::{join_rje, split_rje};
+pub use diff::{diff, Difference, DifferenceKind};
+pub use path::{MessagePath, Occurrence, Value};
+pub use anonymiser::Anonymiser;
+pub use screening::set_screening_information;
+pub use reader::MessageReader;
+pub use simulator::{
+    FinSimulator, Submission, SIMULATOR_FORMAT_ERROR, SIMULATOR_VALIDATION_ERROR,
+};
+pub use store::{MessageQuery, MessageStore, StoredMessage};
+pub use gpi::{
+    check_uetr_chain, GpiAmount, GpiConfirmation, TransactionStatus, PENDING_REASONS, TRACKER_BIC,
+};
+#[cfg(feature = "tokio")]
+pub use async_reader::AsyncMessageReader;
+#[cfg(feature = "router")]
+pub use router::{Route, RouteCriterion, Router, RoutingDecision};
\ No newline at end of file
@@ -1,12 +1,15 @@
+use crate::swift::bic::LogicalTerminalAddress;
+use crate::swift::datetime::{LocalDateTime, SwiftDate, SwiftTime};
+use crate::swift::mt::header_fields::{
+    Mir, ScreeningInformation, ServiceTypeIdentifier, Uetr, ValidationFlag,
+};
 use crate::utils::StringParser;
-use chrono::DateTime;
-use chrono::NaiveDateTime;
-use chrono::Utc;
 use std::collections::HashMap;
 use std::io;
 use std::io::ErrorKind::InvalidData;
 
-#[derive(FromPrimitive, Debug, PartialEq)]
+#[derive(FromPrimitive, Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum ServiceIdentifier {
     Message = 1,
     LoginRequest = 2,
@@ -24,20 +27,28 @@ pub enum ServiceIdentifier {
     SelectNegativeAck = 43,
 }
 
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct BasicHeader {
     pub application_identifier: String,
     pub service_identifier: ServiceIdentifier,
-    pub logical_terminal: String,
+    pub logical_terminal: LogicalTerminalAddress,
     pub session_number: u32,
     pub sequence_number: u32,
 }
 
+impl Default for BasicHeader {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl BasicHeader {
     pub fn new() -> BasicHeader {
         return BasicHeader {
             application_identifier: "F".into(),
             service_identifier: ServiceIdentifier::Message,
-            logical_terminal: "            ".into(),
+            logical_terminal: LogicalTerminalAddress::default(),
             session_number: 0,
             sequence_number: 0,
         };
@@ -46,7 +57,7 @@ impl BasicHeader {
     pub fn from_raw(parser: &mut StringParser) -> Result<BasicHeader, io::Error> {
         let application_identifier = parser.n_chars(1)?;
         let service_identifier_raw = parser.n_chars(2)?;
-        let logical_terminal = parser.n_chars(12)?;
+        let logical_terminal = LogicalTerminalAddress::parse(&parser.n_chars(12)?)?;
         let session_number: u32 = parser
             .n_chars(4)?
             .parse::<u32>()
@@ -82,42 +93,92 @@ impl BasicHeader {
             sequence_number,
         });
     }
+
+    pub fn to_raw(&self) -> String {
+        return format!(
+            "{{1:{}{:02}{}{:04}{:06}}}",
+            self.application_identifier,
+            self.service_identifier as u32,
+            self.logical_terminal,
+            self.session_number,
+            self.sequence_number
+        );
+    }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum ApplicationHeader {
     Input { data: InputData },
     Output { data: OutputData },
     Empty,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct InputData {
     pub message_type: String,
-    pub destination: String,
+    pub destination: LogicalTerminalAddress,
     pub priority: String,
     pub delivery_monitoring: String,
     pub obsolescence_period: String,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct OutputData {
     pub message_type: String,
-    pub sender_datetime: DateTime<Utc>,
-    pub sender_address: String,
+    /// Input date and time of the message, in the local time of the sender
+    pub sender_datetime: LocalDateTime,
+    pub sender_address: LogicalTerminalAddress,
     pub session_number: String,
     pub sequence_number: String,
-    pub receiver_datetime: DateTime<Utc>,
+    /// Output date and time of the message, in the local time of the receiver
+    pub receiver_datetime: LocalDateTime,
     pub message_priority: String,
 }
 
 impl ApplicationHeader {
+    pub fn message_type(&self) -> Option<&str> {
+        return match self {
+            ApplicationHeader::Input { data } => Some(&data.message_type),
+            ApplicationHeader::Output { data } => Some(&data.message_type),
+            ApplicationHeader::Empty => None,
+        };
+    }
+
+    pub fn to_raw(&self) -> String {
+        return match self {
+            ApplicationHeader::Input { data } => format!(
+                "{{2:I{}{}{}{}{}}}",
+                data.message_type,
+                data.destination,
+                data.priority,
+                data.delivery_monitoring,
+                data.obsolescence_period
+            ),
+            ApplicationHeader::Output { data } => format!(
+                "{{2:O{}{}{}{}{}{}{}{}{}}}",
+                data.message_type,
+                data.sender_datetime.time().to_hhmm(),
+                data.sender_datetime.date().to_yymmdd(),
+                data.sender_address,
+                data.session_number,
+                data.sequence_number,
+                data.receiver_datetime.date().to_yymmdd(),
+                data.receiver_datetime.time().to_hhmm(),
+                data.message_priority
+            ),
+            ApplicationHeader::Empty => String::new(),
+        };
+    }
+
     pub fn from_raw(parser: &mut StringParser) -> Result<ApplicationHeader, io::Error> {
         let direction = parser.next()?;
         let message_type = parser.n_chars(3)?;
 
         return if direction == 'I' {
-            let destination = parser.n_chars(12)?;
+            let destination = LogicalTerminalAddress::parse(&parser.n_chars(12)?)?;
             let priority = parser.n_chars(1)?;
             let delivery_monitoring = parser.n_chars(1).ok().unwrap_or("".to_string());
             let obsolescence_period = parser.n_chars(3).ok().unwrap_or("".to_string());
@@ -131,45 +192,32 @@ impl ApplicationHeader {
                 },
             })
         } else if direction == 'O' {
-            let sender_time = parser.n_chars(4)?;
-            let sender_date = parser.n_chars(6)?;
-
-            let sender_date_time = NaiveDateTime::parse_from_str(
-                format!("{}{}", sender_date, sender_time).as_str(),
-                "%y%m%d%H%M",
-            )
-            .map_err(|e| {
-                io::Error::new(
-                    InvalidData,
-                    format!("Cannot parse sender date/time: {}", e.to_string()),
-                )
+            let sender_time = SwiftTime::parse_hhmm(&parser.n_chars(4)?).map_err(|e| {
+                io::Error::new(InvalidData, format!("Cannot parse sender time: {}", e))
             })?;
-            let sender_address = parser.n_chars(12)?;
+            let sender_date = SwiftDate::parse_yymmdd(&parser.n_chars(6)?).map_err(|e| {
+                io::Error::new(InvalidData, format!("Cannot parse sender date: {}", e))
+            })?;
+            let sender_address = LogicalTerminalAddress::parse(&parser.n_chars(12)?)?;
             let session_number = parser.n_chars(4)?;
             let sequence_number = parser.n_chars(6)?;
 
-            let receiver_date = parser.n_chars(6)?;
-            let receiver_time = parser.n_chars(4)?;
-            let receiver_date_time = NaiveDateTime::parse_from_str(
-                format!("{}{}", receiver_date, receiver_time).as_str(),
-                "%y%m%d%H%M",
-            )
-            .map_err(|e| {
-                io::Error::new(
-                    InvalidData,
-                    format!("Cannot parse receiver date/time: {}", e.to_string()),
-                )
+            let receiver_date = SwiftDate::parse_yymmdd(&parser.n_chars(6)?).map_err(|e| {
+                io::Error::new(InvalidData, format!("Cannot parse receiver date: {}", e))
+            })?;
+            let receiver_time = SwiftTime::parse_hhmm(&parser.n_chars(4)?).map_err(|e| {
+                io::Error::new(InvalidData, format!("Cannot parse receiver time: {}", e))
             })?;
 
             let message_priority = parser.n_chars(1)?;
             Ok(ApplicationHeader::Output {
                 data: OutputData {
                     message_type,
-                    sender_datetime: DateTime::<Utc>::from_utc(sender_date_time, Utc),
+                    sender_datetime: LocalDateTime::from_parts(sender_date, sender_time),
                     sender_address,
                     session_number,
                     sequence_number,
-                    receiver_datetime: DateTime::<Utc>::from_utc(receiver_date_time, Utc),
+                    receiver_datetime: LocalDateTime::from_parts(receiver_date, receiver_time),
                     message_priority,
                 },
             })
@@ -182,23 +230,31 @@ impl ApplicationHeader {
     }
 }
 
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct UserHeader {
     /* 103 */ pub service_identifier: Option<String>,
     /* 113 */ pub banking_priority: Option<String>,
     /* 108 */ pub message_user_reference: Option<String>,
-    /* 119 */ pub validation_flag: Option<String>,
-    /* 423 */ pub balance_checkpoint_date_time: Option<String>,
-    /* 106 */ pub mir: Option<String>,
+    /* 119 */ pub validation_flag: Option<ValidationFlag>,
+    /* 423 */ pub balance_checkpoint_date_time: Option<LocalDateTime>,
+    /* 106 */ pub mir: Option<Mir>,
     /* 424 */ pub related_reference: Option<String>,
-    /* 111 */ pub service_type_identifier: Option<String>,
-    /* 121 */ pub uetr: Option<String>,
+    /* 111 */ pub service_type_identifier: Option<ServiceTypeIdentifier>,
+    /* 121 */ pub uetr: Option<Uetr>,
     /* 115 */ pub payment_release_information_receiver_fin_copy: Option<String>,
     /* 165 */ pub payment_release_information_receiver_fin_inform: Option<String>,
-    /* 433 */ pub screening_information_receiver: Option<String>,
+    /* 433 */ pub screening_information_receiver: Option<ScreeningInformation>,
     /* 434 */ pub payment_controls_information_for_receiver: Option<String>,
     pub unk_fields: HashMap<String, String>,
 }
 
+impl Default for UserHeader {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl UserHeader {
     pub fn new() -> UserHeader {
         return UserHeader {
@@ -225,62 +281,108 @@ impl UserHeader {
             service_identifier: fields.remove("103"),
             banking_priority: fields.remove("113"),
             message_user_reference: fields.remove("108"),
-            validation_flag: fields.remove("119"),
-            balance_checkpoint_date_time: fields.remove("423"),
-            mir: fields.remove("106"),
+            validation_flag: read_typed_field(&mut fields, "119", ValidationFlag::parse),
+            balance_checkpoint_date_time: read_typed_field(
+                &mut fields,
+                "423",
+                LocalDateTime::parse_yymmddhhmmss,
+            ),
+            mir: read_typed_field(&mut fields, "106", Mir::parse),
             related_reference: fields.remove("424"),
-            service_type_identifier: fields.remove("111"),
-            uetr: fields.remove("121"),
+            service_type_identifier: read_typed_field(
+                &mut fields,
+                "111",
+                ServiceTypeIdentifier::parse,
+            ),
+            uetr: read_typed_field(&mut fields, "121", Uetr::parse),
             payment_release_information_receiver_fin_copy: fields.remove("115"),
             payment_release_information_receiver_fin_inform: fields.remove("165"),
-            screening_information_receiver: fields.remove("433"),
+            screening_information_receiver: read_typed_field(
+                &mut fields,
+                "433",
+                ScreeningInformation::parse,
+            ),
             payment_controls_information_for_receiver: fields.remove("434"),
             unk_fields: fields,
         });
     }
 
-    pub fn to_raw(self) -> String {
+    /// Tags and errors of the typed fields that could not be read and are kept with the
+    /// unknown fields, sorted by tag
+    pub fn field_errors(&self) -> Vec<(String, io::Error)> {
+        let mut errors: Vec<(String, io::Error)> = self
+            .unk_fields
+            .iter()
+            .filter_map(|(tag, value)| typed_field_error(tag, value).map(|e| (tag.clone(), e)))
+            .collect();
+        errors.sort_by(|a, b| a.0.cmp(&b.0));
+        return errors;
+    }
+
+    pub fn is_empty(&self) -> bool {
+        return self.service_identifier.is_none()
+            && self.banking_priority.is_none()
+            && self.message_user_reference.is_none()
+            && self.validation_flag.is_none()
+            && self.balance_checkpoint_date_time.is_none()
+            && self.mir.is_none()
+            && self.related_reference.is_none()
+            && self.service_type_identifier.is_none()
+            && self.uetr.is_none()
+            && self.payment_release_information_receiver_fin_copy.is_none()
+            && self
+                .payment_release_information_receiver_fin_inform
+                .is_none()
+            && self.screening_information_receiver.is_none()
+            && self.payment_controls_information_for_receiver.is_none()
+            && self.unk_fields.is_empty();
+    }
+
+    pub fn to_raw(&self) -> String {
         let mut ret = String::new();
         ret.push_str("{3:");
 
-        if let Some(service_identifier) = self.service_identifier {
+        if let Some(service_identifier) = &self.service_identifier {
             ret.push_str(&format!("{{103:{service_identifier}}}"))
         }
 
-        if let Some(banking_priority) = self.banking_priority {
+        if let Some(banking_priority) = &self.banking_priority {
             ret.push_str(&format!("{{113:{banking_priority}}}"))
         }
 
-        if let Some(message_user_reference) = self.message_user_reference {
+        if let Some(message_user_reference) = &self.message_user_reference {
             ret.push_str(&format!("{{108:{message_user_reference}}}"))
         }
 
-        if let Some(validation_flag) = self.validation_flag {
+        if let Some(validation_flag) = &self.validation_flag {
             ret.push_str(&format!("{{119:{validation_flag}}}"))
         }
 
-        if let Some(balance_checkpoint_date_time) = self.balance_checkpoint_date_time {
-            ret.push_str(&format!("{{423:{balance_checkpoint_date_time}}}"))
+        if let Some(balance_checkpoint_date_time) = &self.balance_checkpoint_date_time {
+            ret.push_str(&format!(
+                "{{423:{}}}",
+                balance_checkpoint_date_time.to_yymmddhhmmss()
+            ))
         }
 
-        if let Some(mir) = self.mir {
+        if let Some(mir) = &self.mir {
             ret.push_str(&format!("{{106:{mir}}}"))
         }
 
-        if let Some(related_reference) = self.related_reference {
+        if let Some(related_reference) = &self.related_reference {
             ret.push_str(&format!("{{424:{related_reference}}}"))
         }
 
-        if let Some(service_type_identifier) = self.service_type_identifier {
+        if let Some(service_type_identifier) = &self.service_type_identifier {
             ret.push_str(&format!("{{111:{service_type_identifier}}}"))
         }
 
-        if let Some(uetr) = self.uetr {
+        if let Some(uetr) = &self.uetr {
             ret.push_str(&format!("{{121:{uetr}}}"))
         }
 
         if let Some(payment_release_information_receiver_fin_copy) =
-            self.payment_release_information_receiver_fin_copy
+            &self.payment_release_information_receiver_fin_copy
         {
             ret.push_str(&format!(
                 "{{115:{payment_release_information_receiver_fin_copy}}}"
@@ -288,34 +390,62 @@ impl UserHeader {
         }
 
         if let Some(payment_release_information_receiver_fin_inform) =
-            self.payment_release_information_receiver_fin_inform
+            &self.payment_release_information_receiver_fin_inform
         {
             ret.push_str(&format!(
                 "{{165:{payment_release_information_receiver_fin_inform}}}"
             ))
         }
 
-        if let Some(screening_information_receiver) = self.screening_information_receiver {
+        if let Some(screening_information_receiver) = &self.screening_information_receiver {
             ret.push_str(&format!("{{433:{screening_information_receiver}}}"))
         }
 
         if let Some(payment_controls_information_for_receiver) =
-            self.payment_controls_information_for_receiver
+            &self.payment_controls_information_for_receiver
         {
             ret.push_str(&format!(
                 "{{434:{payment_controls_information_for_receiver}}}"
             ))
         }
 
-        self.unk_fields
-            .iter()
-            .for_each(|(k, v)| ret.push_str(&format!("{{{k}:{v}}}")));
+        write_unknown_fields(&mut ret, &self.unk_fields);
 
-        ret.push_str("}");
+        ret.push('}');
         return ret;
     }
 }
 
+/// Removes the field from the block and parses its value. A value that cannot be read is
+/// left in the block, so that it is kept with the unknown fields and reported by the
+/// `Validator` instead of failing the parse of the message.
+fn read_typed_field<T>(
+    fields: &mut HashMap<String, String>,
+    tag: &str,
+    parse: fn(&str) -> Result<T, io::Error>,
+) -> Option<T> {
+    let value = parse(fields.get(tag)?).ok()?;
+    fields.remove(tag);
+    return Some(value);
+}
+
+/// Error reading the value of a typed field of the user header, `None` for valid values and
+/// untyped fields
+fn typed_field_error(tag: &str, value: &str) -> Option<io::Error> {
+    let result = match tag {
+        "119" => ValidationFlag::parse(value).map(|_| ()),
+        "423" => LocalDateTime::parse_yymmddhhmmss(value).map(|_| ()),
+        "106" => Mir::parse(value).map(|_| ()),
+        "111" => ServiceTypeIdentifier::parse(value).map(|_| ()),
+        "121" => Uetr::parse(value).map(|_| ()),
+        "433" => ScreeningInformation::parse(value).map(|_| ()),
+        _ => Ok(()),
+    };
+    return result.err();
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Trailer {
     pub pac: Option<String>,
     pub chk: Option<String>,
@@ -328,6 +458,12 @@ pub struct Trailer {
     pub unk_fields: HashMap<String, String>,
 }
 
+impl Default for Trailer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl Trailer {
     pub fn new() -> Trailer {
         return Trailer {
@@ -359,56 +495,162 @@ impl Trailer {
         });
     }
 
-    pub fn to_raw(self) -> String {
+    pub fn is_empty(&self) -> bool {
+        return self.pac.is_none()
+            && self.chk.is_none()
+            && self.sys.is_none()
+            && self.tng.is_none()
+            && self.pde.is_none()
+            && self.pdm.is_none()
+            && self.dlm.is_none()
+            && self.mrf.is_none()
+            && self.unk_fields.is_empty();
+    }
+
+    pub fn to_raw(&self) -> String {
         let mut ret = String::new();
-        ret.push_str("{3:");
+        ret.push_str("{5:");
 
-        if let Some(pac) = self.pac {
+        if let Some(pac) = &self.pac {
             ret.push_str(&format!("{{PAC:{pac}}}"));
         }
 
-        if let Some(chk) = self.chk {
+        if let Some(chk) = &self.chk {
             ret.push_str(&format!("{{CHK:{chk}}}"));
         }
 
-        if let Some(sys) = self.sys {
+        if let Some(sys) = &self.sys {
             ret.push_str(&format!("{{SYS:{sys}}}"));
         }
 
-        if let Some(tng) = self.tng {
+        if let Some(tng) = &self.tng {
             ret.push_str(&format!("{{TNG:{tng}}}"));
         }
 
-        if let Some(pde) = self.pde {
+        if let Some(pde) = &self.pde {
             ret.push_str(&format!("{{PDE:{pde}}}"));
         }
 
-        if let Some(pdm) = self.pdm {
+        if let Some(pdm) = &self.pdm {
             ret.push_str(&format!("{{PDM:{pdm}}}"));
         }
 
-        if let Some(dlm) = self.dlm {
+        if let Some(dlm) = &self.dlm {
             ret.push_str(&format!("{{DLM:{dlm}}}"));
         }
 
-        if let Some(mrf) = self.mrf {
+        if let Some(mrf) = &self.mrf {
             ret.push_str(&format!("{{MRF:{mrf}}}"));
         }
 
-        self.unk_fields
-            .iter()
-            .for_each(|(k, v)| ret.push_str(&format!("{{{k}:{v}}}")));
+        write_unknown_fields(&mut ret, &self.unk_fields);
 
-        ret.push_str("}");
+        ret.push('}');
 
         return ret;
     }
 }
 
+/// Block S added by SWIFTAlliance Access to exported messages, e.g. `{S:{SAC:}{COP:P}}`
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SystemTrailer {
+    /// Present if the signature verification and the authorisation in the RMA succeeded
+    pub sac: Option<String>,
+    /// Copy indicator, e.g. `P` for the primary copy
+    pub cop: Option<String>,
+    /// Checksum of the user, calculated by Alliance Access over the message
+    pub chu: Option<String>,
+    /// Present if the message was entered manually
+    pub man: Option<String>,
+    /// Possible duplicate emission detected by Alliance Access
+    pub spd: Option<String>,
+    pub unk_fields: HashMap<String, String>,
+}
+
+impl Default for SystemTrailer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SystemTrailer {
+    pub fn new() -> SystemTrailer {
+        return SystemTrailer {
+            sac: Option::None,
+            cop: Option::None,
+            chu: Option::None,
+            man: Option::None,
+            spd: Option::None,
+            unk_fields: HashMap::new(),
+        };
+    }
+
+    pub fn from_raw(content: String) -> Result<SystemTrailer, io::Error> {
+        let mut fields = read_sys_block_fields(content);
+
+        return Ok(SystemTrailer {
+            sac: fields.remove("SAC"),
+            cop: fields.remove("COP"),
+            chu: fields.remove("CHU"),
+            man: fields.remove("MAN"),
+            spd: fields.remove("SPD"),
+            unk_fields: fields,
+        });
+    }
+
+    pub fn is_empty(&self) -> bool {
+        return self.sac.is_none()
+            && self.cop.is_none()
+            && self.chu.is_none()
+            && self.man.is_none()
+            && self.spd.is_none()
+            && self.unk_fields.is_empty();
+    }
+
+    pub fn to_raw(&self) -> String {
+        let mut ret = String::new();
+        ret.push_str("{S:");
+
+        if let Some(sac) = &self.sac {
+            ret.push_str(&format!("{{SAC:{sac}}}"));
+        }
+
+        if let Some(cop) = &self.cop {
+            ret.push_str(&format!("{{COP:{cop}}}"));
+        }
+
+        if let Some(chu) = &self.chu {
+            ret.push_str(&format!("{{CHU:{chu}}}"));
+        }
+
+        if let Some(man) = &self.man {
+            ret.push_str(&format!("{{MAN:{man}}}"));
+        }
+
+        if let Some(spd) = &self.spd {
+            ret.push_str(&format!("{{SPD:{spd}}}"));
+        }
+
+        write_unknown_fields(&mut ret, &self.unk_fields);
+
+        ret.push('}');
+
+        return ret;
+    }
+}
+
+/// Writes fields without dedicated member sorted by tag, so that the output is stable
+fn write_unknown_fields(ret: &mut String, fields: &HashMap<String, String>) {
+    let mut tags: Vec<&String> = fields.keys().collect();
+    tags.sort();
+    tags.iter()
+        .for_each(|k| ret.push_str(&format!("{{{}:{}}}", k, fields[*k])));
+}
+
 fn read_sys_block_fields(content: String) -> HashMap<String, String> {
     return content
-        .split("}")
-        .into_iter()
+        .split('}')
         .filter(|tk| !tk.trim().is_empty())
         .map(|tk| &tk[1..])
         .map(|tk| tk.split_once(":").unwrap_or((tk, "")))
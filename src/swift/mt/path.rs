@@ -0,0 +1,988 @@
+use crate::swift::amount::Amount;
+use crate::swift::bic::{Bic, LogicalTerminalAddress};
+use crate::swift::country::is_valid_country_code;
+use crate::swift::currency::Currency;
+use crate::swift::datetime::{LocalDateTime, SwiftDate};
+use crate::swift::iban::Iban;
+use crate::swift::mt::fields::{AmountField, PartyField};
+use crate::swift::mt::header_fields::{
+    Mir, ScreeningInformation, ServiceTypeIdentifier, Uetr, ValidationFlag,
+};
+use crate::swift::mt::model::{ApplicationHeader, SystemTrailer, Trailer, UserHeader};
+use crate::swift::mt::sequences::sequence_paths;
+use crate::swift::mt::swift_mt_parser::SwiftMtMessage;
+use crate::swift::mt::text::{header_block_fields, tag_matches, Field};
+use crate::swift::mt::trailer_fields::PossibleDuplicate;
+use std::fmt;
+use std::io;
+use std::io::ErrorKind::InvalidData;
+use std::str::FromStr;
+
+static BASIC_HEADER_ELEMENTS: [&str; 6] = ["APP", "SERVICE", "LT", "BIC", "SESSION", "SEQUENCE"];
+static APPLICATION_HEADER_ELEMENTS: [&str; 8] = [
+    "MT",
+    "DIRECTION",
+    "PRIORITY",
+    "ADDRESS",
+    "DELIVERY_MONITORING",
+    "OBSOLESCENCE",
+    "INPUT_TIME",
+    "OUTPUT_TIME",
+];
+static AMOUNT_COMPONENTS: [&str; 3] = ["date", "currency", "amount"];
+static PARTY_COMPONENTS: [&str; 7] = [
+    "account",
+    "iban",
+    "bic",
+    "name",
+    "address",
+    "country",
+    "party_identifier",
+];
+
+/// Which occurrences of a repeated sequence or field a path selects
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Occurrence {
+    /// The n-th occurrence, starting at 1
+    Nth(usize),
+    All,
+}
+
+/// Value at a path, typed according to the header element, field or component
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    Text(String),
+    Number(u32),
+    Date(SwiftDate),
+    DateTime(LocalDateTime),
+    Currency(Currency),
+    Amount(Amount),
+    Bic(Bic),
+    LogicalTerminal(LogicalTerminalAddress),
+    Iban(Iban),
+    Lines(Vec<String>),
+    Uetr(Uetr),
+    ValidationFlag(ValidationFlag),
+    ServiceType(ServiceTypeIdentifier),
+    Mir(Mir),
+    Screening(ScreeningInformation),
+    PossibleDuplicate(PossibleDuplicate),
+    /// Header or trailer field that is present without content, e.g. `{SAC:}` or `{PDE:}`
+    Flag,
+}
+
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        return match self {
+            Value::Text(text) => write!(f, "{}", text),
+            Value::Number(number) => write!(f, "{}", number),
+            Value::Date(date) => write!(f, "{}", date),
+            Value::DateTime(date_time) => write!(f, "{}", date_time),
+            Value::Currency(currency) => write!(f, "{}", currency),
+            Value::Amount(amount) => write!(f, "{}", amount),
+            Value::Bic(bic) => write!(f, "{}", bic),
+            Value::LogicalTerminal(address) => write!(f, "{}", address),
+            Value::Iban(iban) => write!(f, "{}", iban),
+            Value::Lines(lines) => write!(f, "{}", lines.join("\r\n")),
+            Value::Uetr(uetr) => write!(f, "{}", uetr),
+            Value::ValidationFlag(flag) => write!(f, "{}", flag),
+            Value::ServiceType(service_type) => write!(f, "{}", service_type),
+            Value::Mir(mir) => write!(f, "{}", mir),
+            Value::Screening(screening) => write!(f, "{}", screening),
+            Value::PossibleDuplicate(duplicate) => write!(f, "{}", duplicate),
+            Value::Flag => Ok(()),
+        };
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Target {
+    BasicHeader(String),
+    ApplicationHeader(String),
+    UserHeader(String),
+    Text {
+        sequences: Vec<(String, Occurrence)>,
+        tag: String,
+        occurrence: Occurrence,
+        component: Option<String>,
+    },
+    Trailer(String),
+    SystemTrailer(String),
+}
+
+/// Path to header elements and fields of a message, for reading and writing values from
+/// configuration instead of code. A path starts with the block followed by dot separated
+/// segments:
+///
+/// * `B1.LT`: element of the basic header, one of `APP`, `SERVICE`, `LT`, `BIC`, `SESSION`
+///   and `SEQUENCE`
+/// * `B2.MT`: element of the application header, one of `MT`, `DIRECTION` (`I` or `O`),
+///   `PRIORITY`, `ADDRESS` (receiver of input and sender of output messages),
+///   `DELIVERY_MONITORING`, `OBSOLESCENCE`, `INPUT_TIME` and `OUTPUT_TIME`
+/// * `B3.121`, `B5.CHK`, `BS.COP`: field of the user header, trailer or system trailer. The
+///   fields with a type (119, 423, 106, 111, 121, 433, PDE and PDM) are returned typed, fields
+///   without content as `Value::Flag` and the others as text.
+/// * `B4.SeqB[2].59F.name`: field of the message text, optionally within sequences named
+///   `Seq<name>` (16R blocks like `SeqGENL` or repetitive sequences like `SeqB`, see
+///   `sequence_paths`), optionally followed by a component of the field
+///
+/// Sequences and fields select their first occurrence, `[n]` selects the n-th and `[*]` all
+/// of them. Tags may be patterns like `50a` (see `tag_matches`) or `*` for all fields. Without
+/// sequence segments, fields are searched in the whole message text.
+///
+/// Components of amount fields (e.g. 32A) are `date`, `currency` and `amount`, components of
+/// party fields (50a to 59a) are `account`, `iban`, `bic`, `name`, `address`, `country` and
+/// `party_identifier`, and `lines` gives the lines of any field. All components but `lines`
+/// can be set, as far as the option of the field has them.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MessagePath {
+    path: String,
+    target: Target,
+}
+
+impl MessagePath {
+    pub fn parse(path: &str) -> Result<MessagePath, io::Error> {
+        let segments: Vec<&str> = path.split('.').collect();
+        let element = |elements: &[&str]| {
+            let element = segments[1].to_ascii_uppercase();
+            if !elements.contains(&element.as_str()) {
+                return Err(path_error(
+                    path,
+                    &format!("expected one of {}", elements.join(", ")),
+                ));
+            }
+
+            return Ok(element);
+        };
+
+        let target = match (segments[0], segments.len()) {
+            ("B1", 2) => Target::BasicHeader(element(&BASIC_HEADER_ELEMENTS)?),
+            ("B2", 2) => Target::ApplicationHeader(element(&APPLICATION_HEADER_ELEMENTS)?),
+            ("B3", 2) => Target::UserHeader(segments[1].to_string()),
+            ("B4", n) if n > 1 => parse_text_target(path, &segments[1..])?,
+            ("B5", 2) => Target::Trailer(segments[1].to_ascii_uppercase()),
+            ("BS", 2) => Target::SystemTrailer(segments[1].to_ascii_uppercase()),
+            _ => {
+                return Err(path_error(
+                    path,
+                    "expected a block B1 to B5 or BS followed by an element or field",
+                ))
+            }
+        };
+
+        return Ok(MessagePath {
+            path: path.to_string(),
+            target,
+        });
+    }
+
+    /// Values at the path, empty if the message has no such element or field
+    pub fn get(&self, message: &SwiftMtMessage) -> Result<Vec<Value>, io::Error> {
+        return match &self.target {
+            Target::BasicHeader(element) => Ok(basic_header_value(message, element)),
+            Target::ApplicationHeader(element) => Ok(application_header_value(message, element)),
+            Target::UserHeader(tag) => block_value(&message.user_header.to_raw(), tag),
+            Target::Trailer(tag) => block_value(&message.trailer.to_raw(), tag),
+            Target::SystemTrailer(tag) => block_value(&message.system_trailer.to_raw(), tag),
+            Target::Text { component, .. } => {
+                let mut values = Vec::new();
+                for index in self.select_fields(message) {
+                    let field = &message.text.fields[index];
+                    match component {
+                        Some(component) => values.append(&mut component_values(field, component)?),
+                        None => values.push(Value::Text(field.value.clone())),
+                    }
+                }
+
+                Ok(values)
+            }
+        };
+    }
+
+    /// First value at the path
+    pub fn get_first(&self, message: &SwiftMtMessage) -> Result<Option<Value>, io::Error> {
+        return Ok(self.get(message)?.into_iter().next());
+    }
+
+    /// Sets the value in FIN format at the path, returning the number of values changed. A
+    /// missing header field is added, as is a missing field of the message text if the path
+    /// selects a single field without component. The field is added after the last field of
+    /// its sequence, or after the last field outside of sequences.
+    pub fn set(&self, message: &mut SwiftMtMessage, value: &str) -> Result<usize, io::Error> {
+        match &self.target {
+            Target::BasicHeader(element) => set_basic_header(message, element, value)?,
+            Target::ApplicationHeader(element) => set_application_header(message, element, value)?,
+            Target::UserHeader(tag) => {
+                let content = set_block_field(&message.user_header.to_raw(), tag, value);
+                let user_header = UserHeader::from_raw(content)?;
+                // the parser keeps malformed values for the validator, a new value must be valid
+                if let Some((_, e)) = user_header
+                    .field_errors()
+                    .into_iter()
+                    .find(|(t, _)| t == tag)
+                {
+                    return Err(io::Error::new(
+                        InvalidData,
+                        format!("Invalid field {}: {}", tag, e),
+                    ));
+                }
+                message.user_header = user_header;
+            }
+            Target::Trailer(tag) => {
+                let content = set_block_field(&message.trailer.to_raw(), tag, value);
+                message.trailer = Trailer::from_raw(content)?;
+            }
+            Target::SystemTrailer(tag) => {
+                let content = set_block_field(&message.system_trailer.to_raw(), tag, value);
+                message.system_trailer = SystemTrailer::from_raw(content)?;
+            }
+            Target::Text { component, .. } => {
+                let indices = self.select_fields(message);
+                if indices.is_empty() {
+                    return self.add_field(message, value);
+                }
+
+                for index in &indices {
+                    let field = &mut message.text.fields[*index];
+                    field.value = match component {
+                        Some(component) => set_component(field, component, value)?,
+                        None => value.to_string(),
+                    };
+                }
+
+                return Ok(indices.len());
+            }
+        }
+
+        return Ok(1);
+    }
+
+    /// Indices of the fields of the message text selected by the path
+    fn select_fields(&self, message: &SwiftMtMessage) -> Vec<usize> {
+        let Target::Text {
+            sequences,
+            tag,
+            occurrence,
+            ..
+        } = &self.target
+        else {
+            return Vec::new();
+        };
+
+        let message_type = message.application_header.message_type().unwrap_or("");
+        let mut groups: Vec<(String, Vec<usize>)> = Vec::new();
+        for (path, index) in sequence_paths(message_type, &message.text) {
+            if !sequences.is_empty() && !path_matches(&path, sequences) {
+                continue;
+            }
+
+            let field = &message.text.fields[index];
+            if tag != "*" && !tag_matches(tag, &field.tag) {
+                continue;
+            }
+
+            // without sequence segments the whole text is one group
+            let group = if sequences.is_empty() {
+                String::new()
+            } else {
+                path
+            };
+            match groups.iter_mut().find(|(g, _)| *g == group) {
+                Some((_, indices)) => indices.push(index),
+                None => groups.push((group, vec![index])),
+            }
+        }
+
+        return groups
+            .into_iter()
+            .flat_map(|(_, indices)| match occurrence {
+                Occurrence::All => indices,
+                Occurrence::Nth(n) => indices.into_iter().skip(n - 1).take(1).collect(),
+            })
+            .collect();
+    }
+
+    fn add_field(&self, message: &mut SwiftMtMessage, value: &str) -> Result<usize, io::Error> {
+        let Target::Text {
+            sequences,
+            tag,
+            occurrence,
+            component,
+        } = &self.target
+        else {
+            return Ok(0);
+        };
+
+        let concrete = component.is_none()
+            && *occurrence == Occurrence::Nth(1)
+            && tag
+                .chars()
+                .all(|c| c.is_ascii_digit() || c.is_ascii_uppercase())
+            && sequences.iter().all(|(_, o)| *o != Occurrence::All);
+        if !concrete {
+            return Ok(0);
+        }
+
+        let message_type = message.application_header.message_type().unwrap_or("");
+        let last = sequence_paths(message_type, &message.text)
+            .into_iter()
+            .filter(|(path, _)| {
+                if sequences.is_empty() {
+                    path.is_empty()
+                } else {
+                    path_matches(path, sequences)
+                }
+            })
+            .map(|(_, index)| index)
+            .next_back();
+
+        let position = match last {
+            Some(index) => index + 1,
+            None if sequences.is_empty() => message.text.fields.len(),
+            None => {
+                return Err(path_error(
+                    &self.path,
+                    "the sequence does not exist in the message",
+                ))
+            }
+        };
+
+        message.text.fields.insert(position, Field::new(tag, value));
+        return Ok(1);
+    }
+}
+
+impl FromStr for MessagePath {
+    type Err = io::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        return MessagePath::parse(s);
+    }
+}
+
+impl fmt::Display for MessagePath {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        return write!(f, "{}", self.path);
+    }
+}
+
+fn path_error(path: &str, reason: &str) -> io::Error {
+    return io::Error::new(InvalidData, format!("Invalid path {}: {}", path, reason));
+}
+
+fn parse_text_target(path: &str, segments: &[&str]) -> Result<Target, io::Error> {
+    let mut sequences = Vec::new();
+    let mut rest = segments;
+    while let Some(sequence) = rest.first().and_then(|s| s.strip_prefix("Seq")) {
+        sequences.push(split_occurrence(path, sequence)?);
+        rest = &rest[1..];
+    }
+
+    let (tag, occurrence) = match rest.first() {
+        Some(&"*") => ("*".to_string(), Occurrence::All),
+        Some(segment) => split_occurrence(path, segment)?,
+        None => return Err(path_error(path, "expected a field after the sequences")),
+    };
+
+    let is_tag = tag == "*"
+        || (tag.is_ascii()
+            && tag.len() >= 2
+            && tag.len() <= 3
+            && tag[..2].chars().all(|c| c.is_ascii_digit())
+            && tag[2..].chars().all(|c| c.is_ascii_uppercase() || c == 'a'));
+    if !is_tag {
+        return Err(path_error(path, &format!("{} is not a field tag", tag)));
+    }
+
+    let component = match rest.get(1) {
+        Some(component) => {
+            let known = component == &"lines"
+                || AMOUNT_COMPONENTS.contains(component)
+                || PARTY_COMPONENTS.contains(component);
+            if !known || rest.len() > 2 {
+                return Err(path_error(
+                    path,
+                    &format!("unknown field component {}", rest[1..].join(".")),
+                ));
+            }
+            Some(component.to_string())
+        }
+        None => None,
+    };
+
+    return Ok(Target::Text {
+        sequences,
+        tag,
+        occurrence,
+        component,
+    });
+}
+
+/// Splits `name[n]` or `name[*]` into the name and the occurrence, which defaults to the first
+fn split_occurrence(path: &str, segment: &str) -> Result<(String, Occurrence), io::Error> {
+    let Some((name, index)) = segment.strip_suffix(']').and_then(|s| s.split_once('[')) else {
+        return Ok((segment.to_string(), Occurrence::Nth(1)));
+    };
+
+    let occurrence = match index {
+        "*" => Occurrence::All,
+        _ => match index.parse::<usize>() {
+            Ok(n) if n > 0 => Occurrence::Nth(n),
+            _ => {
+                return Err(path_error(
+                    path,
+                    &format!("invalid occurrence {} of {}", index, name),
+                ))
+            }
+        },
+    };
+
+    return Ok((name.to_string(), occurrence));
+}
+
+/// Whether a sequence path like `GENL[1]/LINK[2]` matches the sequence segments of a path
+fn path_matches(path: &str, sequences: &[(String, Occurrence)]) -> bool {
+    if path.is_empty() {
+        return false;
+    }
+
+    let segments: Vec<&str> = path.split('/').collect();
+    return segments.len() == sequences.len()
+        && segments
+            .iter()
+            .zip(sequences)
+            .all(|(segment, (name, occurrence))| {
+                let Some((segment_name, index)) =
+                    segment.strip_suffix(']').and_then(|s| s.split_once('['))
+                else {
+                    return false;
+                };
+
+                return segment_name == name
+                    && match occurrence {
+                        Occurrence::All => true,
+                        Occurrence::Nth(n) => index == n.to_string(),
+                    };
+            });
+}
+
+fn basic_header_value(message: &SwiftMtMessage, element: &str) -> Vec<Value> {
+    let header = &message.basic_header;
+    return vec![match element {
+        "APP" => Value::Text(header.application_identifier.clone()),
+        "SERVICE" => Value::Number(header.service_identifier as u32),
+        "LT" => Value::LogicalTerminal(header.logical_terminal.clone()),
+        "BIC" => Value::Bic(header.logical_terminal.bic11()),
+        "SESSION" => Value::Number(header.session_number),
+        _ => Value::Number(header.sequence_number),
+    }];
+}
+
+fn application_header_value(message: &SwiftMtMessage, element: &str) -> Vec<Value> {
+    let value = match (&message.application_header, element) {
+        (ApplicationHeader::Empty, _) => None,
+        (header, "MT") => header.message_type().map(|mt| Value::Text(mt.to_string())),
+        (ApplicationHeader::Input { .. }, "DIRECTION") => Some(Value::Text("I".to_string())),
+        (ApplicationHeader::Output { .. }, "DIRECTION") => Some(Value::Text("O".to_string())),
+        (ApplicationHeader::Input { data }, "PRIORITY") => Some(Value::Text(data.priority.clone())),
+        (ApplicationHeader::Output { data }, "PRIORITY") => {
+            Some(Value::Text(data.message_priority.clone()))
+        }
+        (ApplicationHeader::Input { data }, "ADDRESS") => {
+            Some(Value::LogicalTerminal(data.destination.clone()))
+        }
+        (ApplicationHeader::Output { data }, "ADDRESS") => {
+            Some(Value::LogicalTerminal(data.sender_address.clone()))
+        }
+        (ApplicationHeader::Input { data }, "DELIVERY_MONITORING") => {
+            Some(Value::Text(data.delivery_monitoring.clone()))
+        }
+        (ApplicationHeader::Input { data }, "OBSOLESCENCE") => {
+            Some(Value::Text(data.obsolescence_period.clone()))
+        }
+        (ApplicationHeader::Output { data }, "INPUT_TIME") => {
+            Some(Value::DateTime(data.sender_datetime))
+        }
+        (ApplicationHeader::Output { data }, "OUTPUT_TIME") => {
+            Some(Value::DateTime(data.receiver_datetime))
+        }
+        _ => None,
+    };
+
+    return value
+        .filter(|v| !matches!(v, Value::Text(t) if t.is_empty()))
+        .into_iter()
+        .collect();
+}
+
+fn block_value(raw: &str, tag: &str) -> Result<Vec<Value>, io::Error> {
+    return header_block_fields(raw)
+        .into_iter()
+        .filter(|f| f.tag == tag)
+        .map(|f| header_field_value(&f))
+        .collect();
+}
+
+/// Value of a field of the user header, trailer or system trailer, typed like the model
+fn header_field_value(field: &Field) -> Result<Value, io::Error> {
+    let value = field.value.as_str();
+    return Ok(match field.tag.as_str() {
+        "119" => Value::ValidationFlag(ValidationFlag::parse(value)?),
+        "423" => Value::DateTime(LocalDateTime::parse_yymmddhhmmss(value)?),
+        "106" => Value::Mir(Mir::parse(value)?),
+        "111" => Value::ServiceType(ServiceTypeIdentifier::parse(value)?),
+        "121" => Value::Uetr(Uetr::parse(value)?),
+        "433" => Value::Screening(ScreeningInformation::parse(value)?),
+        "PDE" | "PDM" => match PossibleDuplicate::parse(value)? {
+            Some(duplicate) => Value::PossibleDuplicate(duplicate),
+            None => Value::Flag,
+        },
+        _ if value.is_empty() => Value::Flag,
+        _ => Value::Text(value.to_string()),
+    });
+}
+
+fn component_values(field: &Field, component: &str) -> Result<Vec<Value>, io::Error> {
+    if component == "lines" {
+        return Ok(vec![Value::Lines(
+            field.lines().iter().map(|l| l.to_string()).collect(),
+        )]);
+    }
+
+    if AMOUNT_COMPONENTS.contains(&component) && AmountField::is_amount_field(&field.tag) {
+        let amount = AmountField::from_field(field)?;
+        return Ok(match component {
+            "date" => amount.value_date.map(Value::Date).into_iter().collect(),
+            "currency" => vec![Value::Currency(amount.currency)],
+            _ => vec![Value::Amount(amount.amount)],
+        });
+    }
+
+    if PARTY_COMPONENTS.contains(&component) && PartyField::is_party_field(&field.tag) {
+        let party = PartyField::from_field(field)?;
+        let value = match component {
+            "account" => party.account.map(Value::Text),
+            "iban" => party.iban.map(Value::Iban),
+            "bic" => party.bic.map(Value::Bic),
+            "name" => party.name.map(Value::Text),
+            "address" => Some(Value::Lines(party.address)),
+            "country" => party.country_code.map(Value::Text),
+            _ => party.party_identifier.map(Value::Text),
+        };
+        return Ok(value
+            .filter(|v| !matches!(v, Value::Lines(lines) if lines.is_empty()))
+            .into_iter()
+            .collect());
+    }
+
+    return Err(io::Error::new(
+        InvalidData,
+        format!("Field {} has no component {}", field.tag, component),
+    ));
+}
+
+fn set_component(field: &Field, component: &str, value: &str) -> Result<String, io::Error> {
+    if AMOUNT_COMPONENTS.contains(&component) && AmountField::is_amount_field(&field.tag) {
+        let mut amount = AmountField::from_field(field)?;
+        match component {
+            "date" if amount.value_date.is_some() => amount.value_date = Some(parse_date(value)?),
+            "currency" => amount.currency = value.parse()?,
+            "amount" => amount.amount = Amount::parse(value)?,
+            _ => {
+                return Err(io::Error::new(
+                    InvalidData,
+                    format!("Field {} has no date", field.tag),
+                ))
+            }
+        }
+
+        return Ok(format!(
+            "{}{}{}",
+            amount.value_date.map(|d| d.to_yymmdd()).unwrap_or_default(),
+            amount.currency,
+            amount.amount
+        ));
+    }
+
+    if !PARTY_COMPONENTS.contains(&component) || !PartyField::is_party_field(&field.tag) {
+        return Err(component_error(field, component));
+    }
+
+    let option = field.tag.chars().nth(2);
+    let mut lines: Vec<String> = field.lines().iter().map(|l| l.to_string()).collect();
+    let has_identifier_code = PartyField::has_identifier_code(&field.tag);
+    // the account or the party identifier of 50F
+    let has_first_line = lines
+        .first()
+        .is_some_and(|l| l.starts_with('/') || field.tag == "50F");
+    let first = if has_first_line { 1 } else { 0 };
+    let value_lines: Vec<String> = value.split("\r\n").map(|l| l.to_string()).collect();
+
+    match (component, option) {
+        ("account", _) | ("iban", _) => {
+            if component == "iban" {
+                Iban::parse(value)?;
+            }
+            let account = format!("/{}", value);
+            if has_first_line {
+                lines[0] = account;
+            } else {
+                lines.insert(0, account);
+            }
+        }
+        ("party_identifier", _) if field.tag == "50F" => {
+            if has_first_line {
+                lines[0] = value.to_string();
+            } else {
+                lines.insert(0, value.to_string());
+            }
+        }
+        ("bic", _) if has_identifier_code => {
+            Bic::parse(value)?;
+            lines.truncate(first);
+            lines.push(value.to_string());
+        }
+        ("name", Some('F')) => {
+            set_numbered_lines(&mut lines, first, "1", &[value.to_string()]);
+        }
+        ("address", Some('F')) => set_numbered_lines(&mut lines, first, "2", &value_lines),
+        ("country", Some('F')) => {
+            if !is_valid_country_code(value) {
+                return Err(io::Error::new(
+                    InvalidData,
+                    format!("Unknown country code {}", value),
+                ));
+            }
+
+            let town = lines
+                .iter()
+                .find_map(|l| l.strip_prefix("3/"))
+                .and_then(|c| c.split_once('/'))
+                .map(|(_, town)| town.to_string());
+            let line = match town {
+                Some(town) => format!("{}/{}", value, town),
+                None => value.to_string(),
+            };
+            set_numbered_lines(&mut lines, first, "3", &[line]);
+        }
+        ("name", _) if !has_identifier_code && option != Some('C') => {
+            if first < lines.len() {
+                lines[first] = value.to_string();
+            } else {
+                lines.push(value.to_string());
+            }
+        }
+        ("address", _) if !has_identifier_code && option != Some('C') => {
+            if first >= lines.len() {
+                return Err(io::Error::new(
+                    InvalidData,
+                    format!("Field {} has no name to add the address to", field.tag),
+                ));
+            }
+            lines.truncate(first + 1);
+            lines.extend(value_lines);
+        }
+        _ => return Err(component_error(field, component)),
+    }
+
+    let value = lines.join("\r\n");
+    PartyField::from_field(&Field::new(&field.tag, &value))?;
+    return Ok(value);
+}
+
+/// Replaces the lines of option F with the number by the content, keeping the place of the
+/// first of them, or adds them after the lines with lower numbers
+fn set_numbered_lines(lines: &mut Vec<String>, first: usize, number: &str, content: &[String]) {
+    let prefix = format!("{}/", number);
+    let position = lines
+        .iter()
+        .skip(first)
+        .position(|l| l.starts_with(&prefix) || l.as_str() > prefix.as_str())
+        .map_or(lines.len(), |p| p + first);
+    lines.retain(|l| !l.starts_with(&prefix));
+    let position = position.min(lines.len());
+    for (offset, line) in content.iter().enumerate() {
+        lines.insert(position + offset, format!("{}{}", prefix, line));
+    }
+}
+
+fn component_error(field: &Field, component: &str) -> io::Error {
+    return io::Error::new(
+        InvalidData,
+        format!(
+            "Component {} of field {} cannot be set",
+            component, field.tag
+        ),
+    );
+}
+
+fn parse_date(value: &str) -> Result<SwiftDate, io::Error> {
+    if value.len() == 6 {
+        return SwiftDate::parse_yymmdd(value);
+    }
+
+    return value.parse();
+}
+
+fn set_basic_header(
+    message: &mut SwiftMtMessage,
+    element: &str,
+    value: &str,
+) -> Result<(), io::Error> {
+    let header = &mut message.basic_header;
+    let number = |max: u32| match value.parse::<u32>() {
+        Ok(n) if n <= max => Ok(n),
+        _ => Err(io::Error::new(
+            InvalidData,
+            format!("Expected a number up to {} but got {}", max, value),
+        )),
+    };
+
+    match element {
+        "LT" => header.logical_terminal = LogicalTerminalAddress::parse(value)?,
+        "SESSION" => header.session_number = number(9999)?,
+        "SEQUENCE" => header.sequence_number = number(999999)?,
+        _ => {
+            return Err(io::Error::new(
+                InvalidData,
+                format!("Basic header element {} cannot be set", element),
+            ))
+        }
+    }
+
+    return Ok(());
+}
+
+fn set_application_header(
+    message: &mut SwiftMtMessage,
+    element: &str,
+    value: &str,
+) -> Result<(), io::Error> {
+    if element == "MT" && (value.len() != 3 || !value.chars().all(|c| c.is_ascii_digit())) {
+        return Err(io::Error::new(
+            InvalidData,
+            format!("Message type must have 3 digits: {}", value),
+        ));
+    }
+    if element == "PRIORITY" && !["S", "U", "N"].contains(&value) {
+        return Err(io::Error::new(
+            InvalidData,
+            format!("Priority must be S, U or N: {}", value),
+        ));
+    }
+
+    match (&mut message.application_header, element) {
+        (ApplicationHeader::Input { data }, "MT") => data.message_type = value.to_string(),
+        (ApplicationHeader::Output { data }, "MT") => data.message_type = value.to_string(),
+        (ApplicationHeader::Input { data }, "PRIORITY") => data.priority = value.to_string(),
+        (ApplicationHeader::Output { data }, "PRIORITY") => {
+            data.message_priority = value.to_string()
+        }
+        (ApplicationHeader::Input { data }, "ADDRESS") => {
+            data.destination = LogicalTerminalAddress::parse(value)?
+        }
+        (ApplicationHeader::Output { data }, "ADDRESS") => {
+            data.sender_address = LogicalTerminalAddress::parse(value)?
+        }
+        _ => {
+            return Err(io::Error::new(
+                InvalidData,
+                format!("Application header element {} cannot be set", element),
+            ))
+        }
+    }
+
+    return Ok(());
+}
+
+/// Content of the header block with the field set to the value, added if missing
+fn set_block_field(raw: &str, tag: &str, value: &str) -> String {
+    let mut fields = header_block_fields(raw);
+    match fields.iter_mut().find(|f| f.tag == tag) {
+        Some(field) => field.value = value.to_string(),
+        None => fields.push(Field::new(tag, value)),
+    }
+
+    return fields
+        .iter()
+        .map(|f| format!("{{{}:{}}}", f.tag, f.value))
+        .collect();
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::swift::mt::path::{MessagePath, Value};
+    use crate::swift::mt::{SwiftMtMessage, SwiftMtParser};
+
+    static MESSAGE: &str = "{1:F01FOOBARXXAXXX0123000456}{2:I101BANKDEFFXXXXN}{3:{108:MUR123}{121:eb6305c9-1f7f-49de-aed0-16487c27b42d}}{4:\r\n:20:REF123\r\n:30:230102\r\n:21:TX1\r\n:32B:EUR100,\r\n:59:/DE89370400440532013000\r\nJOHN DOE\r\n:21:TX2\r\n:32B:USD200,50\r\n:59F:/12345678\r\n1/JANE DOE\r\n2/MAIN STREET 1\r\n3/US/NEW YORK\r\n-}{5:{CHK:123456789ABC}}";
+
+    fn message() -> SwiftMtMessage {
+        return SwiftMtParser::new().parse(MESSAGE.to_string()).unwrap();
+    }
+
+    fn get(message: &SwiftMtMessage, path: &str) -> Vec<String> {
+        return MessagePath::parse(path)
+            .unwrap()
+            .get(message)
+            .unwrap()
+            .iter()
+            .map(|v| v.to_string())
+            .collect();
+    }
+
+    #[test]
+    fn positive_tests() {
+        let message = message();
+        assert_eq!(get(&message, "B1.LT"), vec!["FOOBARXXAXXX"]);
+        assert_eq!(get(&message, "B1.SEQUENCE"), vec!["456"]);
+        assert_eq!(get(&message, "B2.MT"), vec!["101"]);
+        assert_eq!(get(&message, "B2.address"), vec!["BANKDEFFXXXX"]);
+        assert_eq!(
+            get(&message, "B3.121"),
+            vec!["eb6305c9-1f7f-49de-aed0-16487c27b42d"]
+        );
+        assert_eq!(get(&message, "B5.CHK"), vec!["123456789ABC"]);
+        assert_eq!(get(&message, "B4.20"), vec!["REF123"]);
+        assert_eq!(get(&message, "B4.32B"), vec!["EUR100,"]);
+        assert_eq!(get(&message, "B4.32B[2].amount"), vec!["200,50"]);
+        assert_eq!(get(&message, "B4.SeqB[2].59F.name"), vec!["JANE DOE"]);
+        assert_eq!(get(&message, "B4.SeqB[*].21"), vec!["TX1", "TX2"]);
+        assert_eq!(get(&message, "B4.59a[*].account").len(), 2);
+        assert_eq!(get(&message, "B4.*").len(), 8);
+        assert!(get(&message, "B3.113").is_empty());
+
+        let path = MessagePath::parse("B4.SeqB.59.iban").unwrap();
+        assert!(matches!(
+            path.get_first(&message).unwrap(),
+            Some(Value::Iban(_))
+        ));
+        assert_eq!(path.to_string(), "B4.SeqB.59.iban");
+
+        let mut message = message.clone();
+        let set = |message: &mut SwiftMtMessage, path: &str, value: &str| {
+            return MessagePath::parse(path)
+                .unwrap()
+                .set(message, value)
+                .unwrap();
+        };
+        assert_eq!(set(&mut message, "B4.SeqB[*].32B.currency", "CHF"), 2);
+        assert_eq!(get(&message, "B4.32B[*]"), vec!["CHF100,", "CHF200,50"]);
+        assert_eq!(set(&mut message, "B4.SeqB[2].59F.account", "87654321"), 1);
+        assert_eq!(get(&message, "B4.59F.account"), vec!["87654321"]);
+        assert_eq!(set(&mut message, "B4.SeqB[1].70", "INVOICE 1"), 1);
+        assert_eq!(set(&mut message, "B4.72", "/ACC/INFO"), 1);
+        assert_eq!(
+            message
+                .text
+                .fields
+                .iter()
+                .map(|f| f.tag.as_str())
+                .collect::<Vec<&str>>(),
+            vec!["20", "30", "72", "21", "32B", "59", "70", "21", "32B", "59F"]
+        );
+        set(&mut message, "B3.119", "STP");
+        assert_eq!(get(&message, "B3.119"), vec!["STP"]);
+        set(&mut message, "B5.PDE", "");
+        assert!(message.to_raw().contains("{PDE:}"));
+        set(&mut message, "B1.SESSION", "124");
+        assert!(message
+            .to_raw()
+            .starts_with("{1:F01FOOBARXXAXXX0124000456}"));
+        set(&mut message, "B2.MT", "102");
+        assert_eq!(get(&message, "B2.MT"), vec!["102"]);
+
+        assert!(matches!(
+            MessagePath::parse("B3.121").unwrap().get_first(&message),
+            Ok(Some(Value::Uetr(_)))
+        ));
+        assert!(matches!(
+            MessagePath::parse("B3.119").unwrap().get_first(&message),
+            Ok(Some(Value::ValidationFlag(_)))
+        ));
+        assert_eq!(
+            MessagePath::parse("B5.PDE")
+                .unwrap()
+                .get_first(&message)
+                .unwrap(),
+            Some(Value::Flag)
+        );
+
+        set(&mut message, "B4.SeqB[1].59.name", "JOHN ROE");
+        set(
+            &mut message,
+            "B4.SeqB[1].59.address",
+            "MAIN STREET 2\r\nBERLIN",
+        );
+        set(&mut message, "B4.SeqB[1].59.iban", "GB82WEST12345698765432");
+        assert_eq!(
+            get(&message, "B4.59"),
+            vec!["/GB82WEST12345698765432\r\nJOHN ROE\r\nMAIN STREET 2\r\nBERLIN"]
+        );
+        set(&mut message, "B4.59F.name", "JANE ROE");
+        set(&mut message, "B4.59F.country", "CA");
+        set(&mut message, "B4.59F.address", "HIGH STREET 3");
+        assert_eq!(
+            get(&message, "B4.59F"),
+            vec!["/87654321\r\n1/JANE ROE\r\n2/HIGH STREET 3\r\n3/CA/NEW YORK"]
+        );
+        set(&mut message, "B4.SeqB[2].57A", "DEUTDEFF");
+        set(&mut message, "B4.57A.bic", "BNPAFRPP");
+        assert_eq!(get(&message, "B4.57A.bic"), vec!["BNPAFRPP"]);
+    }
+
+    #[test]
+    fn negative_tests() {
+        for path in [
+            "B4",
+            "B6.20",
+            "B1.FOO",
+            "B4.SeqB[0].21",
+            "B4.2",
+            "B4.32A.foo",
+            "B4.32A.amount.x",
+            "B4.SeqB",
+            "B4.1é",
+            "B4.SeqB.é9",
+        ] {
+            assert!(MessagePath::parse(path).is_err(), "{}", path);
+        }
+
+        let mut message = message();
+        assert!(get(&message, "B4.SeqC.21").is_empty());
+        assert!(MessagePath::parse("B4.20.amount")
+            .unwrap()
+            .get(&message)
+            .is_err());
+        assert!(MessagePath::parse("B3.121")
+            .unwrap()
+            .set(&mut message, "not-a-uetr")
+            .is_err());
+        assert!(MessagePath::parse("B4.32B.date")
+            .unwrap()
+            .set(&mut message, "230102")
+            .is_err());
+        assert!(MessagePath::parse("B4.SeqC.21")
+            .unwrap()
+            .set(&mut message, "TX3")
+            .is_err());
+        for (path, value) in [
+            ("B4.59a[*].bic", "DEUTDEFF"),
+            ("B4.59.country", "DE"),
+            ("B4.59F.country", "XX"),
+            ("B4.59.iban", "DE88370400440532013000"),
+            ("B4.59.lines", "JOHN DOE"),
+            ("B2.MT", "10"),
+            ("B2.MT", "1O3"),
+            ("B2.PRIORITY", "X"),
+        ] {
+            assert!(
+                MessagePath::parse(path)
+                    .unwrap()
+                    .set(&mut message, value)
+                    .is_err(),
+                "{}",
+                path
+            );
+        }
+    }
+}
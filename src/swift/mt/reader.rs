@@ -0,0 +1,308 @@
+use crate::swift::mt::swift_mt_parser::{SwiftMtMessage, SwiftMtParser};
+use std::io;
+use std::io::ErrorKind::{Interrupted, InvalidData};
+use std::io::Read;
+
+/// Default limit for the size of a single message, well above the 10,000 characters of the
+/// message text of FIN plus headers, trailers and an appended original message
+pub(crate) static MAX_MESSAGE_SIZE: usize = 64 * 1024;
+
+/// Size of the chunks read from the underlying reader
+pub(crate) static CHUNK_SIZE: usize = 8 * 1024;
+
+/// Splits a stream of FIN messages into single messages as data arrives. Messages are
+/// separated by the `$` of RJE batches or follow each other directly, a message ending before
+/// the basic header of the next one. The original message appended to an acknowledgement
+/// (service identifier 21) stays part of the acknowledgement.
+///
+/// The framer does no I/O itself, so the synchronous and the asynchronous reader frame the
+/// messages the same way.
+#[derive(Debug)]
+pub(crate) struct MessageFramer {
+    buffer: Vec<u8>,
+    /// Position up to which the buffer has been scanned
+    position: usize,
+    /// Nesting level of the braces at the scanned position
+    depth: usize,
+    /// Basic headers seen in the current message
+    basic_headers: usize,
+    /// Start of the current top level block
+    block_start: usize,
+    acknowledgement: bool,
+    pub(crate) max_message_size: usize,
+    /// Whether only `$` ends a message, see `MessageReader::with_separator_required`
+    pub(crate) separator_required: bool,
+}
+
+impl MessageFramer {
+    pub(crate) fn new(max_message_size: usize) -> MessageFramer {
+        return MessageFramer {
+            buffer: Vec::new(),
+            position: 0,
+            depth: 0,
+            basic_headers: 0,
+            block_start: 0,
+            acknowledgement: false,
+            max_message_size,
+            separator_required: false,
+        };
+    }
+
+    pub(crate) fn push(&mut self, data: &[u8]) {
+        self.buffer.extend_from_slice(data);
+    }
+
+    /// The next complete message, or None if more data is needed. At the end of the stream
+    /// the rest of the buffer is the last message.
+    pub(crate) fn next_message(
+        &mut self,
+        end_of_stream: bool,
+    ) -> Result<Option<String>, io::Error> {
+        loop {
+            let (end, skip) = match self.scan(end_of_stream) {
+                Some(boundary) => boundary,
+                None if end_of_stream => (self.buffer.len(), 0),
+                None => {
+                    if self.buffer.len() > self.max_message_size {
+                        self.reset(self.buffer.len());
+                        return Err(io::Error::new(
+                            InvalidData,
+                            format!(
+                                "Message exceeds the maximum size of {} bytes",
+                                self.max_message_size
+                            ),
+                        ));
+                    }
+                    return Ok(None);
+                }
+            };
+
+            let message = String::from_utf8(self.buffer[..end].to_vec());
+            self.reset(end + skip);
+            let message = message.map_err(|e| {
+                io::Error::new(InvalidData, format!("Message is not valid UTF-8: {}", e))
+            })?;
+
+            if !message.trim().is_empty() {
+                return Ok(Some(message.trim().to_string()));
+            }
+            if self.buffer.is_empty() {
+                return Ok(None);
+            }
+        }
+    }
+
+    /// Scans the new data for the end of the current message, returning the end and the
+    /// number of separator bytes after it
+    fn scan(&mut self, end_of_stream: bool) -> Option<(usize, usize)> {
+        while self.position < self.buffer.len() {
+            let position = self.position;
+            match self.buffer[position] {
+                b'$' if self.depth == 0 => return Some((position, 1)),
+                b'{' if self.depth == 0 => {
+                    // the block identifier may not have arrived yet
+                    let Some(identifier) = self.buffer.get(position + 1..position + 3) else {
+                        return end_of_stream.then_some((self.buffer.len(), 0));
+                    };
+                    if identifier == b"1:" {
+                        let original = self.acknowledgement && self.basic_headers == 1;
+                        if self.basic_headers > 0 && !original && !self.separator_required {
+                            return Some((position, 0));
+                        }
+                        self.basic_headers += 1;
+                    }
+                    self.block_start = position;
+                    self.depth = 1;
+                }
+                b'{' => self.depth += 1,
+                b'}' if self.depth > 0 => {
+                    self.depth -= 1;
+                    let block = &self.buffer[self.block_start..];
+                    if self.depth == 0 && self.basic_headers == 1 && block.starts_with(b"{1:") {
+                        self.acknowledgement = block[3..].starts_with(b"F21");
+                    }
+                }
+                _ => {}
+            }
+            self.position += 1;
+        }
+
+        return None;
+    }
+
+    /// Drops the buffer up to the position and starts a new message
+    fn reset(&mut self, position: usize) {
+        self.buffer.drain(..position);
+        self.position = 0;
+        self.depth = 0;
+        self.basic_headers = 0;
+        self.block_start = 0;
+        self.acknowledgement = false;
+    }
+}
+
+/// Reads the messages of a file or socket one by one, e.g. RJE batches or messages following
+/// each other directly. Data is read in chunks only when the next message is requested, so a
+/// slow consumer slows down the producer instead of filling up memory.
+pub struct MessageReader<R: Read> {
+    reader: R,
+    framer: MessageFramer,
+    end_of_stream: bool,
+}
+
+impl<R: Read> MessageReader<R> {
+    pub fn new(reader: R) -> MessageReader<R> {
+        return MessageReader {
+            reader,
+            framer: MessageFramer::new(MAX_MESSAGE_SIZE),
+            end_of_stream: false,
+        };
+    }
+
+    /// Limit for the size of a single message, 64 KiB by default. The data read so far of a
+    /// larger message is dropped and reported as an error.
+    pub fn with_max_message_size(mut self, size: usize) -> MessageReader<R> {
+        self.framer.max_message_size = size;
+        return self;
+    }
+
+    /// Ends messages only at the `$` of RJE batches or at the end of the stream, for
+    /// connections where a message is answered as soon as it arrives. Without the separator a
+    /// message could not be told apart from the start of a slow one, so messages following
+    /// each other directly are read as one invalid message.
+    pub fn with_separator_required(mut self) -> MessageReader<R> {
+        self.framer.separator_required = true;
+        return self;
+    }
+
+    /// The next message in FIN format without parsing it, None at the end of the stream
+    pub fn next_raw(&mut self) -> Result<Option<String>, io::Error> {
+        let mut chunk = vec![0u8; CHUNK_SIZE];
+        loop {
+            if let Some(message) = self.framer.next_message(self.end_of_stream)? {
+                return Ok(Some(message));
+            }
+            if self.end_of_stream {
+                return Ok(None);
+            }
+
+            match self.reader.read(&mut chunk) {
+                Ok(0) => self.end_of_stream = true,
+                Ok(n) => self.framer.push(&chunk[..n]),
+                Err(e) if e.kind() == Interrupted => {}
+                Err(e) => return Err(e),
+            }
+        }
+    }
+}
+
+/// Parses the messages, reporting messages that cannot be parsed as errors without ending
+/// the iteration
+impl<R: Read> Iterator for MessageReader<R> {
+    type Item = Result<SwiftMtMessage, io::Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        return match self.next_raw() {
+            Ok(Some(raw)) => Some(parse(raw)),
+            Ok(None) => None,
+            Err(e) => Some(Err(e)),
+        };
+    }
+}
+
+pub(crate) fn parse(raw: String) -> Result<SwiftMtMessage, io::Error> {
+    return SwiftMtParser::new()
+        .parse(raw)
+        .map_err(|e| io::Error::new(InvalidData, e.message));
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::swift::mt::reader::{MessageFramer, MessageReader};
+    use std::io::Read;
+
+    static FIRST: &str = "{1:F01FOOBARXXAXXX0123000456}{2:I103BANKDEFFXXXXN}{4:\r\n:20:REF1\r\n-}{5:{CHK:123456789ABC}}";
+    static SECOND: &str = "{1:F01FOOBARXXAXXX0123000457}{2:I202BANKDEFFXXXXN}{4:\r\n:20:REF2\r\n-}";
+    static ACK: &str = "{1:F21FOOBARXXAXXX0123000456}{4:{177:2301021530}{451:0}}{1:F01FOOBARXXAXXX0123000456}{2:I103BANKDEFFXXXXN}{4:\r\n:20:REF1\r\n-}";
+
+    /// Reader returning the data a few bytes at a time, like a socket
+    struct Trickle {
+        data: Vec<u8>,
+        position: usize,
+    }
+
+    impl Read for Trickle {
+        fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+            let n = buf.len().min(3).min(self.data.len() - self.position);
+            buf[..n].copy_from_slice(&self.data[self.position..self.position + n]);
+            self.position += n;
+            return Ok(n);
+        }
+    }
+
+    fn trickle(data: &str) -> Trickle {
+        return Trickle {
+            data: data.as_bytes().to_vec(),
+            position: 0,
+        };
+    }
+
+    #[test]
+    fn positive_tests() {
+        let batch = format!("{}\r\n${}\r\n$\r\n", FIRST, SECOND);
+        let mut reader = MessageReader::new(trickle(&batch));
+        assert_eq!(reader.next_raw().unwrap().as_deref(), Some(FIRST));
+        assert_eq!(reader.next_raw().unwrap().as_deref(), Some(SECOND));
+        assert_eq!(reader.next_raw().unwrap(), None);
+
+        let stream = format!("{}\r\n{}{}", FIRST, ACK, SECOND);
+        let messages: Vec<_> = MessageReader::new(trickle(&stream))
+            .map(|m| m.unwrap())
+            .collect();
+        assert_eq!(messages.len(), 3);
+        assert_eq!(messages[0].text.field("20").unwrap().value, "REF1");
+        assert!(messages[1].original_message.is_some());
+        assert_eq!(messages[2].to_raw(), SECOND);
+
+        let mut framer = MessageFramer::new(1024);
+        framer.push(&FIRST.as_bytes()[..20]);
+        assert_eq!(framer.next_message(false).unwrap(), None);
+        framer.push(&FIRST.as_bytes()[20..]);
+        assert_eq!(framer.next_message(false).unwrap(), None);
+        framer.push(b"{");
+        assert_eq!(framer.next_message(false).unwrap(), None);
+        framer.push(b"1:");
+        assert_eq!(framer.next_message(false).unwrap().as_deref(), Some(FIRST));
+        assert_eq!(framer.next_message(true).unwrap().as_deref(), Some("{1:"));
+    }
+
+    #[test]
+    fn negative_tests() {
+        let stream = format!("{}$garbage${}", FIRST, SECOND);
+        let results: Vec<_> = MessageReader::new(stream.as_bytes()).collect();
+        assert_eq!(results.len(), 3);
+        assert!(results[0].is_ok());
+        assert!(results[1].is_err());
+        assert!(results[2].is_ok());
+
+        let truncated = &FIRST[..40];
+        let mut reader = MessageReader::new(truncated.as_bytes());
+        assert!(reader.next().unwrap().is_err());
+        assert!(reader.next().is_none());
+
+        let stream = format!("{}{}$", FIRST, SECOND);
+        let mut reader = MessageReader::new(trickle(&stream)).with_separator_required();
+        assert_eq!(
+            reader.next_raw().unwrap(),
+            Some(format!("{}{}", FIRST, SECOND))
+        );
+        assert_eq!(reader.next_raw().unwrap(), None);
+
+        let mut reader = MessageReader::new(trickle(FIRST)).with_max_message_size(30);
+        assert!(reader.next_raw().is_err());
+
+        let mut reader = MessageReader::new(&[b'{', b'1', b':', 0xff, b'}'][..]);
+        assert!(reader.next_raw().is_err());
+        assert_eq!(reader.next_raw().unwrap(), None);
+    }
+}
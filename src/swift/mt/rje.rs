@@ -0,0 +1,48 @@
+/// Splits an RJE (remote job entry) batch into its messages. Messages in a batch are separated
+/// by `$`, which is not part of the character sets of FIN, so it cannot occur inside a
+/// message. Whitespace around the messages and empty parts are dropped.
+pub fn split_rje(batch: &str) -> Vec<&str> {
+    return batch
+        .split('$')
+        .map(|message| message.trim())
+        .filter(|message| !message.is_empty())
+        .collect();
+}
+
+/// Joins messages to an RJE batch
+pub fn join_rje<S: AsRef<str>>(messages: &[S]) -> String {
+    return messages
+        .iter()
+        .map(|message| message.as_ref())
+        .collect::<Vec<&str>>()
+        .join("\r\n$");
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::swift::mt::rje::{join_rje, split_rje};
+    use crate::swift::mt::SwiftMtParser;
+
+    static FIRST: &str = "{1:F01FOOBARXXAXXX0123000456}{2:I103BANKDEFFXXXXN}{4:\r\n:20:REF1\r\n-}";
+    static SECOND: &str = "{1:F01FOOBARXXAXXX0123000457}{2:I202BANKDEFFXXXXN}{4:\r\n:20:REF2\r\n-}";
+
+    #[test]
+    fn positive_tests() {
+        let batch = join_rje(&[FIRST, SECOND]);
+        let messages = split_rje(&batch);
+        assert_eq!(messages, vec![FIRST, SECOND]);
+
+        for message in messages {
+            assert!(SwiftMtParser::new().parse(message.to_string()).is_ok());
+        }
+
+        assert_eq!(split_rje(&format!("{}\r\n$\r\n", FIRST)), vec![FIRST]);
+        assert_eq!(split_rje(FIRST), vec![FIRST]);
+    }
+
+    #[test]
+    fn negative_tests() {
+        assert!(split_rje("").is_empty());
+        assert!(split_rje("$\r\n$").is_empty());
+    }
+}
@@ -0,0 +1,638 @@
+use crate::swift::mt::fields::AmountField;
+use crate::swift::mt::model::ApplicationHeader;
+use crate::swift::mt::path::MessagePath;
+use crate::swift::mt::rje::split_rje;
+use crate::swift::mt::swift_mt_parser::{SwiftMtMessage, SwiftMtParser};
+use crate::swift::mt::validator::Validator;
+use chrono::Local;
+use std::fs;
+use std::fs::OpenOptions;
+use std::io;
+use std::io::ErrorKind::InvalidData;
+use std::io::Write;
+use std::path::{Component, Path, PathBuf};
+use std::thread;
+use std::time::Duration;
+
+/// Extensions of the inbound files picked up by the router
+static INBOUND_EXTENSIONS: [&str; 2] = ["fin", "rje"];
+
+/// Condition of a route on one property of a message, holding if the property has one of the
+/// values
+#[derive(Debug, Clone, PartialEq)]
+pub enum RouteCriterion {
+    /// Message type, e.g. `103`
+    MessageType(Vec<String>),
+    /// Sender address, matching the logical terminal address or the start of the BIC, e.g.
+    /// `FOOBARXX` for all branches
+    Sender(Vec<String>),
+    /// Receiver address, matched like the sender
+    Receiver(Vec<String>),
+    /// Currency of the first amount field
+    Currency(Vec<String>),
+    /// Value at a path, holding if any of the selected values matches
+    Path(MessagePath, Vec<String>),
+}
+
+/// Destination directory for the messages meeting all criteria
+#[derive(Debug, Clone, PartialEq)]
+pub struct Route {
+    /// Directory relative to the output directory of the router
+    pub destination: String,
+    pub criteria: Vec<RouteCriterion>,
+    /// The criteria as written in the catalogue, for the audit log
+    pub description: String,
+}
+
+/// Where a message of an inbound file went and why, one line of the audit log
+#[derive(Debug, Clone, PartialEq)]
+pub struct RoutingDecision {
+    /// Inbound file the message was read from
+    pub source: PathBuf,
+    /// Position of the message in the file, starting at 1
+    pub index: usize,
+    /// File the message was moved or written to
+    pub target: PathBuf,
+    pub dead_letter: bool,
+    pub reason: String,
+}
+
+/// Watches an inbound directory of FIN files and RJE batches (`*.fin` and `*.rje`) and moves
+/// every message into the destination of the first route it matches. Messages that cannot be
+/// parsed, fail validation or match no route (without a default destination) go to the
+/// dead letter directory, as do files that are not valid UTF-8.
+///
+/// A file whose messages all go to the same directory is moved there unchanged, otherwise
+/// each message is written to a file of its own named like `split` does (`<name>_0001.fin`)
+/// and the inbound file is removed. Next to each moved or written file, `<file>.audit` records
+/// the decisions about its messages, one line `time|source|message|destination|reason` each.
+///
+/// While watching, files modified within the last poll interval are left for a later poll, as
+/// they may still be being written. Producers should nevertheless write a file under another
+/// name, e.g. `batch.rje.part`, and rename it once it is complete.
+pub struct Router {
+    inbound: PathBuf,
+    output: PathBuf,
+    dead_letter: PathBuf,
+    routes: Vec<Route>,
+    default_destination: Option<String>,
+    validator: Option<Validator>,
+}
+
+impl RouteCriterion {
+    /// Parses `key=value,value`, where the key is `mt`, `sender`, `receiver`, `currency` or a
+    /// `MessagePath`
+    pub fn parse(criterion: &str) -> Result<RouteCriterion, io::Error> {
+        let Some((key, values)) = criterion.split_once('=') else {
+            return Err(io::Error::new(
+                InvalidData,
+                format!("Route criterion without value: {}", criterion),
+            ));
+        };
+
+        let values: Vec<String> = values
+            .split(',')
+            .map(|v| v.trim().to_string())
+            .filter(|v| !v.is_empty())
+            .collect();
+        if values.is_empty() {
+            return Err(io::Error::new(
+                InvalidData,
+                format!("Route criterion without value: {}", criterion),
+            ));
+        }
+
+        return Ok(match key.trim() {
+            "mt" => RouteCriterion::MessageType(values),
+            "sender" => RouteCriterion::Sender(values),
+            "receiver" => RouteCriterion::Receiver(values),
+            "currency" => RouteCriterion::Currency(values),
+            path => RouteCriterion::Path(MessagePath::parse(path)?, values),
+        });
+    }
+
+    pub fn matches(&self, message: &SwiftMtMessage) -> bool {
+        return match self {
+            RouteCriterion::MessageType(values) => message
+                .application_header
+                .message_type()
+                .is_some_and(|mt| values.iter().any(|v| v == mt)),
+            RouteCriterion::Sender(values) => {
+                let sender = match &message.application_header {
+                    ApplicationHeader::Output { data } => &data.sender_address,
+                    _ => &message.basic_header.logical_terminal,
+                };
+                address_matches(&sender.to_string(), values)
+            }
+            RouteCriterion::Receiver(values) => {
+                let receiver = match &message.application_header {
+                    ApplicationHeader::Input { data } => &data.destination,
+                    _ => &message.basic_header.logical_terminal,
+                };
+                address_matches(&receiver.to_string(), values)
+            }
+            RouteCriterion::Currency(values) => {
+                currency(message).is_some_and(|currency| values.contains(&currency))
+            }
+            RouteCriterion::Path(path, values) => path.get(message).is_ok_and(|found| {
+                found
+                    .iter()
+                    .any(|value| values.contains(&value.to_string()))
+            }),
+        };
+    }
+}
+
+impl Route {
+    /// Route for the criteria, a semicolon separated list like `mt=103,202;currency=EUR`.
+    /// Without criteria the route matches every message.
+    pub fn parse(destination: &str, criteria: &str) -> Result<Route, io::Error> {
+        check_destination(destination)?;
+        return Ok(Route {
+            destination: destination.to_string(),
+            criteria: criteria
+                .split(';')
+                .map(|c| c.trim())
+                .filter(|c| !c.is_empty())
+                .map(RouteCriterion::parse)
+                .collect::<Result<_, _>>()?,
+            description: criteria.to_string(),
+        });
+    }
+
+    /// Reads routes from the line based catalogue format `destination|criteria`, in the order
+    /// they are tried. Empty lines and lines starting with # are ignored.
+    pub fn from_catalogue(catalogue: &str) -> Result<Vec<Route>, io::Error> {
+        let mut routes = Vec::new();
+
+        for (number, line) in catalogue.lines().map(|l| l.trim()).enumerate() {
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let Some((destination, criteria)) = line.split_once('|') else {
+                return Err(io::Error::new(
+                    InvalidData,
+                    format!("Route in line {} has no criteria column", number + 1),
+                ));
+            };
+            routes.push(Route::parse(destination.trim(), criteria.trim())?);
+        }
+
+        return Ok(routes);
+    }
+
+    pub fn matches(&self, message: &SwiftMtMessage) -> bool {
+        return self.criteria.iter().all(|c| c.matches(message));
+    }
+}
+
+impl RoutingDecision {
+    /// Line of the audit log
+    pub fn to_audit_line(&self) -> String {
+        let destination = self
+            .target
+            .parent()
+            .and_then(|d| d.file_name())
+            .unwrap_or_default();
+        return format!(
+            "{}|{}|{}|{}|{}",
+            Local::now().format("%Y-%m-%dT%H:%M:%S"),
+            self.source.display(),
+            self.index,
+            destination.to_string_lossy(),
+            self.reason
+        );
+    }
+}
+
+impl Router {
+    /// Router moving the files of the inbound directory to destinations below the output
+    /// directory, with the dead letter directory `dead-letter` below the output directory
+    pub fn new(inbound: &Path, output: &Path) -> Router {
+        return Router {
+            inbound: inbound.to_path_buf(),
+            output: output.to_path_buf(),
+            dead_letter: output.join("dead-letter"),
+            routes: Vec::new(),
+            default_destination: None,
+            validator: None,
+        };
+    }
+
+    pub fn with_route(mut self, route: Route) -> Router {
+        self.routes.push(route);
+        return self;
+    }
+
+    pub fn with_routes(mut self, routes: Vec<Route>) -> Router {
+        self.routes.extend(routes);
+        return self;
+    }
+
+    pub fn with_dead_letter(mut self, dead_letter: &Path) -> Router {
+        self.dead_letter = dead_letter.to_path_buf();
+        return self;
+    }
+
+    /// Destination of messages matching no route, which go to the dead letter directory
+    /// otherwise. Like the destinations of routes it must be a directory below the output
+    /// directory.
+    pub fn with_default_destination(mut self, destination: &str) -> Result<Router, io::Error> {
+        check_destination(destination)?;
+        self.default_destination = Some(destination.to_string());
+        return Ok(self);
+    }
+
+    /// Validates the messages before routing them, sending those with errors to the dead
+    /// letter directory
+    pub fn with_validator(mut self, validator: Validator) -> Router {
+        self.validator = Some(validator);
+        return self;
+    }
+
+    /// The first route the message matches
+    pub fn route(&self, message: &SwiftMtMessage) -> Option<&Route> {
+        return self.routes.iter().find(|r| r.matches(message));
+    }
+
+    /// Routes the files currently in the inbound directory in the order of their names
+    pub fn route_pending(&self) -> Result<Vec<RoutingDecision>, io::Error> {
+        return self.route_settled(Duration::ZERO);
+    }
+
+    /// Routes the files of the inbound directory that were last modified at least the given
+    /// time ago, leaving the others in place
+    fn route_settled(&self, age: Duration) -> Result<Vec<RoutingDecision>, io::Error> {
+        let mut files: Vec<PathBuf> = fs::read_dir(&self.inbound)?
+            .filter_map(|entry| entry.ok().map(|e| e.path()))
+            .filter(|path| {
+                path.is_file()
+                    && path
+                        .extension()
+                        .is_some_and(|e| INBOUND_EXTENSIONS.iter().any(|x| e == *x))
+                    && is_settled(path, age)
+            })
+            .collect();
+        files.sort();
+
+        let mut decisions = Vec::new();
+        for file in files {
+            decisions.append(&mut self.route_file(&file)?);
+        }
+
+        return Ok(decisions);
+    }
+
+    /// Routes the files arriving in the inbound directory, polling it at the interval until
+    /// an error occurs. Files modified within the interval are routed by a later poll.
+    pub fn watch(&self, interval: Duration) -> Result<(), io::Error> {
+        loop {
+            self.route_settled(interval)?;
+            thread::sleep(interval);
+        }
+    }
+
+    /// Moves the messages of the file to their destinations
+    pub fn route_file(&self, file: &Path) -> Result<Vec<RoutingDecision>, io::Error> {
+        let content = String::from_utf8(fs::read(file)?).map(|content| {
+            match content.contains("\r\n") {
+                true => content,
+                false => content.replace('\n', "\r\n"),
+            }
+        });
+
+        let messages = match &content {
+            Ok(content) => split_rje(content),
+            Err(_) => Vec::new(),
+        };
+        let mut destinations: Vec<(PathBuf, String)> =
+            messages.iter().map(|raw| self.destination(raw)).collect();
+        if let Err(e) = &content {
+            destinations.push((
+                self.dead_letter.clone(),
+                format!("File is not valid UTF-8: {}", e.utf8_error()),
+            ));
+        } else if destinations.is_empty() {
+            destinations.push((self.dead_letter.clone(), "No messages".to_string()));
+        }
+
+        let name = file.file_name().unwrap_or_default().to_string_lossy();
+        let mut decisions = Vec::new();
+
+        if destinations.iter().all(|(d, _)| *d == destinations[0].0) {
+            fs::create_dir_all(&destinations[0].0)?;
+            let target = unique_path(&destinations[0].0, &name);
+            fs::rename(file, &target)?;
+            for (index, (destination, reason)) in destinations.into_iter().enumerate() {
+                decisions.push(self.decision(file, index, target.clone(), &destination, reason));
+            }
+        } else {
+            let stem = file.file_stem().unwrap_or_default().to_string_lossy();
+            for (index, (destination, reason)) in destinations.into_iter().enumerate() {
+                fs::create_dir_all(&destination)?;
+                let target = unique_path(&destination, &format!("{}_{:04}.fin", stem, index + 1));
+                fs::write(&target, messages[index])?;
+                decisions.push(self.decision(file, index, target, &destination, reason));
+            }
+            fs::remove_file(file)?;
+        }
+
+        for decision in &decisions {
+            let mut audit = OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(audit_path(&decision.target))?;
+            writeln!(audit, "{}", decision.to_audit_line())?;
+        }
+
+        return Ok(decisions);
+    }
+
+    /// Directory for the message and the reason for choosing it
+    fn destination(&self, raw: &str) -> (PathBuf, String) {
+        let message = match SwiftMtParser::new().parse(raw.to_string()) {
+            Ok(message) => message,
+            Err(e) => {
+                return (
+                    self.dead_letter.clone(),
+                    format!("Parsing error: {}", e.message),
+                )
+            }
+        };
+
+        if let Some(validator) = &self.validator {
+            let errors: Vec<String> = validator
+                .validate(&message)
+                .iter()
+                .map(|e| format!("{} {}", e.code, e.message))
+                .collect();
+            if !errors.is_empty() {
+                return (
+                    self.dead_letter.clone(),
+                    format!("Validation errors: {}", errors.join("; ")),
+                );
+            }
+        }
+
+        if let Some(position) = self.routes.iter().position(|r| r.matches(&message)) {
+            let route = &self.routes[position];
+            return (
+                self.output.join(&route.destination),
+                format!("Route {} matched: {}", position + 1, route.description),
+            );
+        }
+
+        return match &self.default_destination {
+            Some(destination) => (
+                self.output.join(destination),
+                "No route matched, default destination".to_string(),
+            ),
+            None => (self.dead_letter.clone(), "No route matched".to_string()),
+        };
+    }
+
+    fn decision(
+        &self,
+        source: &Path,
+        index: usize,
+        target: PathBuf,
+        destination: &Path,
+        reason: String,
+    ) -> RoutingDecision {
+        return RoutingDecision {
+            source: source.to_path_buf(),
+            index: index + 1,
+            target,
+            dead_letter: destination == self.dead_letter,
+            reason,
+        };
+    }
+}
+
+/// Destinations are relative paths that stay below the output directory
+fn check_destination(destination: &str) -> Result<(), io::Error> {
+    let path = Path::new(destination);
+    if destination.is_empty() || !path.components().all(|c| matches!(c, Component::Normal(_))) {
+        return Err(io::Error::new(
+            InvalidData,
+            format!("Invalid route destination: {}", destination),
+        ));
+    }
+
+    return Ok(());
+}
+
+/// Whether the address is the logical terminal address or the BIC starts with the value
+fn address_matches(address: &str, values: &[String]) -> bool {
+    if address.len() != 12 {
+        return false;
+    }
+
+    let bic11 = format!("{}{}", &address[0..8], &address[9..12]);
+    return values
+        .iter()
+        .any(|v| v == address || (v.len() >= 4 && bic11.starts_with(v.as_str())));
+}
+
+/// Currency of the first amount field of the message text
+fn currency(message: &SwiftMtMessage) -> Option<String> {
+    return message
+        .text
+        .fields
+        .iter()
+        .filter(|f| AmountField::is_amount_field(&f.tag))
+        .find_map(|f| AmountField::from_field(f).ok())
+        .map(|a| a.currency.code().to_string());
+}
+
+/// Whether the file was last modified at least the given time ago. A modification time in
+/// the future counts as recent.
+fn is_settled(file: &Path, age: Duration) -> bool {
+    if age.is_zero() {
+        return true;
+    }
+
+    return fs::metadata(file)
+        .and_then(|m| m.modified())
+        .is_ok_and(|modified| modified.elapsed().is_ok_and(|elapsed| elapsed >= age));
+}
+
+/// Path for the file name in the directory, numbered before the extension if a file of the
+/// name exists already (`single_2.fin`), so the name still matches the inbound extensions
+fn unique_path(directory: &Path, name: &str) -> PathBuf {
+    let mut path = directory.join(name);
+    let (stem, extension) = match name.rsplit_once('.') {
+        Some((stem, extension)) if !stem.is_empty() => (stem, format!(".{}", extension)),
+        _ => (name, String::new()),
+    };
+
+    let mut number = 1;
+    while path.exists() || audit_path(&path).exists() {
+        number += 1;
+        path = directory.join(format!("{}_{}{}", stem, number, extension));
+    }
+
+    return path;
+}
+
+fn audit_path(target: &Path) -> PathBuf {
+    let mut name = target.file_name().unwrap_or_default().to_os_string();
+    name.push(".audit");
+    return target.with_file_name(name);
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::swift::mt::router::{Route, RouteCriterion, Router};
+    use crate::swift::mt::{SwiftMtParser, Validator};
+    use std::env;
+    use std::fs;
+    use std::path::PathBuf;
+    use std::time::Duration;
+
+    static PAYMENT: &str = "{1:F01FOOBARXXAXXX0123000456}{2:I103BANKDEFFXXXXN}{4:\r\n:20:REF1\r\n:23B:CRED\r\n:32A:230102EUR1000,\r\n:50K:/12345678\r\nJOHN DOE\r\n:59:/DE89370400440532013000\r\nJANE DOE\r\n:71A:SHA\r\n-}";
+    static TRANSFER: &str = "{1:F01FOOBARXXAXXX0123000457}{2:I202BANKGB2LXXXXN}{4:\r\n:20:REF2\r\n:21:REL2\r\n:32A:230102GBP500,\r\n:58A:BANKGB2L\r\n-}";
+
+    static ROUTES: &str = "\
+# destination|criteria
+payments-eur|mt=103;currency=EUR
+uk|receiver=BANKGB2L
+jane|B4.59a.name=JANE DOE
+";
+
+    fn directory(name: &str) -> PathBuf {
+        let path = env::temp_dir().join(format!("rustmsg-router-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&path);
+        fs::create_dir_all(path.join("inbound")).unwrap();
+        return path;
+    }
+
+    #[test]
+    fn positive_tests() {
+        let routes = Route::from_catalogue(ROUTES).unwrap();
+        assert_eq!(routes.len(), 3);
+        assert_eq!(
+            routes[0].criteria[0],
+            RouteCriterion::MessageType(vec!["103".to_string()])
+        );
+
+        let payment = SwiftMtParser::new().parse(PAYMENT.to_string()).unwrap();
+        let router = Router::new(&PathBuf::from("in"), &PathBuf::from("out")).with_routes(routes);
+        assert_eq!(router.route(&payment).unwrap().destination, "payments-eur");
+        let gbp = SwiftMtParser::new()
+            .parse(PAYMENT.replace("EUR", "GBP"))
+            .unwrap();
+        assert_eq!(router.route(&gbp).unwrap().destination, "jane");
+
+        let root = directory("positive");
+        let router = Router::new(&root.join("inbound"), &root)
+            .with_routes(Route::from_catalogue(ROUTES).unwrap())
+            .with_validator(Validator::new());
+
+        fs::write(
+            root.join("inbound/single.fin"),
+            PAYMENT.replace("\r\n", "\n"),
+        )
+        .unwrap();
+        fs::write(
+            root.join("inbound/batch.rje"),
+            format!("{}\r\n${}", PAYMENT, TRANSFER),
+        )
+        .unwrap();
+        fs::write(root.join("inbound/ignored.tmp"), PAYMENT).unwrap();
+
+        let decisions = router.route_pending().unwrap();
+        assert_eq!(decisions.len(), 3);
+        assert!(decisions.iter().all(|d| !d.dead_letter));
+        assert_eq!(
+            decisions[0].target,
+            root.join("payments-eur/batch_0001.fin")
+        );
+        assert_eq!(decisions[1].target, root.join("uk/batch_0002.fin"));
+        assert_eq!(decisions[2].target, root.join("payments-eur/single.fin"));
+        assert_eq!(
+            fs::read_to_string(root.join("uk/batch_0002.fin")).unwrap(),
+            TRANSFER
+        );
+        assert!(root.join("payments-eur/single.fin").exists());
+        assert!(root.join("inbound/ignored.tmp").exists());
+        assert!(!root.join("inbound/batch.rje").exists());
+
+        let audit = fs::read_to_string(root.join("payments-eur/single.fin.audit")).unwrap();
+        assert!(audit
+            .trim_end()
+            .ends_with("|1|payments-eur|Route 1 matched: mt=103;currency=EUR"));
+
+        fs::write(root.join("inbound/single.fin"), PAYMENT).unwrap();
+        let decisions = router.route_pending().unwrap();
+        assert_eq!(decisions[0].target, root.join("payments-eur/single_2.fin"));
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn negative_tests() {
+        assert!(Route::from_catalogue("payments").is_err());
+        assert!(Route::from_catalogue("payments|mt").is_err());
+        assert!(Route::from_catalogue("payments|mt=").is_err());
+        assert!(Route::from_catalogue("payments|B9.X=1").is_err());
+        assert!(Route::from_catalogue("/tmp|mt=103").is_err());
+        assert!(Route::from_catalogue("../elsewhere|mt=103").is_err());
+        assert!(Route::from_catalogue("payments/../../elsewhere|mt=103").is_err());
+        assert!(Router::new(&PathBuf::from("in"), &PathBuf::from("out"))
+            .with_default_destination("..")
+            .is_err());
+
+        let root = directory("negative");
+        let router = Router::new(&root.join("inbound"), &root)
+            .with_route(Route::parse("uk", "receiver=BANKGB2L").unwrap())
+            .with_validator(Validator::new());
+
+        fs::write(
+            root.join("inbound/mixed.fin"),
+            format!(
+                "{}\r\n$garbage\r\n${}",
+                PAYMENT.replace("EUR1000,", "EUR1000,123"),
+                TRANSFER
+            ),
+        )
+        .unwrap();
+        let decisions = router.route_pending().unwrap();
+        assert_eq!(decisions.len(), 3);
+        assert!(decisions[0].dead_letter);
+        assert!(decisions[0].reason.starts_with("Validation errors: C03"));
+        assert!(decisions[1].dead_letter);
+        assert!(decisions[1].reason.starts_with("Parsing error"));
+        assert!(!decisions[2].dead_letter);
+        assert!(root.join("dead-letter/mixed_0002.fin.audit").exists());
+
+        fs::write(root.join("inbound/unrouted.fin"), PAYMENT).unwrap();
+        let decisions = router.route_pending().unwrap();
+        assert_eq!(decisions[0].reason, "No route matched");
+        assert_eq!(decisions[0].target, root.join("dead-letter/unrouted.fin"));
+
+        fs::write(root.join("inbound/binary.fin"), [b'{', b'1', b':', 0xff, b'}']).unwrap();
+        let decisions = router.route_pending().unwrap();
+        assert_eq!(decisions.len(), 1);
+        assert!(decisions[0].dead_letter);
+        assert!(decisions[0].reason.starts_with("File is not valid UTF-8"));
+        assert_eq!(decisions[0].target, root.join("dead-letter/binary.fin"));
+
+        let router = router.with_default_destination("other").unwrap();
+        fs::write(root.join("inbound/unrouted.fin"), PAYMENT).unwrap();
+        let decisions = router.route_pending().unwrap();
+        assert!(!decisions[0].dead_letter);
+        assert_eq!(decisions[0].target, root.join("other/unrouted.fin"));
+
+        // a file still being written is left for a later poll
+        fs::write(root.join("inbound/copying.fin"), &PAYMENT[..40]).unwrap();
+        assert!(router
+            .route_settled(Duration::from_secs(60))
+            .unwrap()
+            .is_empty());
+        assert!(root.join("inbound/copying.fin").exists());
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+}
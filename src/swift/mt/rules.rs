@@ -0,0 +1,485 @@
+use crate::swift::mt::swift_mt_parser::SwiftMtMessage;
+use crate::swift::mt::text::MessageText;
+use crate::utils::StringParser;
+use std::io;
+use std::io::ErrorKind::InvalidData;
+
+/// Network validated rules of the standards release, one rule per line in the catalogue format
+/// `rule id|error code|message types|condition|requirement`, see `RuleSet::from_catalogue`.
+static NETWORK_RULES: &str = "\
+C1|D75|103|33B AND 33B[0..3] != 32A[6..9]|36
+C6|E13|103|71A = 'OUR'|NOT 71F
+C6|D50|103|71A = 'SHA'|NOT 71G
+C6|E15|103|71A = 'BEN'|71F AND NOT 71G
+C1|C81|202,205|56a|57a
+";
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Operand {
+    /// Value of a field of the message text, optionally restricted to a character range
+    /// (e.g. `32A[6..9]` for the currency of field 32A)
+    Field {
+        tag: String,
+        range: Option<(usize, usize)>,
+    },
+    Literal(String),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Condition {
+    Always,
+    Present(String),
+    Equals(Operand, Operand),
+    NotEquals(Operand, Operand),
+    Not(Box<Condition>),
+    And(Box<Condition>, Box<Condition>),
+    Or(Box<Condition>, Box<Condition>),
+}
+
+/// A conditional constraint: whenever `condition` holds for a message of one of the
+/// `message_types` (all types if empty), `requirement` must hold as well. A message type with
+/// a validation flag such as `103STP` or `202COV` only applies to messages carrying the flag
+/// in field 119, a plain message type to all variants of the type.
+#[derive(Debug, Clone)]
+pub struct Rule {
+    pub id: String,
+    pub error_code: String,
+    pub message_types: Vec<String>,
+    pub condition: Condition,
+    pub requirement: Condition,
+    pub description: String,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RuleViolation {
+    pub rule_id: String,
+    pub error_code: String,
+    pub message: String,
+}
+
+pub struct RuleSet {
+    rules: Vec<Rule>,
+}
+
+impl Operand {
+    pub fn resolve(&self, text: &MessageText) -> Option<String> {
+        return match self {
+            Operand::Literal(value) => Some(value.clone()),
+            Operand::Field { tag, range } => {
+                let value = &text.field(tag)?.value;
+                match range {
+                    Some((start, end)) => Some(
+                        value
+                            .chars()
+                            .skip(*start)
+                            .take(end.saturating_sub(*start))
+                            .collect(),
+                    ),
+                    None => Some(value.clone()),
+                }
+            }
+        };
+    }
+}
+
+impl Condition {
+    /// Parses a condition such as `33B AND 33B[0..3] != 32A[6..9]` or `NOT (71A = 'OUR')`.
+    /// A bare tag tests for the presence of the field, a comparison involving an absent field
+    /// never holds.
+    pub fn parse(expression: &str) -> Result<Condition, io::Error> {
+        let mut parser = StringParser::new(expression.to_string());
+        let condition = parse_or(&mut parser)?;
+
+        skip_whitespace(&mut parser);
+        if parser.has_more() {
+            return Err(io::Error::new(
+                InvalidData,
+                format!(
+                    "Unexpected input at position {} of condition {}",
+                    parser.position(),
+                    expression
+                ),
+            ));
+        }
+
+        return Ok(condition);
+    }
+
+    pub fn evaluate(&self, text: &MessageText) -> bool {
+        return match self {
+            Condition::Always => true,
+            Condition::Present(tag) => text.has_field(tag),
+            Condition::Equals(left, right) => match (left.resolve(text), right.resolve(text)) {
+                (Some(l), Some(r)) => l == r,
+                _ => false,
+            },
+            Condition::NotEquals(left, right) => match (left.resolve(text), right.resolve(text)) {
+                (Some(l), Some(r)) => l != r,
+                _ => false,
+            },
+            Condition::Not(condition) => !condition.evaluate(text),
+            Condition::And(left, right) => left.evaluate(text) && right.evaluate(text),
+            Condition::Or(left, right) => left.evaluate(text) || right.evaluate(text),
+        };
+    }
+}
+
+impl Rule {
+    pub fn parse(
+        id: &str,
+        error_code: &str,
+        message_types: &[&str],
+        condition: &str,
+        requirement: &str,
+    ) -> Result<Rule, io::Error> {
+        return Ok(Rule {
+            id: id.into(),
+            error_code: error_code.into(),
+            message_types: message_types.iter().map(|mt| mt.to_string()).collect(),
+            condition: Condition::parse(condition)?,
+            requirement: Condition::parse(requirement)?,
+            description: format!("If {} then {}", condition, requirement),
+        });
+    }
+
+    pub fn applies_to(&self, message_type: &str) -> bool {
+        return self.message_types.is_empty()
+            || self.message_types.iter().any(|mt| mt == message_type);
+    }
+
+    pub fn check(&self, message: &SwiftMtMessage) -> Option<RuleViolation> {
+        let flag = message.user_header.validation_flag.as_ref();
+        let applies = message
+            .application_header
+            .message_type()
+            .map_or(self.message_types.is_empty(), |mt| {
+                self.applies_to(mt)
+                    || flag.is_some_and(|f| self.applies_to(&format!("{}{}", mt, f.code())))
+            });
+
+        if !applies
+            || !self.condition.evaluate(&message.text)
+            || self.requirement.evaluate(&message.text)
+        {
+            return None;
+        }
+
+        return Some(RuleViolation {
+            rule_id: self.id.clone(),
+            error_code: self.error_code.clone(),
+            message: format!("Rule {} violated: {}", self.id, self.description),
+        });
+    }
+}
+
+impl Default for RuleSet {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl RuleSet {
+    pub fn new() -> RuleSet {
+        return RuleSet { rules: Vec::new() };
+    }
+
+    /// The network validated rules shipped with the crate
+    pub fn network_rules() -> RuleSet {
+        return RuleSet::from_catalogue(NETWORK_RULES).expect("Built-in network rules are invalid");
+    }
+
+    /// Reads rules from the line based catalogue format
+    /// `rule id|error code|message types|condition|requirement` where message types is a comma
+    /// separated list (empty for all types). Empty lines and lines starting with # are ignored.
+    pub fn from_catalogue(catalogue: &str) -> Result<RuleSet, io::Error> {
+        let mut rule_set = RuleSet::new();
+
+        for line in catalogue.lines().map(|l| l.trim()) {
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let columns: Vec<&str> = line.split('|').map(|c| c.trim()).collect();
+            if columns.len() != 5 {
+                return Err(io::Error::new(
+                    InvalidData,
+                    format!(
+                        "Rule must have 5 columns but had {}: {}",
+                        columns.len(),
+                        line
+                    ),
+                ));
+            }
+
+            let message_types: Vec<&str> = columns[2]
+                .split(',')
+                .map(|mt| mt.trim())
+                .filter(|mt| !mt.is_empty())
+                .collect();
+
+            rule_set.add(Rule::parse(
+                columns[0],
+                columns[1],
+                &message_types,
+                columns[3],
+                columns[4],
+            )?);
+        }
+
+        return Ok(rule_set);
+    }
+
+    pub fn add(&mut self, rule: Rule) {
+        self.rules.push(rule);
+    }
+
+    pub fn rules(&self) -> &[Rule] {
+        return &self.rules;
+    }
+
+    pub fn evaluate(&self, message: &SwiftMtMessage) -> Vec<RuleViolation> {
+        return self
+            .rules
+            .iter()
+            .filter_map(|rule| rule.check(message))
+            .collect();
+    }
+}
+
+fn parse_or(parser: &mut StringParser) -> Result<Condition, io::Error> {
+    let mut condition = parse_and(parser)?;
+    while next_keyword_is(parser, "OR") {
+        condition = Condition::Or(Box::new(condition), Box::new(parse_and(parser)?));
+    }
+
+    return Ok(condition);
+}
+
+fn parse_and(parser: &mut StringParser) -> Result<Condition, io::Error> {
+    let mut condition = parse_unary(parser)?;
+    while next_keyword_is(parser, "AND") {
+        condition = Condition::And(Box::new(condition), Box::new(parse_unary(parser)?));
+    }
+
+    return Ok(condition);
+}
+
+fn parse_unary(parser: &mut StringParser) -> Result<Condition, io::Error> {
+    if next_keyword_is(parser, "NOT") {
+        return Ok(Condition::Not(Box::new(parse_unary(parser)?)));
+    }
+
+    skip_whitespace(parser);
+    if parser.peek().ok() == Some('(') {
+        parser.next()?;
+        let condition = parse_or(parser)?;
+        expect(parser, ')')?;
+        return Ok(condition);
+    }
+
+    let left = parse_operand(parser)?;
+    skip_whitespace(parser);
+
+    return match parser.peek().ok() {
+        Some('=') => {
+            parser.next()?;
+            Ok(Condition::Equals(left, parse_operand(parser)?))
+        }
+        Some('!') => {
+            parser.next()?;
+            expect(parser, '=')?;
+            Ok(Condition::NotEquals(left, parse_operand(parser)?))
+        }
+        _ => match left {
+            Operand::Field { tag, range: None } => Ok(Condition::Present(tag)),
+            _ => Err(io::Error::new(
+                InvalidData,
+                format!("Expected comparison at position {}", parser.position()),
+            )),
+        },
+    };
+}
+
+fn parse_operand(parser: &mut StringParser) -> Result<Operand, io::Error> {
+    skip_whitespace(parser);
+
+    if parser.peek().ok() == Some('\'') {
+        parser.next()?;
+        let literal = parser.until('\'')?;
+        return Ok(Operand::Literal(literal));
+    }
+
+    let tag = read_word(parser);
+    if tag.is_empty() || !tag.starts_with(|c: char| c.is_ascii_digit()) {
+        return Err(io::Error::new(
+            InvalidData,
+            format!("Expected field tag at position {}", parser.position()),
+        ));
+    }
+
+    if parser.peek().ok() != Some('[') {
+        return Ok(Operand::Field { tag, range: None });
+    }
+
+    parser.next()?;
+    let start = read_number(parser)?;
+    expect(parser, '.')?;
+    expect(parser, '.')?;
+    let end = read_number(parser)?;
+    expect(parser, ']')?;
+
+    return Ok(Operand::Field {
+        tag,
+        range: Some((start, end)),
+    });
+}
+
+fn next_keyword_is(parser: &mut StringParser, keyword: &str) -> bool {
+    skip_whitespace(parser);
+    let position = parser.position();
+    if read_word(parser).eq_ignore_ascii_case(keyword) {
+        return true;
+    }
+
+    parser.set_position(position);
+    return false;
+}
+
+fn read_word(parser: &mut StringParser) -> String {
+    let mut word = String::new();
+    while let Ok(c) = parser.peek() {
+        if !c.is_ascii_alphanumeric() {
+            break;
+        }
+
+        word.push(c);
+        parser.set_position(parser.position() + 1);
+    }
+
+    return word;
+}
+
+fn read_number(parser: &mut StringParser) -> Result<usize, io::Error> {
+    let position = parser.position();
+    return read_word(parser).parse::<usize>().map_err(|e| {
+        io::Error::new(
+            InvalidData,
+            format!("Expected number at position {}: {}", position, e),
+        )
+    });
+}
+
+fn expect(parser: &mut StringParser, expected: char) -> Result<(), io::Error> {
+    skip_whitespace(parser);
+    let position = parser.position();
+    return match parser.next() {
+        Ok(c) if c == expected => Ok(()),
+        _ => Err(io::Error::new(
+            InvalidData,
+            format!("Expected {} at position {}", expected, position),
+        )),
+    };
+}
+
+fn skip_whitespace(parser: &mut StringParser) {
+    while parser.peek().is_ok_and(|c| c.is_whitespace()) {
+        parser.set_position(parser.position() + 1);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::swift::mt::rules::{Condition, Operand, RuleSet};
+    use crate::swift::mt::SwiftMtParser;
+
+    fn parse_message(text: &str) -> crate::swift::mt::SwiftMtMessage {
+        let msg = format!(
+            "{{1:F01FOOBARXXAXXX0000000000}}{{2:I103FOOBARXXAXXXN}}{{4:\r\n{}-}}",
+            text
+        );
+        return SwiftMtParser::new().parse(msg).unwrap();
+    }
+
+    #[test]
+    fn positive_tests() {
+        assert_eq!(
+            Condition::parse("33B AND 33B[0..3] != 32A[6..9]").unwrap(),
+            Condition::And(
+                Box::new(Condition::Present("33B".into())),
+                Box::new(Condition::NotEquals(
+                    Operand::Field {
+                        tag: "33B".into(),
+                        range: Some((0, 3))
+                    },
+                    Operand::Field {
+                        tag: "32A".into(),
+                        range: Some((6, 9))
+                    }
+                ))
+            )
+        );
+        assert_eq!(
+            Condition::parse("not (71A = 'OUR' or 71G)").unwrap(),
+            Condition::Not(Box::new(Condition::Or(
+                Box::new(Condition::Equals(
+                    Operand::Field {
+                        tag: "71A".into(),
+                        range: None
+                    },
+                    Operand::Literal("OUR".into())
+                )),
+                Box::new(Condition::Present("71G".into()))
+            )))
+        );
+
+        let rules = RuleSet::network_rules();
+
+        let valid = parse_message(
+            ":20:REF\r\n:32A:230102EUR1000,\r\n:33B:USD1100,\r\n:36:0,9090909\r\n:71A:SHA\r\n",
+        );
+        assert!(rules.evaluate(&valid).is_empty());
+
+        let same_currency = parse_message(
+            ":20:REF\r\n:32A:230102EUR1000,\r\n:33B:EUR1000,\r\n:71A:OUR\r\n:71G:EUR5,\r\n",
+        );
+        assert!(rules.evaluate(&same_currency).is_empty());
+
+        let rules = RuleSet::from_catalogue("C9|E01|103STP|NOT 57D|71A").unwrap();
+        assert!(rules.evaluate(&parse_message(":20:REF\r\n")).is_empty());
+        let stp = SwiftMtParser::new()
+            .parse(
+                "{1:F01FOOBARXXAXXX0000000000}{2:I103FOOBARXXAXXXN}{3:{119:STP}}{4:\r\n:20:REF\r\n-}"
+                    .to_string(),
+            )
+            .unwrap();
+        assert_eq!(rules.evaluate(&stp)[0].error_code, "E01");
+        assert_eq!(RuleSet::network_rules().evaluate(&stp).len(), 0);
+    }
+
+    #[test]
+    fn negative_tests() {
+        let rules = RuleSet::network_rules();
+
+        let missing_rate = parse_message(
+            ":20:REF\r\n:32A:230102EUR1000,\r\n:33B:USD1100,\r\n:71A:SHA\r\n:71G:EUR5,\r\n",
+        );
+        let violations = rules.evaluate(&missing_rate);
+        assert_eq!(violations.len(), 2);
+        assert_eq!(violations[0].rule_id, "C1");
+        assert_eq!(violations[0].error_code, "D75");
+        assert_eq!(violations[1].rule_id, "C6");
+        assert_eq!(violations[1].error_code, "D50");
+
+        let our_with_charges =
+            parse_message(":20:REF\r\n:32A:230102EUR1000,\r\n:71A:OUR\r\n:71F:EUR5,\r\n");
+        let violations = rules.evaluate(&our_with_charges);
+        assert_eq!(violations.len(), 1);
+        assert_eq!(violations[0].error_code, "E13");
+
+        assert!(Condition::parse("33B AND").is_err());
+        assert!(Condition::parse("33B[0..3]").is_err());
+        assert!(Condition::parse("71A = 'OUR' )").is_err());
+        assert!(RuleSet::from_catalogue("C1|D75|103|33B").is_err());
+    }
+}
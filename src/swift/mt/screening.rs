@@ -0,0 +1,328 @@
+use crate::swift::bic::{Bic, LogicalTerminalAddress};
+use crate::swift::country::is_valid_country_code;
+use crate::swift::iban::Iban;
+use crate::swift::mt::fields::{account_line, PartyField};
+use crate::swift::mt::header_fields::ScreeningInformation;
+use crate::swift::mt::model::ApplicationHeader;
+use crate::swift::mt::sequences::sequence_paths;
+use crate::swift::mt::swift_mt_parser::SwiftMtMessage;
+use crate::swift::mt::text::Field;
+use crate::swift::screening::{normalize, EntityKind, Screenable, ScreeningEntity};
+use std::collections::HashMap;
+use std::io;
+
+/// Narrative fields screened line by line
+static FREE_TEXT_TAGS: [&str; 8] = ["70", "72", "75", "76", "77A", "77B", "79", "86"];
+
+impl Screenable for SwiftMtMessage {
+    /// Entities of the sender and receiver address of the headers, the party and institution
+    /// fields 50a to 59a and the narrative fields. The path of an entity is the `MessagePath`
+    /// of its header element or field, e.g. `B4.SeqB[2].59F[1]`.
+    fn screening_entities(&self) -> Vec<ScreeningEntity> {
+        let mut entities = Vec::new();
+        address_entities(&mut entities, "B1.BIC", &self.basic_header.logical_terminal);
+        match &self.application_header {
+            ApplicationHeader::Input { data } => {
+                address_entities(&mut entities, "B2.ADDRESS", &data.destination)
+            }
+            ApplicationHeader::Output { data } => {
+                address_entities(&mut entities, "B2.ADDRESS", &data.sender_address)
+            }
+            ApplicationHeader::Empty => {}
+        }
+
+        let message_type = self.application_header.message_type().unwrap_or("");
+        let mut in_text: HashMap<&str, usize> = HashMap::new();
+        let mut in_sequence: HashMap<(String, &str), usize> = HashMap::new();
+
+        for (sequence, index) in sequence_paths(message_type, &self.text) {
+            let field = &self.text.fields[index];
+            let text_occurrence = in_text.entry(&field.tag).or_insert(0);
+            *text_occurrence += 1;
+            let sequence_occurrence = in_sequence
+                .entry((sequence.clone(), &field.tag))
+                .or_insert(0);
+            *sequence_occurrence += 1;
+
+            // occurrences of paths without sequences are counted over the whole text
+            let path = if sequence.is_empty() {
+                format!("B4.{}[{}]", field.tag, text_occurrence)
+            } else {
+                let sequences: Vec<String> =
+                    sequence.split('/').map(|s| format!("Seq{}", s)).collect();
+                format!(
+                    "B4.{}.{}[{}]",
+                    sequences.join("."),
+                    field.tag,
+                    sequence_occurrence
+                )
+            };
+
+            let mut field_entities = FieldEntities {
+                field,
+                sequence: &sequence,
+                path,
+                entities: Vec::new(),
+            };
+            if PartyField::is_party_field(&field.tag) {
+                field_entities.read_party();
+            } else if FREE_TEXT_TAGS.contains(&field.tag.as_str()) {
+                for (index, line) in field.lines().into_iter().enumerate() {
+                    field_entities.push(EntityKind::FreeText, line, index);
+                }
+            }
+            entities.append(&mut field_entities.entities);
+        }
+
+        return entities;
+    }
+}
+
+/// Writes the result of screening a received message to field 433 of the user header,
+/// checking that the additional information fits into the field
+pub fn set_screening_information(
+    message: &mut SwiftMtMessage,
+    information: ScreeningInformation,
+) -> Result<(), io::Error> {
+    ScreeningInformation::parse(&information.to_string())?;
+    message.user_header.screening_information_receiver = Some(information);
+    return Ok(());
+}
+
+/// BIC and country of a header address
+fn address_entities(
+    entities: &mut Vec<ScreeningEntity>,
+    path: &str,
+    address: &LogicalTerminalAddress,
+) {
+    if address.is_empty() {
+        return;
+    }
+
+    let bic = address.bic11();
+    let element = path.split('.').next_back().unwrap_or("");
+    entities.extend(ScreeningEntity::new(
+        EntityKind::Bic,
+        bic.as_str(),
+        element,
+        "",
+        path,
+        None,
+    ));
+    entities.extend(ScreeningEntity::new(
+        EntityKind::Country,
+        bic.country_code(),
+        element,
+        "",
+        path,
+        None,
+    ));
+}
+
+/// Entities of one field of the message text
+struct FieldEntities<'a> {
+    field: &'a Field,
+    sequence: &'a str,
+    path: String,
+    entities: Vec<ScreeningEntity>,
+}
+
+impl FieldEntities<'_> {
+    fn push(&mut self, kind: EntityKind, value: &str, index: usize) {
+        self.entities.extend(ScreeningEntity::new(
+            kind,
+            value,
+            &self.field.tag,
+            self.sequence,
+            &self.path,
+            Some(index + 1),
+        ));
+    }
+
+    fn push_country(&mut self, country_code: &str, index: usize) {
+        if is_valid_country_code(country_code) {
+            self.push(EntityKind::Country, country_code, index);
+        }
+    }
+
+    /// Continues the previous name or address of the field
+    fn append(&mut self, value: &str, index: usize) {
+        match self.entities.last_mut() {
+            Some(last) if [EntityKind::Name, EntityKind::Address].contains(&last.kind) => {
+                last.value = normalize(&format!("{} {}", last.value, value))
+            }
+            _ => self.push(EntityKind::FreeText, value, index),
+        }
+    }
+
+    /// Reads the country of an IBAN or party identifier, the BIC of options A, C and G, the
+    /// numbered lines of option F and the name and address lines of the other options
+    fn read_party(&mut self) {
+        let option = self.field.tag.chars().nth(2);
+        let mut position = 0;
+        let mut previous_number = "";
+
+        for (index, line) in self.field.lines().into_iter().enumerate() {
+            if index == 0 && line.starts_with('/') {
+                let account = account_line(self.field).unwrap_or_default();
+                if Iban::looks_like_iban(&account) {
+                    self.push_country(&account[0..2], index);
+                }
+                continue;
+            }
+            if index == 0 && self.field.tag == "50F" {
+                self.push_country(line.split('/').nth(1).unwrap_or(""), index);
+                continue;
+            }
+
+            position += 1;
+            match option {
+                _ if PartyField::has_identifier_code(&self.field.tag) => {
+                    self.push(EntityKind::Bic, line, index);
+                    if let Ok(bic) = Bic::parse(line) {
+                        self.push_country(bic.country_code(), index);
+                    }
+                }
+                Some('F') => {
+                    let (number, content) = line.split_once('/').unwrap_or(("", line));
+                    self.read_numbered_line(number, content, previous_number, index);
+                    previous_number = number;
+                }
+                None | Some('D') | Some('H') | Some('K') if position == 1 => {
+                    self.push(EntityKind::Name, line, index)
+                }
+                None | Some('B') | Some('D') | Some('H') | Some('K') => {
+                    self.push(EntityKind::Address, line, index)
+                }
+                _ => self.push(EntityKind::FreeText, line, index),
+            }
+        }
+    }
+
+    /// Line of option F: name (1), address (2), country and town (3), place of birth (5),
+    /// identification (6, 7) and continuation (8). The date of birth (4) is not screened.
+    fn read_numbered_line(
+        &mut self,
+        number: &str,
+        content: &str,
+        previous_number: &str,
+        index: usize,
+    ) {
+        match number {
+            "1" if previous_number == "1" => self.append(content, index),
+            "1" => self.push(EntityKind::Name, content, index),
+            "2" => self.push(EntityKind::Address, content, index),
+            "3" | "5" => {
+                let (country, town) = content.split_once('/').unwrap_or((content, ""));
+                self.push_country(country, index);
+                self.push(EntityKind::Address, town, index);
+            }
+            "4" => {}
+            "6" | "7" => self.push_country(content.split('/').next().unwrap_or(""), index),
+            "8" => self.append(content, index),
+            _ => self.push(EntityKind::FreeText, content, index),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::swift::mt::screening::set_screening_information;
+    use crate::swift::mt::{
+        MessagePath, ScreeningCodeWord, ScreeningInformation, SwiftMtMessage, SwiftMtParser,
+    };
+    use crate::swift::screening::{EntityKind, Screenable};
+
+    fn parse(message_type: &str, text: &str) -> SwiftMtMessage {
+        let raw = format!(
+            "{{1:F01FOOBARXXAXXX0123000456}}{{2:I{}BANKDEFFXXXXN}}{{4:\r\n{}\r\n-}}",
+            message_type, text
+        );
+        return SwiftMtParser::new().parse(raw).unwrap();
+    }
+
+    #[test]
+    fn positive_tests() {
+        let message = parse(
+            "103",
+            ":20:REF123\r\n:23B:CRED\r\n:32A:230102EUR1000,\r\n:50F:/DE89370400440532013000\r\n1/JOHN\r\n1/SMITH\r\n2/ELM  STREET 5\r\n3/US/new york\r\n4/19800312\r\n:57A:BANKGB2L\r\n:59:/12345678\r\nJANE DOE\r\nOAK ROAD 7\r\n:70:INVOICE 4711\r\n:71A:SHA",
+        );
+        let entities = message.screening_entities();
+        let found: Vec<(EntityKind, &str, &str)> = entities
+            .iter()
+            .map(|e| (e.kind, e.field.as_str(), e.value.as_str()))
+            .collect();
+
+        assert_eq!(
+            found,
+            vec![
+                (EntityKind::Bic, "BIC", "FOOBARXXXXX"),
+                (EntityKind::Country, "BIC", "AR"),
+                (EntityKind::Bic, "ADDRESS", "BANKDEFFXXX"),
+                (EntityKind::Country, "ADDRESS", "DE"),
+                (EntityKind::Country, "50F", "DE"),
+                (EntityKind::Name, "50F", "JOHN SMITH"),
+                (EntityKind::Address, "50F", "ELM STREET 5"),
+                (EntityKind::Country, "50F", "US"),
+                (EntityKind::Address, "50F", "NEW YORK"),
+                (EntityKind::Bic, "57A", "BANKGB2L"),
+                (EntityKind::Country, "57A", "GB"),
+                (EntityKind::Name, "59", "JANE DOE"),
+                (EntityKind::Address, "59", "OAK ROAD 7"),
+                (EntityKind::FreeText, "70", "INVOICE 4711"),
+            ]
+        );
+
+        let name = &entities[5];
+        assert_eq!(name.path, "B4.50F[1]");
+        assert_eq!(name.line, Some(2));
+        assert_eq!(name.sequence, "");
+        assert_eq!(entities[0].path, "B1.BIC");
+        assert_eq!(entities[0].line, None);
+
+        let message = parse(
+            "101",
+            ":20:REF1\r\n:28D:1/1\r\n:30:230102\r\n:21:TX1\r\n:32B:EUR100,\r\n:59:JOHN DOE\r\n:71A:SHA\r\n:21:TX2\r\n:32B:EUR200,\r\n:59:JANE DOE\r\n:71A:SHA",
+        );
+        let entities = message.screening_entities();
+        let jane = entities.iter().find(|e| e.value == "JANE DOE").unwrap();
+        assert_eq!(jane.sequence, "B[2]");
+        assert_eq!(jane.path, "B4.SeqB[2].59[1]");
+        assert_eq!(
+            MessagePath::parse(&jane.path)
+                .unwrap()
+                .get(&message)
+                .unwrap()[0]
+                .to_string(),
+            "JANE DOE"
+        );
+
+        let mut message = message;
+        set_screening_information(
+            &mut message,
+            ScreeningInformation::new(ScreeningCodeWord::Fpo)
+                .with_additional_information("CASE 42"),
+        )
+        .unwrap();
+        assert!(message.to_raw().contains("{433:/FPO/CASE 42}"));
+    }
+
+    #[test]
+    fn negative_tests() {
+        let message = parse("103", ":20:REF123\r\n:50K:\r\n:59:/12345678\r\nNAME");
+        let entities = message.screening_entities();
+        assert!(entities.iter().all(|e| e.field != "50K"));
+        assert!(!entities
+            .iter()
+            .any(|e| e.field == "59" && e.kind == EntityKind::Country));
+
+        let mut message = message;
+        assert!(set_screening_information(
+            &mut message,
+            ScreeningInformation::new(ScreeningCodeWord::Nok)
+                .with_additional_information("MORE THAN TWENTY CHARACTERS"),
+        )
+        .is_err());
+        assert!(message.user_header.screening_information_receiver.is_none());
+    }
+}
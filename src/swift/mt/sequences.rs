@@ -0,0 +1,132 @@
+use crate::swift::mt::text::{tag_matches, MessageText};
+use std::collections::HashMap;
+
+/// Repetitive sequences of message types not using 16R/16S, one sequence per line in the
+/// format `message types|sequence name|member tags`. A sequence starts with its first member
+/// tag, and each further field with that tag starts the next occurrence. The sequence ends at
+/// the first field that is not a member.
+static REPETITIVE_SEQUENCES: &str = "\
+101|B|21,21F,23E,32B,50a,52a,56a,57a,59a,70,77B,33B,71A,25A,36
+102,102STP|B|21,32B,50a,52a,26T,77B,33B,71A,71F,71G,36,57a,59a,70,23E,56a
+104,107|B|21,23E,21C,21D,21E,32B,50a,52a,57a,59a,70,26T,77B,33B,71A,71F,71G,36
+201|B|20,32B,56a,57a,72
+203|B|20,21,32B,56a,57a,58a,72
+940,942,950|Statement line|61,86
+";
+
+/// Pairs the index of each field of the text with the path of the sequence it belongs to,
+/// e.g. `GENL[1]`, `GENL[1]/LINK[2]` or `B[2]` (empty outside of sequences). The 16R and 16S
+/// fields themselves are left out.
+pub(crate) fn sequence_paths(message_type: &str, text: &MessageText) -> Vec<(String, usize)> {
+    let repetitive: Vec<(String, Vec<String>)> = REPETITIVE_SEQUENCES
+        .lines()
+        .filter_map(|line| {
+            let columns: Vec<&str> = line.split('|').collect();
+            let applies = columns[0].split(',').any(|mt| mt == message_type);
+            return applies.then(|| {
+                (
+                    columns[1].to_string(),
+                    columns[2].split(',').map(|t| t.to_string()).collect(),
+                )
+            });
+        })
+        .collect();
+
+    let mut paths = Vec::new();
+    // open 16R blocks with their path
+    let mut blocks: Vec<String> = Vec::new();
+    // current repetitive sequence and its path
+    let mut sequence: Option<(usize, String)> = None;
+    let mut occurrences: HashMap<String, usize> = HashMap::new();
+    let mut next_path = |parent: &str, name: &str| {
+        let key = format!("{}/{}", parent, name);
+        let count = occurrences.entry(key).or_insert(0);
+        *count += 1;
+        let segment = format!("{}[{}]", name, count);
+        return if parent.is_empty() {
+            segment
+        } else {
+            format!("{}/{}", parent, segment)
+        };
+    };
+
+    for (field_index, field) in text.fields.iter().enumerate() {
+        let parent = blocks.last().cloned().unwrap_or_default();
+        if field.tag == "16R" {
+            blocks.push(next_path(&parent, &field.value));
+            continue;
+        }
+        if field.tag == "16S" {
+            blocks.pop();
+            continue;
+        }
+
+        if let Some(index) = sequence.as_ref().map(|(index, _)| *index) {
+            let members = &repetitive[index].1;
+            if !members.iter().any(|m| tag_matches(m, &field.tag)) {
+                sequence = None;
+            } else if tag_matches(&members[0], &field.tag) {
+                sequence = Some((index, next_path(&parent, &repetitive[index].0)));
+            }
+        } else if let Some(index) = repetitive
+            .iter()
+            .position(|(_, members)| tag_matches(&members[0], &field.tag))
+        {
+            sequence = Some((index, next_path(&parent, &repetitive[index].0)));
+        }
+
+        let path = match &sequence {
+            Some((_, path)) => path.clone(),
+            None => parent,
+        };
+        paths.push((path, field_index));
+    }
+
+    return paths;
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::swift::mt::sequences::sequence_paths;
+    use crate::swift::mt::text::MessageText;
+
+    #[test]
+    fn positive_tests() {
+        let text = MessageText::from_raw(
+            ":20:REF\r\n:21:TX1\r\n:32B:EUR1,\r\n:21:TX2\r\n:32B:EUR2,\r\n".to_string(),
+        )
+        .unwrap();
+        assert_eq!(
+            sequence_paths("101", &text),
+            vec![
+                ("".to_string(), 0),
+                ("B[1]".to_string(), 1),
+                ("B[1]".to_string(), 2),
+                ("B[2]".to_string(), 3),
+                ("B[2]".to_string(), 4),
+            ]
+        );
+
+        let text = MessageText::from_raw(
+            ":16R:GENL\r\n:20C::SEME//REF\r\n:16R:LINK\r\n:20C::PREV//A\r\n:16S:LINK\r\n:16S:GENL\r\n:16R:LINK\r\n:16S:LINK\r\n"
+                .to_string(),
+        )
+        .unwrap();
+        assert_eq!(
+            sequence_paths("540", &text),
+            vec![
+                ("GENL[1]".to_string(), 1),
+                ("GENL[1]/LINK[1]".to_string(), 3),
+            ]
+        );
+    }
+
+    #[test]
+    fn negative_tests() {
+        let text =
+            MessageText::from_raw(":20:REF\r\n:21:TX1\r\n:32B:EUR1,\r\n".to_string()).unwrap();
+        assert!(sequence_paths("103", &text)
+            .iter()
+            .all(|(path, _)| path.is_empty()));
+    }
+}
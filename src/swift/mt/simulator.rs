@@ -0,0 +1,314 @@
+use crate::swift::bic::LogicalTerminalAddress;
+use crate::swift::datetime::LocalDateTime;
+use crate::swift::mt::model::{
+    ApplicationHeader, BasicHeader, OutputData, ServiceIdentifier, SystemTrailer, Trailer,
+    UserHeader,
+};
+use crate::swift::mt::swift_mt_parser::{SwiftMtMessage, SwiftMtParser};
+use crate::swift::mt::text::{Field, MessageText};
+use crate::swift::mt::trailer_fields::set_checksum;
+use crate::swift::mt::validator::Validator;
+use chrono::Local;
+use std::collections::HashMap;
+
+/// Error code of NAKs for validation errors without a network error code, e.g. the character
+/// set checks of the crate
+pub static SIMULATOR_VALIDATION_ERROR: &str = "Z00";
+/// Error code of NAKs for messages that cannot be parsed or are not input user messages
+pub static SIMULATOR_FORMAT_ERROR: &str = "Z01";
+
+/// Outcome of a message submitted to the simulator
+#[derive(Debug, Clone, PartialEq)]
+pub struct Submission {
+    /// ACK or NAK (service identifier 21) with the submitted message appended
+    pub acknowledgement: SwiftMtMessage,
+    /// The message as delivered to the receiver, for accepted messages
+    pub output: Option<SwiftMtMessage>,
+}
+
+/// Simulates the FIN interface for integration tests of sending and receiving applications.
+///
+/// Submitted messages get the session number of the session they are sent in and the next
+/// input sequence number (ISN) of the sender's logical terminal, are validated and answered
+/// by an ACK or a NAK. Accepted messages are turned into output messages for the destination
+/// with an output application header (`O`) carrying the MIR of the input message, the next
+/// output sequence number (OSN) of the receiving terminal and a CHK trailer. Each receiving
+/// terminal has an output session of its own, independent of the sessions of the senders,
+/// which starts at 1 and moves on to the next session when the OSN wraps around.
+///
+/// NAKs carry the code of the first validation error in field 405. Errors without a network
+/// error code are reported as `Z00` and messages that cannot be parsed as `Z01`.
+pub struct FinSimulator {
+    validator: Validator,
+    session_number: u32,
+    input_sequence_numbers: HashMap<LogicalTerminalAddress, u32>,
+    output_session_numbers: HashMap<LogicalTerminalAddress, u32>,
+    output_sequence_numbers: HashMap<LogicalTerminalAddress, u32>,
+    date_time: Option<LocalDateTime>,
+}
+
+impl Default for FinSimulator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl FinSimulator {
+    /// Simulator validating with the default `Validator`, using the current local time
+    pub fn new() -> FinSimulator {
+        return FinSimulator {
+            validator: Validator::new(),
+            session_number: 0,
+            input_sequence_numbers: HashMap::new(),
+            output_session_numbers: HashMap::new(),
+            output_sequence_numbers: HashMap::new(),
+            date_time: None,
+        };
+    }
+
+    pub fn with_validator(mut self, validator: Validator) -> FinSimulator {
+        self.validator = validator;
+        return self;
+    }
+
+    /// Uses a fixed date and time for input and output instead of the current time, e.g. for
+    /// reproducible tests
+    pub fn with_date_time(mut self, date_time: LocalDateTime) -> FinSimulator {
+        self.date_time = Some(date_time);
+        return self;
+    }
+
+    /// Opens a new session, e.g. for a new connection, returning its number. Session numbers
+    /// start at 1 and wrap around after 9999.
+    pub fn open_session(&mut self) -> u32 {
+        self.session_number = self.session_number % 9999 + 1;
+        return self.session_number;
+    }
+
+    /// Acknowledges the message in FIN format received in the session
+    pub fn submit(&mut self, session_number: u32, raw: &str) -> Submission {
+        let mut message = match SwiftMtParser::new().parse(raw.to_string()) {
+            Ok(message) => message,
+            Err(_) => {
+                let mut basic_header = BasicHeader::new();
+                basic_header.session_number = session_number;
+                return Submission {
+                    acknowledgement: self.acknowledgement(
+                        &basic_header,
+                        None,
+                        Some(SIMULATOR_FORMAT_ERROR),
+                        None,
+                    ),
+                    output: None,
+                };
+            }
+        };
+
+        let sender = message.basic_header.logical_terminal.clone();
+        let sequence_number = self.input_sequence_numbers.entry(sender).or_insert(0);
+        *sequence_number = *sequence_number % 999999 + 1;
+        message.basic_header.session_number = session_number;
+        message.basic_header.sequence_number = *sequence_number;
+
+        let is_input = matches!(message.application_header, ApplicationHeader::Input { .. })
+            && message.basic_header.service_identifier == ServiceIdentifier::Message;
+        let error_code = if !is_input {
+            Some(SIMULATOR_FORMAT_ERROR.to_string())
+        } else {
+            self.validator.validate(&message).first().map(|e| {
+                if e.code.len() == 3 {
+                    e.code.clone()
+                } else {
+                    SIMULATOR_VALIDATION_ERROR.to_string()
+                }
+            })
+        };
+
+        let output = match &error_code {
+            None => Some(self.output(&message)),
+            Some(_) => None,
+        };
+        let basic_header = message.basic_header.clone();
+        let message_user_reference = message.user_header.message_user_reference.clone();
+        let acknowledgement = self.acknowledgement(
+            &basic_header,
+            message_user_reference.as_deref(),
+            error_code.as_deref(),
+            Some(message),
+        );
+
+        return Submission {
+            acknowledgement,
+            output,
+        };
+    }
+
+    fn acknowledgement(
+        &self,
+        basic_header: &BasicHeader,
+        message_user_reference: Option<&str>,
+        error_code: Option<&str>,
+        original: Option<SwiftMtMessage>,
+    ) -> SwiftMtMessage {
+        let now = self.now();
+        let mut fields = vec![
+            Field::new(
+                "177",
+                &format!("{}{}", now.date().to_yymmdd(), now.time().to_hhmm()),
+            ),
+            Field::new("451", if error_code.is_none() { "0" } else { "1" }),
+        ];
+        if let Some(code) = error_code {
+            fields.push(Field::new("405", code));
+        }
+        if let Some(reference) = message_user_reference {
+            fields.push(Field::new("108", reference));
+        }
+
+        let mut basic_header = basic_header.clone();
+        basic_header.service_identifier = ServiceIdentifier::MessageAck;
+        return SwiftMtMessage {
+            application_header: ApplicationHeader::Empty,
+            basic_header,
+            user_header: UserHeader::new(),
+            text: MessageText { fields },
+            trailer: Trailer::new(),
+            system_trailer: SystemTrailer::new(),
+            original_message: original.map(Box::new),
+        };
+    }
+
+    /// The input message as received by its destination
+    fn output(&mut self, input: &SwiftMtMessage) -> SwiftMtMessage {
+        let ApplicationHeader::Input { data } = &input.application_header else {
+            unreachable!("Only input messages are delivered");
+        };
+
+        let now = self.now();
+        let receiver = data.destination.clone();
+        let session_number = self
+            .output_session_numbers
+            .entry(receiver.clone())
+            .or_insert(1);
+        let sequence_number = self
+            .output_sequence_numbers
+            .entry(receiver.clone())
+            .or_insert(0);
+        if *sequence_number == 999999 {
+            *session_number = *session_number % 9999 + 1;
+        }
+        *sequence_number = *sequence_number % 999999 + 1;
+
+        let mut output = SwiftMtMessage {
+            application_header: ApplicationHeader::Output {
+                data: OutputData {
+                    message_type: data.message_type.clone(),
+                    sender_datetime: now,
+                    sender_address: input.basic_header.logical_terminal.clone(),
+                    session_number: format!("{:04}", input.basic_header.session_number),
+                    sequence_number: format!("{:06}", input.basic_header.sequence_number),
+                    receiver_datetime: now,
+                    message_priority: data.priority.clone(),
+                },
+            },
+            basic_header: BasicHeader {
+                logical_terminal: receiver,
+                session_number: *session_number,
+                sequence_number: *sequence_number,
+                ..BasicHeader::new()
+            },
+            user_header: input.user_header.clone(),
+            text: input.text.clone(),
+            trailer: Trailer::new(),
+            system_trailer: SystemTrailer::new(),
+            original_message: None,
+        };
+
+        set_checksum(&mut output);
+        return output;
+    }
+
+    fn now(&self) -> LocalDateTime {
+        return self
+            .date_time
+            .unwrap_or_else(|| LocalDateTime::new(Local::now().naive_local()));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::swift::datetime::LocalDateTime;
+    use crate::swift::mt::simulator::FinSimulator;
+    use crate::swift::mt::{verify_checksum, Acknowledgement, SwiftMtParser};
+
+    static MESSAGE: &str = "{1:F01FOOBARXXAXXX0000000000}{2:I103BANKDEFFXXXXN}{3:{108:MUR123}}{4:\r\n:20:REF123\r\n:23B:CRED\r\n:32A:230102EUR1000,\r\n:50K:/12345678\r\nJOHN DOE\r\n:59:/DE89370400440532013000\r\nJANE DOE\r\n:71A:SHA\r\n-}";
+
+    fn simulator() -> FinSimulator {
+        return FinSimulator::new()
+            .with_date_time(LocalDateTime::parse_yyyymmddhhmmss("20230102153000").unwrap());
+    }
+
+    #[test]
+    fn positive_tests() {
+        let mut simulator = simulator();
+        let session = simulator.open_session();
+        assert_eq!(session, 1);
+
+        let submission = simulator.submit(session, MESSAGE);
+        let raw = submission.acknowledgement.to_raw();
+        assert!(raw.starts_with("{1:F21FOOBARXXAXXX0001000001}{4:{177:2301021530}{451:0}{108:MUR123}}{1:F01FOOBARXXAXXX0001000001}"));
+
+        let ack = Acknowledgement::from_message(SwiftMtParser::new().parse(raw).unwrap()).unwrap();
+        assert!(ack.is_ack());
+        assert_eq!(ack.mir.to_string(), "230102FOOBARXXAXXX0001000001");
+
+        let output = submission.output.unwrap();
+        assert!(output.to_raw().starts_with(
+            "{1:F01BANKDEFFXXXX0001000001}{2:O1031530230102FOOBARXXAXXX00010000012301021530N}{3:{108:MUR123}}"
+        ));
+        assert_eq!(output.text, ack.original_message.unwrap().text);
+        assert!(verify_checksum(&output).is_ok());
+
+        let submission = simulator.submit(session, MESSAGE);
+        assert_eq!(submission.acknowledgement.basic_header.sequence_number, 2);
+        assert_eq!(submission.output.unwrap().basic_header.sequence_number, 2);
+
+        let session = simulator.open_session();
+        let submission = simulator.submit(session, MESSAGE);
+        assert_eq!(submission.acknowledgement.basic_header.session_number, 2);
+        assert_eq!(submission.acknowledgement.basic_header.sequence_number, 3);
+
+        // the receiver stays in its own output session whatever session the sender uses
+        let output = submission.output.unwrap();
+        assert_eq!(output.basic_header.session_number, 1);
+        assert_eq!(output.basic_header.sequence_number, 3);
+        let other_receiver = MESSAGE.replace("BANKDEFFXXXX", "BANKGB2LXXXX");
+        let output = simulator.submit(session, &other_receiver).output.unwrap();
+        assert_eq!(output.basic_header.logical_terminal, "BANKGB2LXXXX");
+        assert_eq!(output.basic_header.session_number, 1);
+        assert_eq!(output.basic_header.sequence_number, 1);
+    }
+
+    #[test]
+    fn negative_tests() {
+        let mut simulator = simulator();
+        let session = simulator.open_session();
+
+        let submission = simulator.submit(session, &MESSAGE.replace("EUR1000,", "EUR1000,123"));
+        let ack = Acknowledgement::from_message(submission.acknowledgement).unwrap();
+        assert!(ack.is_nak());
+        assert_eq!(ack.error_code.as_deref(), Some("C03"));
+        assert!(submission.output.is_none());
+
+        let submission = simulator.submit(session, &MESSAGE.replace("JANE DOE", "JANE DOE ä"));
+        let ack = Acknowledgement::from_message(submission.acknowledgement).unwrap();
+        assert_eq!(ack.error_code.as_deref(), Some("Z00"));
+
+        let submission = simulator.submit(session, "{1:F01FOOBAR}");
+        assert!(submission.acknowledgement.original_message.is_none());
+        assert!(submission
+            .acknowledgement
+            .to_raw()
+            .contains("{451:1}{405:Z01}"));
+    }
+}
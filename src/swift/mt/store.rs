@@ -0,0 +1,779 @@
+use crate::swift::amount::Amount;
+use crate::swift::bic::{Bic, LogicalTerminalAddress};
+use crate::swift::currency::Currency;
+use crate::swift::datetime::SwiftDate;
+use crate::swift::mt::fields::{AmountField, PartyField};
+use crate::swift::mt::header_fields::Uetr;
+use crate::swift::mt::model::ApplicationHeader;
+use crate::swift::mt::reader::parse;
+use crate::swift::mt::swift_mt_parser::SwiftMtMessage;
+use std::collections::{BTreeSet, HashMap};
+use std::fs::{File, OpenOptions};
+use std::io;
+use std::io::ErrorKind::InvalidData;
+use std::io::{BufRead, BufReader, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+
+/// File of the raw messages, an RJE batch the messages are appended to
+static MESSAGES_FILE: &str = "messages.rje";
+/// File of the metadata, one line per message
+static INDEX_FILE: &str = "index.txt";
+
+/// Related reference of messages that do not relate to another one, not followed in chains
+static NO_REFERENCE: &str = "NONREF";
+
+/// Metadata of a stored message as kept in the index
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct StoredMessage {
+    /// Position of the message in the store, starting at 1
+    pub id: usize,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    offset: u64,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    length: u64,
+    pub message_type: String,
+    pub sender: LogicalTerminalAddress,
+    pub receiver: LogicalTerminalAddress,
+    /// Field 121 of the user header
+    pub uetr: Option<Uetr>,
+    /// Field 108 of the user header
+    pub message_user_reference: Option<String>,
+    /// Field 20
+    pub reference: Option<String>,
+    /// First field 21
+    pub related_reference: Option<String>,
+    /// Date of the first amount field with a date (32A, 32C, 32D), else the requested
+    /// execution date (30)
+    pub value_date: Option<SwiftDate>,
+    /// Currency and amount of the first amount field
+    pub currency: Option<Currency>,
+    pub amount: Option<Amount>,
+    /// BICs of the sender, the receiver and the party fields, with 11 characters
+    pub bics: Vec<Bic>,
+}
+
+/// Criteria of a search in the store, all of which must hold
+#[derive(Debug, Clone, Default)]
+pub struct MessageQuery {
+    uetr: Option<String>,
+    message_user_reference: Option<String>,
+    reference: Option<String>,
+    related_reference: Option<String>,
+    bic: Option<String>,
+    message_type: Option<String>,
+    value_date_from: Option<SwiftDate>,
+    value_date_until: Option<SwiftDate>,
+    currency: Option<Currency>,
+    min_amount: Option<Amount>,
+    max_amount: Option<Amount>,
+}
+
+/// Local store of messages for investigations. Messages are appended to an RJE file and their
+/// metadata to an index file, one line per message, so the store survives restarts and the
+/// raw messages stay readable with any tool. The UETR, MUR, references and BICs are indexed
+/// in memory when the store is opened.
+pub struct MessageStore {
+    directory: PathBuf,
+    messages: File,
+    index: File,
+    records: Vec<StoredMessage>,
+    by_uetr: HashMap<String, Vec<usize>>,
+    by_message_user_reference: HashMap<String, Vec<usize>>,
+    by_reference: HashMap<String, Vec<usize>>,
+    by_related_reference: HashMap<String, Vec<usize>>,
+    /// Keyed by the first 8 characters of the BIC
+    by_bic: HashMap<String, Vec<usize>>,
+}
+
+impl StoredMessage {
+    fn new(id: usize, offset: u64, length: u64, message: &SwiftMtMessage) -> StoredMessage {
+        let (sender, receiver) = match &message.application_header {
+            ApplicationHeader::Output { data } => (
+                data.sender_address.clone(),
+                message.basic_header.logical_terminal.clone(),
+            ),
+            ApplicationHeader::Input { data } => (
+                message.basic_header.logical_terminal.clone(),
+                data.destination.clone(),
+            ),
+            ApplicationHeader::Empty => (
+                message.basic_header.logical_terminal.clone(),
+                LogicalTerminalAddress::default(),
+            ),
+        };
+
+        let amounts: Vec<AmountField> = message
+            .text
+            .fields
+            .iter()
+            .filter(|f| AmountField::is_amount_field(&f.tag))
+            .filter_map(|f| AmountField::from_field(f).ok())
+            .collect();
+        let value_date = amounts.iter().find_map(|a| a.value_date).or_else(|| {
+            let field = message.text.field("30")?;
+            return SwiftDate::parse_yymmdd(&field.value).ok();
+        });
+
+        let mut bics = Vec::new();
+        for address in [&sender, &receiver] {
+            if !address.is_empty() {
+                bics.push(address.bic11());
+            }
+        }
+        for field in &message.text.fields {
+            if !PartyField::is_party_field(&field.tag) {
+                continue;
+            }
+            if let Some(bic) = PartyField::from_field(field).ok().and_then(|p| p.bic) {
+                let bic = bic11(&bic);
+                if !bics.contains(&bic) {
+                    bics.push(bic);
+                }
+            }
+        }
+
+        let value = |tag: &str| message.text.field(tag).map(|f| f.value.trim().to_string());
+        return StoredMessage {
+            id,
+            offset,
+            length,
+            message_type: message
+                .application_header
+                .message_type()
+                .unwrap_or("")
+                .to_string(),
+            sender,
+            receiver,
+            uetr: message.user_header.uetr.clone(),
+            message_user_reference: message.user_header.message_user_reference.clone(),
+            reference: value("20"),
+            related_reference: value("21"),
+            value_date,
+            currency: amounts.first().map(|a| a.currency),
+            amount: amounts.first().map(|a| a.amount),
+            bics,
+        };
+    }
+
+    /// Reads a line of the index in the format
+    /// `id|offset|length|type|sender|receiver|uetr|mur|20|21|value date|currency|amount|bics`
+    /// with the BICs separated by commas and empty columns for missing values. `%`, `|` and
+    /// line breaks in the type and the references are percent-encoded.
+    fn parse(line: &str) -> Result<StoredMessage, io::Error> {
+        let columns: Vec<&str> = line.split('|').collect();
+        if columns.len() != 14 {
+            return Err(io::Error::new(
+                InvalidData,
+                format!("Index line has {} instead of 14 columns", columns.len()),
+            ));
+        }
+
+        let number = |value: &str| {
+            value.parse::<u64>().map_err(|e| {
+                io::Error::new(InvalidData, format!("Invalid number {}: {}", value, e))
+            })
+        };
+        let text = |value: &str| (!value.is_empty()).then(|| unescape(value)).transpose();
+        let address = |value: &str| match value.trim() {
+            "" => Ok(LogicalTerminalAddress::default()),
+            _ => LogicalTerminalAddress::parse(value),
+        };
+
+        return Ok(StoredMessage {
+            id: number(columns[0])? as usize,
+            offset: number(columns[1])?,
+            length: number(columns[2])?,
+            message_type: unescape(columns[3])?,
+            sender: address(columns[4])?,
+            receiver: address(columns[5])?,
+            uetr: text(columns[6])?.map(|u| Uetr::parse(&u)).transpose()?,
+            message_user_reference: text(columns[7])?,
+            reference: text(columns[8])?,
+            related_reference: text(columns[9])?,
+            value_date: text(columns[10])?.map(|d| d.parse()).transpose()?,
+            currency: text(columns[11])?.map(|c| c.parse()).transpose()?,
+            amount: text(columns[12])?.map(|a| Amount::parse(&a)).transpose()?,
+            bics: columns[13]
+                .split(',')
+                .filter(|b| !b.is_empty())
+                .map(Bic::parse)
+                .collect::<Result<_, _>>()?,
+        });
+    }
+
+    fn to_index_line(&self) -> String {
+        let text = |value: &Option<String>| escape(value.as_deref().unwrap_or(""));
+        let address = |value: &LogicalTerminalAddress| match value.is_empty() {
+            true => String::new(),
+            false => value.to_string(),
+        };
+        let bics: Vec<&str> = self.bics.iter().map(|b| b.as_str()).collect();
+        return format!(
+            "{}|{}|{}|{}|{}|{}|{}|{}|{}|{}|{}|{}|{}|{}",
+            self.id,
+            self.offset,
+            self.length,
+            escape(&self.message_type),
+            address(&self.sender),
+            address(&self.receiver),
+            self.uetr.as_ref().map(|u| u.as_str()).unwrap_or(""),
+            text(&self.message_user_reference),
+            text(&self.reference),
+            text(&self.related_reference),
+            self.value_date.map(|d| d.to_string()).unwrap_or_default(),
+            self.currency.map(|c| c.code()).unwrap_or(""),
+            self.amount.map(|a| a.to_string()).unwrap_or_default(),
+            bics.join(",")
+        );
+    }
+}
+
+impl MessageQuery {
+    pub fn new() -> MessageQuery {
+        return MessageQuery::default();
+    }
+
+    pub fn with_uetr(mut self, uetr: &str) -> MessageQuery {
+        self.uetr = Some(uetr.to_lowercase());
+        return self;
+    }
+
+    pub fn with_message_user_reference(mut self, reference: &str) -> MessageQuery {
+        self.message_user_reference = Some(reference.to_string());
+        return self;
+    }
+
+    pub fn with_reference(mut self, reference: &str) -> MessageQuery {
+        self.reference = Some(reference.to_string());
+        return self;
+    }
+
+    pub fn with_related_reference(mut self, reference: &str) -> MessageQuery {
+        self.related_reference = Some(reference.to_string());
+        return self;
+    }
+
+    /// Messages naming the BIC in a header or party field. A BIC with 8 characters matches
+    /// all branches.
+    pub fn with_bic(mut self, bic: &str) -> MessageQuery {
+        self.bic = Some(bic.to_uppercase());
+        return self;
+    }
+
+    pub fn with_message_type(mut self, message_type: &str) -> MessageQuery {
+        self.message_type = Some(message_type.to_string());
+        return self;
+    }
+
+    /// Messages with a value date on or after the date
+    pub fn with_value_date_from(mut self, date: SwiftDate) -> MessageQuery {
+        self.value_date_from = Some(date);
+        return self;
+    }
+
+    /// Messages with a value date on or before the date
+    pub fn with_value_date_until(mut self, date: SwiftDate) -> MessageQuery {
+        self.value_date_until = Some(date);
+        return self;
+    }
+
+    pub fn with_currency(mut self, currency: Currency) -> MessageQuery {
+        self.currency = Some(currency);
+        return self;
+    }
+
+    pub fn with_min_amount(mut self, amount: Amount) -> MessageQuery {
+        self.min_amount = Some(amount);
+        return self;
+    }
+
+    pub fn with_max_amount(mut self, amount: Amount) -> MessageQuery {
+        self.max_amount = Some(amount);
+        return self;
+    }
+
+    pub fn matches(&self, message: &StoredMessage) -> bool {
+        let equals = |criterion: &Option<String>, value: &Option<String>| {
+            criterion.is_none() || *criterion == *value
+        };
+        let uetr = message.uetr.as_ref().map(|u| u.to_string());
+        let bic = self.bic.as_ref().is_none_or(|bic| {
+            message.bics.iter().any(|b| match bic.len() {
+                8 => b.as_str().starts_with(bic.as_str()),
+                _ => b.as_str() == bic11_str(bic),
+            })
+        });
+        let value_date = |bound: &Option<SwiftDate>, accept: fn(&SwiftDate, &SwiftDate) -> bool| {
+            bound.as_ref().is_none_or(|bound| {
+                message
+                    .value_date
+                    .as_ref()
+                    .is_some_and(|d| accept(d, bound))
+            })
+        };
+        let amount = |bound: &Option<Amount>, accept: fn(&Amount, &Amount) -> bool| {
+            bound
+                .as_ref()
+                .is_none_or(|bound| message.amount.as_ref().is_some_and(|a| accept(a, bound)))
+        };
+
+        return equals(&self.uetr, &uetr)
+            && equals(
+                &self.message_user_reference,
+                &message.message_user_reference,
+            )
+            && equals(&self.reference, &message.reference)
+            && equals(&self.related_reference, &message.related_reference)
+            && self
+                .message_type
+                .as_ref()
+                .is_none_or(|t| *t == message.message_type)
+            && self.currency.is_none_or(|c| message.currency == Some(c))
+            && bic
+            && value_date(&self.value_date_from, |d, bound| d >= bound)
+            && value_date(&self.value_date_until, |d, bound| d <= bound)
+            && amount(&self.min_amount, |a, bound| a >= bound)
+            && amount(&self.max_amount, |a, bound| a <= bound);
+    }
+}
+
+impl MessageStore {
+    /// Opens the store in the directory, creating it if it does not exist, and loads the index
+    pub fn open(directory: &Path) -> Result<MessageStore, io::Error> {
+        std::fs::create_dir_all(directory)?;
+        let index_path = directory.join(INDEX_FILE);
+        let mut records = Vec::new();
+        if index_path.exists() {
+            for (number, line) in BufReader::new(File::open(&index_path)?).lines().enumerate() {
+                let line = line?;
+                if line.trim().is_empty() {
+                    continue;
+                }
+                records.push(StoredMessage::parse(line.trim_end()).map_err(|e| {
+                    io::Error::new(
+                        InvalidData,
+                        format!("Line {} of {}: {}", number + 1, index_path.display(), e),
+                    )
+                })?);
+            }
+        }
+
+        let mut store = MessageStore {
+            directory: directory.to_path_buf(),
+            messages: OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(directory.join(MESSAGES_FILE))?,
+            index: OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(&index_path)?,
+            records: Vec::new(),
+            by_uetr: HashMap::new(),
+            by_message_user_reference: HashMap::new(),
+            by_reference: HashMap::new(),
+            by_related_reference: HashMap::new(),
+            by_bic: HashMap::new(),
+        };
+        for record in records {
+            store.insert(record);
+        }
+
+        return Ok(store);
+    }
+
+    pub fn len(&self) -> usize {
+        return self.records.len();
+    }
+
+    pub fn is_empty(&self) -> bool {
+        return self.records.is_empty();
+    }
+
+    /// Stores the message, returning its metadata
+    pub fn add(&mut self, message: &SwiftMtMessage) -> Result<&StoredMessage, io::Error> {
+        let raw = message.to_raw();
+        let offset = self.messages.metadata()?.len();
+        self.messages.write_all(raw.as_bytes())?;
+        self.messages.write_all(b"\r\n$")?;
+        self.messages.flush()?;
+
+        let record = StoredMessage::new(self.records.len() + 1, offset, raw.len() as u64, message);
+        writeln!(self.index, "{}", record.to_index_line())?;
+        self.index.flush()?;
+
+        self.insert(record);
+        return Ok(&self.records[self.records.len() - 1]);
+    }
+
+    /// Metadata of the message with the id
+    pub fn get(&self, id: usize) -> Option<&StoredMessage> {
+        return id.checked_sub(1).and_then(|i| self.records.get(i));
+    }
+
+    /// The stored message in FIN format
+    pub fn raw(&self, record: &StoredMessage) -> Result<String, io::Error> {
+        let mut file = File::open(self.directory.join(MESSAGES_FILE))?;
+        file.seek(SeekFrom::Start(record.offset))?;
+        let mut raw = vec![0u8; record.length as usize];
+        file.read_exact(&mut raw)?;
+
+        return String::from_utf8(raw).map_err(|e| {
+            io::Error::new(
+                InvalidData,
+                format!("Stored message {} is not valid UTF-8: {}", record.id, e),
+            )
+        });
+    }
+
+    pub fn message(&self, record: &StoredMessage) -> Result<SwiftMtMessage, io::Error> {
+        return parse(self.raw(record)?);
+    }
+
+    /// Metadata of the messages matching the query, in the order they were stored. Criteria on
+    /// the UETR, MUR, references and BIC are looked up in the indexes, the others are checked
+    /// message by message.
+    pub fn query(&self, query: &MessageQuery) -> Vec<&StoredMessage> {
+        let lookups = [
+            (&self.by_uetr, query.uetr.clone()),
+            (
+                &self.by_message_user_reference,
+                query.message_user_reference.clone(),
+            ),
+            (&self.by_reference, query.reference.clone()),
+            (&self.by_related_reference, query.related_reference.clone()),
+            (
+                &self.by_bic,
+                query.bic.as_ref().map(|b| b.chars().take(8).collect()),
+            ),
+        ];
+
+        let candidates: Vec<usize> = lookups
+            .iter()
+            .filter_map(|(index, key)| {
+                key.as_ref()
+                    .map(|k| index.get(k).cloned().unwrap_or_default())
+            })
+            .min_by_key(|positions| positions.len())
+            .unwrap_or_else(|| (0..self.records.len()).collect());
+
+        return candidates
+            .into_iter()
+            .map(|position| &self.records[position])
+            .filter(|record| query.matches(record))
+            .collect();
+    }
+
+    /// The messages linked to the message directly or indirectly, e.g. an MT103, the MT199
+    /// answering it and the MT196 answering that, in the order they were stored. Messages are
+    /// linked if they carry the same UETR, or if the related reference of one is the reference
+    /// of the other and both were exchanged between the same two institutions, so different
+    /// banks using the same reference are not linked. The institutions are compared on their
+    /// BIC8 and not checked if an address is unknown.
+    pub fn chain(&self, id: usize) -> Vec<&StoredMessage> {
+        let mut chain = BTreeSet::new();
+        let mut pending = vec![id];
+
+        while let Some(id) = pending.pop() {
+            let Some(record) = self.get(id) else {
+                continue;
+            };
+            if !chain.insert(id) {
+                continue;
+            }
+
+            let mut linked = Vec::new();
+            if let Some(reference) = &record.reference {
+                linked.extend(
+                    self.lookup(&self.by_related_reference, reference)
+                        .into_iter()
+                        .filter(|p| same_counterparties(&self.records[*p], record)),
+                );
+            }
+            if let Some(related) = record.related_reference.as_deref() {
+                if related != NO_REFERENCE {
+                    linked.extend(
+                        self.lookup(&self.by_reference, related)
+                            .into_iter()
+                            .filter(|p| same_counterparties(record, &self.records[*p])),
+                    );
+                }
+            }
+            if let Some(uetr) = &record.uetr {
+                linked.extend(self.lookup(&self.by_uetr, uetr.as_str()));
+            }
+            pending.extend(linked.iter().map(|p| p + 1).filter(|i| !chain.contains(i)));
+        }
+
+        return chain.into_iter().filter_map(|id| self.get(id)).collect();
+    }
+
+    fn lookup(&self, index: &HashMap<String, Vec<usize>>, key: &str) -> Vec<usize> {
+        return index.get(key).cloned().unwrap_or_default();
+    }
+
+    fn insert(&mut self, record: StoredMessage) {
+        let position = self.records.len();
+        let add = |index: &mut HashMap<String, Vec<usize>>, key: &str| {
+            let positions = index.entry(key.to_string()).or_default();
+            if positions.last() != Some(&position) {
+                positions.push(position);
+            }
+        };
+
+        if let Some(uetr) = &record.uetr {
+            add(&mut self.by_uetr, uetr.as_str());
+        }
+        if let Some(reference) = &record.message_user_reference {
+            add(&mut self.by_message_user_reference, reference);
+        }
+        if let Some(reference) = &record.reference {
+            add(&mut self.by_reference, reference);
+        }
+        if let Some(reference) = &record.related_reference {
+            add(&mut self.by_related_reference, reference);
+        }
+        for bic in &record.bics {
+            add(&mut self.by_bic, &bic.as_str()[0..8]);
+        }
+
+        self.records.push(record);
+    }
+}
+
+/// Whether the messages were exchanged between the same two institutions, in either direction:
+/// an answer goes back to the sender of the original message, a cancellation or a reminder
+/// goes to its receiver again
+fn same_counterparties(first: &StoredMessage, second: &StoredMessage) -> bool {
+    let same = |a: &LogicalTerminalAddress, b: &LogicalTerminalAddress| {
+        a.is_empty() || b.is_empty() || a.bic8() == b.bic8()
+    };
+    return (same(&first.sender, &second.receiver) && same(&first.receiver, &second.sender))
+        || (same(&first.sender, &second.sender) && same(&first.receiver, &second.receiver));
+}
+
+/// The BIC with the branch code XXX of the head office if it has none
+fn bic11(bic: &Bic) -> Bic {
+    return Bic::parse(&bic11_str(bic.as_str())).unwrap_or_else(|_| bic.clone());
+}
+
+/// Percent-encodes the characters that would break the index line
+fn escape(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '%' | '|' | '\r' | '\n' => escaped.push_str(&format!("%{:02X}", c as u32)),
+            _ => escaped.push(c),
+        }
+    }
+
+    return escaped;
+}
+
+fn unescape(value: &str) -> Result<String, io::Error> {
+    let mut unescaped = String::with_capacity(value.len());
+    let mut rest = value;
+    while let Some(position) = rest.find('%') {
+        unescaped.push_str(&rest[..position]);
+        let code = rest
+            .get(position + 1..position + 3)
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok())
+            .ok_or_else(|| {
+                io::Error::new(
+                    InvalidData,
+                    format!("Invalid escape in index column {}", value),
+                )
+            })?;
+        unescaped.push(code as char);
+        rest = &rest[position + 3..];
+    }
+    unescaped.push_str(rest);
+
+    return Ok(unescaped);
+}
+
+fn bic11_str(bic: &str) -> String {
+    return match bic.len() {
+        8 => format!("{}XXX", bic),
+        _ => bic.to_string(),
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::swift::amount::Amount;
+    use crate::swift::currency::Currency;
+    use crate::swift::datetime::SwiftDate;
+    use crate::swift::mt::store::{MessageQuery, MessageStore};
+    use crate::swift::mt::{SwiftMtMessage, SwiftMtParser};
+    use std::env;
+    use std::fs;
+
+    static UETR: &str = "eb6305c9-1f7f-49de-aed0-16487c27b42d";
+
+    fn parse(message_type: &str, user_header: &str, text: &str) -> SwiftMtMessage {
+        let raw = format!(
+            "{{1:F01FOOBARXXAXXX0123000456}}{{2:I{}BANKDEFFXXXXN}}{{3:{}}}{{4:\r\n{}\r\n-}}",
+            message_type, user_header, text
+        );
+        return SwiftMtParser::new().parse(raw).unwrap();
+    }
+
+    fn messages() -> Vec<SwiftMtMessage> {
+        return vec![
+            parse(
+                "103",
+                &format!("{{108:MUR1}}{{121:{}}}", UETR),
+                ":20:PAY1\r\n:23B:CRED\r\n:32A:230102EUR1000,\r\n:50K:/12345678\r\nJOHN DOE\r\n:57A:BANKGB2L\r\n:59:JANE DOE\r\n:71A:SHA",
+            ),
+            parse("199", "{108:MUR2}", ":20:QUERY1\r\n:21:PAY1\r\n:79:WHERE IS IT"),
+            parse("196", "", ":20:ANSWER1\r\n:21:QUERY1\r\n:76:/2/PAID"),
+            parse(
+                "103",
+                "{108:MUR3}",
+                ":20:PAY2\r\n:23B:CRED\r\n:32A:230105USD250,5\r\n:50K:JOHN DOE\r\n:59:JANE DOE\r\n:71A:OUR",
+            ),
+            parse("199", "", ":20:FREE1\r\n:21:NONREF\r\n:79:HELLO"),
+        ];
+    }
+
+    #[test]
+    fn positive_tests() {
+        let directory = env::temp_dir().join(format!("rustmsg-store-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&directory);
+
+        let mut store = MessageStore::open(&directory).unwrap();
+        for message in messages() {
+            store.add(&message).unwrap();
+        }
+        assert_eq!(store.len(), 5);
+
+        let payment = store.get(1).unwrap();
+        assert_eq!(payment.reference.as_deref(), Some("PAY1"));
+        assert_eq!(payment.currency, Some(Currency::EUR));
+        assert_eq!(payment.value_date.unwrap().to_string(), "2023-01-02");
+        assert_eq!(
+            payment.bics.iter().map(|b| b.as_str()).collect::<Vec<_>>(),
+            vec!["FOOBARXXXXX", "BANKDEFFXXX", "BANKGB2LXXX"]
+        );
+        assert_eq!(store.message(payment).unwrap(), messages()[0]);
+
+        // the index is read back when the store is opened again
+        drop(store);
+        let mut store = MessageStore::open(&directory).unwrap();
+        assert_eq!(store.len(), 5);
+        let found = store.query(&MessageQuery::new().with_uetr(&UETR.to_uppercase()));
+        assert_eq!(found, vec![store.get(1).unwrap()]);
+
+        let ids = |query: MessageQuery| -> Vec<usize> {
+            return store.query(&query).iter().map(|r| r.id).collect();
+        };
+        assert_eq!(
+            ids(MessageQuery::new().with_message_user_reference("MUR2")),
+            vec![2]
+        );
+        assert_eq!(ids(MessageQuery::new().with_reference("PAY2")), vec![4]);
+        assert_eq!(
+            ids(MessageQuery::new().with_related_reference("PAY1")),
+            vec![2]
+        );
+        assert_eq!(ids(MessageQuery::new().with_bic("BANKGB2L")), vec![1]);
+        assert_eq!(ids(MessageQuery::new().with_bic("FOOBARXX")).len(), 5);
+        assert_eq!(
+            ids(MessageQuery::new().with_message_type("199")),
+            vec![2, 5]
+        );
+        assert_eq!(
+            ids(MessageQuery::new()
+                .with_value_date_from(SwiftDate::parse_yymmdd("230103").unwrap())),
+            vec![4]
+        );
+        assert_eq!(
+            ids(MessageQuery::new()
+                .with_min_amount(Amount::parse("250,50").unwrap())
+                .with_max_amount(Amount::parse("999,").unwrap())),
+            vec![4]
+        );
+        assert_eq!(
+            ids(MessageQuery::new()
+                .with_currency(Currency::EUR)
+                .with_bic("BANKDEFF")),
+            vec![1]
+        );
+
+        let chain: Vec<usize> = store.chain(3).iter().map(|r| r.id).collect();
+        assert_eq!(chain, vec![1, 2, 3]);
+        let chain: Vec<usize> = store.chain(5).iter().map(|r| r.id).collect();
+        assert_eq!(chain, vec![5]);
+
+        // other banks using the same reference are not part of the chain
+        let other = SwiftMtParser::new()
+            .parse(
+                "{1:F01OTHRGB2LAXXX0123000456}{2:I199BANKUS33XXXXN}{4:\r\n:20:QUERY9\r\n:21:PAY1\r\n:79:WHERE\r\n-}"
+                    .to_string(),
+            )
+            .unwrap();
+        store.add(&other).unwrap();
+        let chain: Vec<usize> = store.chain(1).iter().map(|r| r.id).collect();
+        assert_eq!(chain, vec![1, 2, 3]);
+        let chain: Vec<usize> = store.chain(6).iter().map(|r| r.id).collect();
+        assert_eq!(chain, vec![6]);
+
+        // service messages without a receiver are stored and read back
+        let ack = SwiftMtParser::new()
+            .parse("{1:F21FOOBARXXAXXX0123000456}{4:{177:2301021530}{451:0}}".to_string())
+            .unwrap();
+        let record = store.add(&ack).unwrap();
+        assert!(record.receiver.is_empty());
+        drop(store);
+        let store = MessageStore::open(&directory).unwrap();
+        assert_eq!(store.len(), 7);
+        assert!(store.get(7).unwrap().receiver.is_empty());
+        assert_eq!(store.message(store.get(7).unwrap()).unwrap(), ack);
+
+        fs::remove_dir_all(&directory).unwrap();
+    }
+
+    #[test]
+    fn negative_tests() {
+        let directory = env::temp_dir().join(format!("rustmsg-store-bad-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&directory);
+        fs::create_dir_all(&directory).unwrap();
+
+        let mut store = MessageStore::open(&directory).unwrap();
+        store.add(&messages()[1]).unwrap();
+        assert!(store
+            .query(&MessageQuery::new().with_reference("PAY9"))
+            .is_empty());
+        assert!(store
+            .query(&MessageQuery::new().with_min_amount(Amount::parse("1,").unwrap()))
+            .is_empty());
+        assert!(store.get(0).is_none());
+        assert!(store.get(2).is_none());
+        assert!(store.chain(7).is_empty());
+
+        // references with the separator of the index or line breaks do not break the store
+        let separators = parse("199", "{108:MUR|1}", ":20:A|B\r\n:21:%7C\r\nX\r\n:79:HELLO");
+        store.add(&separators).unwrap();
+        drop(store);
+        let store = MessageStore::open(&directory).unwrap();
+        assert_eq!(store.len(), 2);
+        let record = store.get(2).unwrap();
+        assert_eq!(record.reference.as_deref(), Some("A|B"));
+        assert_eq!(record.related_reference.as_deref(), Some("%7C\r\nX"));
+        assert_eq!(record.message_user_reference.as_deref(), Some("MUR|1"));
+        assert_eq!(
+            store
+                .query(&MessageQuery::new().with_reference("A|B"))
+                .len(),
+            1
+        );
+        drop(store);
+
+        fs::write(directory.join("index.txt"), "1|0|10|103\n").unwrap();
+        assert!(MessageStore::open(&directory).is_err());
+
+        fs::remove_dir_all(&directory).unwrap();
+    }
+}
@@ -1,4 +1,7 @@
-use crate::swift::mt::model::{ApplicationHeader, BasicHeader, Trailer, UserHeader};
+use crate::swift::mt::model::{
+    ApplicationHeader, BasicHeader, ServiceIdentifier, SystemTrailer, Trailer, UserHeader,
+};
+use crate::swift::mt::text::MessageText;
 use crate::utils::StringParser;
 use std::collections::HashMap;
 use std::io;
@@ -15,11 +18,92 @@ pub struct Block {
     content: String,
 }
 
+/// A parsed FIN message.
+///
+/// With the `serde` feature the message serialises to a JSON object with one member per
+/// block, named like the fields below:
+///
+/// - Structs are objects with their field names as keys, absent optional values are `null`.
+/// - The application header is `{"Input": {"data": {..}}}`, `{"Output": {"data": {..}}}`
+///   or `"Empty"`, the service identifier of the basic header is its variant name, e.g.
+///   `"Message"`.
+/// - Values with a FIN representation are strings in that representation, e.g. BICs and
+///   logical terminals (`"FOOBARXXAXXX"`), amounts (`"1000,"`), UETRs, MIRs, validation
+///   flags (`"STP"`) and screening information (`"/AOK/"`). Dates are ISO 8601
+///   (`"2023-01-02"`) and local date times are `"2023-01-02 11:30:00"` followed by the UTC
+///   offset if it is known (`"+0100"`).
+/// - The message text is `{"fields": [{"tag": "20", "value": "REF123"}, ..]}` with the lines
+///   of multi-line values separated by CrLf.
+///
+/// Deserialising the JSON gives a message equal to the one serialised, which also formats to
+/// the same FIN message.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct SwiftMtMessage {
     pub application_header: ApplicationHeader,
     pub basic_header: BasicHeader,
     pub user_header: UserHeader,
+    pub text: MessageText,
     pub trailer: Trailer,
+    pub system_trailer: SystemTrailer,
+    /// Original message appended to an acknowledgement (service identifier 21)
+    pub original_message: Option<Box<SwiftMtMessage>>,
+}
+
+impl SwiftMtMessage {
+    /// Formats the message in FIN format. The message text of service messages and of system
+    /// messages (MT 0xx) is written in system block format.
+    pub fn to_raw(&self) -> String {
+        let mut ret = String::new();
+        ret.push_str(&self.basic_header.to_raw());
+        ret.push_str(&self.application_header.to_raw());
+
+        if !self.user_header.is_empty() {
+            ret.push_str(&self.user_header.to_raw());
+        }
+
+        if self.is_service_message() || self.is_system_message() {
+            if !self.text.fields.is_empty() {
+                ret.push_str(&self.text.to_system_block());
+            }
+        } else if !self.text.fields.is_empty() || self.application_header.message_type().is_some() {
+            ret.push_str(&self.text.to_raw());
+        }
+
+        if !self.trailer.is_empty() {
+            ret.push_str(&self.trailer.to_raw());
+        }
+
+        if !self.system_trailer.is_empty() {
+            ret.push_str(&self.system_trailer.to_raw());
+        }
+
+        if let Some(original_message) = &self.original_message {
+            ret.push_str(&original_message.to_raw());
+        }
+
+        return ret;
+    }
+
+    /// Whether the message is a service message, e.g. an acknowledgement or a login, rather
+    /// than a user or system message (service identifier 01)
+    pub fn is_service_message(&self) -> bool {
+        return self.basic_header.service_identifier != ServiceIdentifier::Message;
+    }
+
+    /// Whether the message is a system message of category 0, e.g. an MT 010
+    pub fn is_system_message(&self) -> bool {
+        return self
+            .application_header
+            .message_type()
+            .is_some_and(|mt| mt.starts_with('0'));
+    }
+}
+
+impl Default for SwiftMtParser {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl SwiftMtParser {
@@ -28,38 +112,64 @@ impl SwiftMtParser {
     }
 
     pub fn parse(self, msg: String) -> Result<SwiftMtMessage, ParsingError> {
-        let blocks = self.parse_blocks(msg)?;
+        let mut parser = StringParser::new(msg);
+        let blocks = read_blocks(&mut parser)?;
 
         let bh = blocks
             .get(&'1')
-            .map(|block| read_basic_header(block))
+            .map(read_basic_header)
             .unwrap_or_else(|| Ok(BasicHeader::new()))?;
         let ah = blocks
             .get(&'2')
-            .map(|block| read_application_header(block))
+            .map(read_application_header)
             .unwrap_or_else(|| Ok(ApplicationHeader::Empty))?;
         let uh = blocks
             .get(&'3')
-            .map(|block| read_user_header(block))
+            .map(read_user_header)
             .unwrap_or_else(|| Ok(UserHeader::new()))?;
+        let tx = blocks
+            .get(&'4')
+            .map(read_text)
+            .unwrap_or_else(|| Ok(MessageText::new()))?;
         let tr = blocks
             .get(&'5')
-            .map(|block| read_trailer(block))
+            .map(read_trailer)
             .unwrap_or_else(|| Ok(Trailer::new()))?;
+        let st = blocks
+            .get(&'S')
+            .map(read_system_trailer)
+            .unwrap_or_else(|| Ok(SystemTrailer::new()))?;
+
+        let original_message = if !parser.has_more() {
+            None
+        } else if bh.service_identifier == ServiceIdentifier::MessageAck {
+            Some(Box::new(SwiftMtParser::new().parse(parser.rest())?))
+        } else {
+            return Err(ParsingError {
+                message: format!(
+                    "Invalid message format, unexpected content after the end of the message at position {}",
+                    parser.position()
+                ),
+            });
+        };
 
         let ret_msg = SwiftMtMessage {
             application_header: ah,
             basic_header: bh,
             user_header: uh,
+            text: tx,
             trailer: tr,
+            system_trailer: st,
+            original_message,
         };
 
         return Ok(ret_msg);
     }
 
+    #[cfg(test)]
     fn parse_blocks(self, msg: String) -> Result<HashMap<char, Block>, ParsingError> {
-        let parser = StringParser::new(msg);
-        return read_blocks(parser);
+        let mut parser = StringParser::new(msg);
+        return read_blocks(&mut parser);
     }
 }
 
@@ -87,13 +197,33 @@ fn read_user_header(block: &Block) -> Result<UserHeader, ParsingError> {
     });
 }
 
+fn read_text(block: &Block) -> Result<MessageText, ParsingError> {
+    let text = if block.content.starts_with('{') {
+        MessageText::from_system_block(&block.content)
+    } else {
+        MessageText::from_raw(block.content.clone())
+    };
+
+    return text.map_err(|e: io::Error| ParsingError {
+        message: format!("Error reading message text: {:?}", e),
+    });
+}
+
 fn read_trailer(block: &Block) -> Result<Trailer, ParsingError> {
     return Trailer::from_raw(block.content.clone()).map_err(|e: io::Error| ParsingError {
-        message: format!("Error reading user header: {:?}", e),
+        message: format!("Error reading trailer: {:?}", e),
+    });
+}
+
+fn read_system_trailer(block: &Block) -> Result<SystemTrailer, ParsingError> {
+    return SystemTrailer::from_raw(block.content.clone()).map_err(|e: io::Error| ParsingError {
+        message: format!("Error reading system trailer: {:?}", e),
     });
 }
 
-fn read_blocks(mut parser: StringParser) -> Result<HashMap<char, Block>, ParsingError> {
+/// Reads blocks until the end of the content or until a block is repeated, which is the start
+/// of another message, e.g. the original message appended to an acknowledgement
+fn read_blocks(parser: &mut StringParser) -> Result<HashMap<char, Block>, ParsingError> {
     let mut blocks: HashMap<char, Block> = HashMap::new();
 
     loop {
@@ -101,6 +231,8 @@ fn read_blocks(mut parser: StringParser) -> Result<HashMap<char, Block>, Parsing
             break;
         }
 
+        let block_start = parser.position();
+
         let mut start = parser.next();
         if start.as_ref().ok() != Some(&'{') {
             return Err(ParsingError {
@@ -131,13 +263,21 @@ fn read_blocks(mut parser: StringParser) -> Result<HashMap<char, Block>, Parsing
         }
 
         let block_type = start.unwrap();
+        if blocks.contains_key(&block_type) {
+            parser.set_position(block_start);
+            break;
+        }
+
         if !VALID_BLOCKS.contains(&block_type) || block_type == '1' || block_type == '2' {
             let content = parser.until('}').unwrap();
             blocks.insert(block_type, Block { content });
         } else if block_type == '3' || block_type == '5' || block_type == 'S' {
-            blocks.insert(block_type, read_system_block(&mut parser)?);
+            blocks.insert(block_type, read_system_block(parser)?);
+        } else if block_type == '4' && parser.peek().ok() == Some('{') {
+            // service messages such as ACKs carry their fields in system block format
+            blocks.insert(block_type, read_system_block(parser)?);
         } else if block_type == '4' {
-            blocks.insert(block_type, read_message_text(&mut parser)?);
+            blocks.insert(block_type, read_message_text(parser)?);
         }
     }
 
@@ -223,16 +363,18 @@ fn read_system_block(parser: &mut StringParser) -> Result<Block, ParsingError> {
 
 #[cfg(test)]
 mod tests {
-    use chrono::{DateTime, NaiveDate, Utc};
+    use chrono::NaiveDate;
 
+    use crate::swift::datetime::LocalDateTime;
     use crate::swift::mt::{
+        header_fields::ScreeningCodeWord,
         model::{ApplicationHeader, ServiceIdentifier},
         swift_mt_parser::SwiftMtParser,
     };
 
     #[test]
     fn positive_tests_parse() {
-        let mut msg = "{1:F01FOOBARXXAXXX0000000000}{2:I103FOOBARXXAXXXN}{3:{108:themur}{433:field433}}{5:{PDE:pde}{CHK:chk}}";
+        let mut msg = "{1:F01FOOBARXXAXXX0000000000}{2:I103FOOBARXXAXXXN}{3:{108:themur}{423:23010215300012}{433:/AOK/}}{5:{PDE:pde}{CHK:chk}}";
         let mut parser = SwiftMtParser::new();
         let mut message = parser.parse(msg.into()).unwrap();
 
@@ -241,15 +383,28 @@ mod tests {
             "themur"
         );
         assert_eq!(
-            message.user_header.screening_information_receiver.unwrap(),
-            "field433"
+            message
+                .user_header
+                .screening_information_receiver
+                .unwrap()
+                .code_word,
+            ScreeningCodeWord::Aok
         );
-        assert_eq!(message.user_header.service_identifier.is_none(), true);
-        assert_eq!(message.user_header.banking_priority.is_none(), true);
+        assert_eq!(
+            message
+                .user_header
+                .balance_checkpoint_date_time
+                .unwrap()
+                .to_yymmddhhmmss(),
+            "23010215300012"
+        );
+        assert!(message.user_header.service_identifier.is_none());
+        assert!(message.user_header.banking_priority.is_none());
 
         assert_eq!(message.trailer.pde.unwrap(), "pde");
         assert_eq!(message.trailer.chk.unwrap(), "chk");
-        assert_eq!(message.trailer.pdm.is_none(), true);
+        assert!(message.trailer.pdm.is_none());
+        assert!(message.system_trailer.is_empty());
 
         assert_eq!(message.basic_header.application_identifier, "F");
         assert_eq!(
@@ -276,7 +431,7 @@ mod tests {
             }
         }
 
-        msg = "{1:F01FOOBARXXAXXX0000000000}{2:O0511511010606ABLRXXXXGXXX00000130850106141149S}{3:{108:themur}{433:field433}}{5:{PDE:pde}{CHK:chk}}";
+        msg = "{1:F01FOOBARXXAXXX0000000000}{2:O0511511010606ABLRXXXXGXXX00000130850106141149S}{3:{108:themur}{433:/AOK/}}{5:{PDE:pde}{CHK:chk}}";
         parser = SwiftMtParser::new();
         message = parser.parse(msg.into()).unwrap();
 
@@ -285,12 +440,11 @@ mod tests {
                 assert_eq!(data.message_type, "051");
                 assert_eq!(
                     data.sender_datetime,
-                    DateTime::<Utc>::from_utc(
-                        NaiveDate::from_ymd_opt(2001, 06, 06)
+                    LocalDateTime::new(
+                        NaiveDate::from_ymd_opt(2001, 6, 6)
                             .unwrap()
                             .and_hms_opt(15, 11, 0)
-                            .unwrap(),
-                        Utc
+                            .unwrap()
                     )
                 );
                 assert_eq!(data.sender_address, "ABLRXXXXGXXX");
@@ -298,15 +452,15 @@ mod tests {
                 assert_eq!(data.sequence_number, "013085");
                 assert_eq!(
                     data.receiver_datetime,
-                    DateTime::<Utc>::from_utc(
+                    LocalDateTime::new(
                         NaiveDate::from_ymd_opt(2001, 6, 14)
                             .unwrap()
                             .and_hms_opt(11, 49, 0)
-                            .unwrap(),
-                        Utc
+                            .unwrap()
                     )
                 );
                 assert_eq!(data.message_priority, "S");
+                assert_eq!(data.receiver_datetime.to_utc(), None);
             }
             _ => {
                 panic!(
@@ -317,6 +471,58 @@ mod tests {
         }
     }
 
+    #[test]
+    fn positive_tests_system_trailer() {
+        let msg = "{1:F01FOOBARXXAXXX0000000000}{2:I103FOOBARXXAXXXN}{5:{CHK:123456789ABC}}{S:{SAC:}{COP:P}{MAN:}{SPD:}{XYZ:1}}";
+        let message = SwiftMtParser::new().parse(msg.into()).unwrap();
+
+        let system_trailer = message.system_trailer;
+        assert_eq!(system_trailer.sac.as_deref(), Some(""));
+        assert_eq!(system_trailer.cop.as_deref(), Some("P"));
+        assert_eq!(system_trailer.man.as_deref(), Some(""));
+        assert_eq!(system_trailer.spd.as_deref(), Some(""));
+        assert!(system_trailer.chu.is_none());
+        assert_eq!(system_trailer.unk_fields.get("XYZ").unwrap(), "1");
+        assert_eq!(
+            system_trailer.to_raw(),
+            "{S:{SAC:}{COP:P}{MAN:}{SPD:}{XYZ:1}}"
+        );
+        assert_eq!(message.trailer.to_raw(), "{5:{CHK:123456789ABC}}");
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn positive_tests_serde() {
+        let msg = "{1:F01FOOBARXXAXXX0123000456}{2:O1031130230102BANKDEFFAXXX01230004562301021131N}{3:{108:MUR123}{119:STP}{121:eb6305c9-1f7f-49de-aed0-16487c27b42d}{423:23010215300012}{433:/AOK/}}{4:\r\n:20:REF123\r\n:32A:230102EUR1000,\r\n-}{5:{CHK:123456789ABC}{PDE:}}{S:{COP:P}{XYZ:1}}";
+        let message = SwiftMtParser::new().parse(msg.into()).unwrap();
+
+        let json = serde_json::to_value(&message).unwrap();
+        assert_eq!(json["basic_header"]["logical_terminal"], "FOOBARXXAXXX");
+        assert_eq!(
+            json["application_header"]["Output"]["data"]["sender_datetime"],
+            "2023-01-02 11:30:00"
+        );
+        assert_eq!(
+            json["user_header"]["uetr"],
+            "eb6305c9-1f7f-49de-aed0-16487c27b42d"
+        );
+        assert_eq!(json["user_header"]["validation_flag"], "STP");
+        assert_eq!(json["text"]["fields"][1]["tag"], "32A");
+
+        let deserialized: crate::swift::mt::SwiftMtMessage = serde_json::from_value(json).unwrap();
+        assert_eq!(deserialized, message);
+        assert_eq!(deserialized.to_raw(), message.to_raw());
+
+        // without block 1 the logical terminal address is blank
+        let message = SwiftMtParser::new()
+            .parse("{4:\r\n:20:REF123\r\n-}".into())
+            .unwrap();
+        assert!(message.basic_header.logical_terminal.is_empty());
+        let json = serde_json::to_string(&message).unwrap();
+        let deserialized: crate::swift::mt::SwiftMtMessage = serde_json::from_str(&json).unwrap();
+        assert_eq!(deserialized, message);
+    }
+
     #[test]
     fn positive_tests_parse_blocks() {
         let mut msg = "{1:F01FOOBARXXAXXX0000000000}{2:I103FOOBARXXAXXXN}";
@@ -0,0 +1,534 @@
+use crate::swift::bic::LogicalTerminalAddress;
+use crate::swift::datetime::{LocalDateTime, SwiftDate, SwiftTime};
+use crate::swift::mt::header_fields::Mir;
+use crate::swift::mt::model::{ApplicationHeader, BasicHeader, ServiceIdentifier, Trailer};
+use crate::swift::mt::model::{SystemTrailer, UserHeader};
+use crate::swift::mt::swift_mt_parser::SwiftMtMessage;
+use crate::swift::mt::text::{Field, MessageText};
+use std::io;
+use std::io::ErrorKind::InvalidData;
+
+/// Login, select, quit or logout request on the APDU level, identified by the service
+/// identifier of the basic header, which also holds the logical terminal and the session and
+/// sequence numbers. The fields of the body, e.g. the select or login parameters, are kept as
+/// they are.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ServiceRequest {
+    pub service_identifier: ServiceIdentifier,
+    pub fields: Vec<Field>,
+}
+
+/// Acknowledgement of a login, select, quit or logout request on the APDU level, identified
+/// by the service identifier of the basic header
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ServiceAcknowledgement {
+    pub service_identifier: ServiceIdentifier,
+    /// Date and time of field 177, in the local time of the sender
+    pub date_time: Option<LocalDateTime>,
+    /// Reason code of field 503 of a negative acknowledgement
+    pub error_code: Option<String>,
+}
+
+/// Body of the MT 010 (non-delivery warning) and MT 015 (delayed message trigger), which
+/// report a message that could not be delivered yet
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct UndeliveredMessage {
+    /// 106
+    pub mir: Mir,
+    /// 108
+    pub message_user_reference: Option<String>,
+    /// 431
+    pub message_status: String,
+    /// 102
+    pub receiver: LogicalTerminalAddress,
+    /// 104
+    pub priority: String,
+}
+
+/// Body of the MT 011 (delivery notification)
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DeliveryNotification {
+    /// First 175, input time of the message
+    pub input_time: SwiftTime,
+    /// 106
+    pub mir: Mir,
+    /// 108
+    pub message_user_reference: Option<String>,
+    /// Second 175, output time of the message
+    pub output_time: SwiftTime,
+    /// 107, output reference of the message at the receiver
+    pub mor: Mir,
+}
+
+/// Body of the MT 012 (sender notification) of FINCopy services
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SenderNotification {
+    /// 175
+    pub input_time: SwiftTime,
+    /// 106
+    pub mir: Mir,
+    /// 108
+    pub message_user_reference: Option<String>,
+    /// 102
+    pub receiver: LogicalTerminalAddress,
+    /// 103
+    pub service_code: String,
+    /// 114
+    pub payment_release_information: Option<String>,
+}
+
+/// Body of the MT 019 (abort notification)
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct AbortNotification {
+    /// 175
+    pub input_time: SwiftTime,
+    /// 106
+    pub mir: Mir,
+    /// 108
+    pub message_user_reference: Option<String>,
+    /// 431
+    pub message_status: String,
+    /// 102
+    pub receiver: LogicalTerminalAddress,
+    /// 104
+    pub priority: String,
+    /// 432
+    pub abort_reason: String,
+}
+
+/// Body of the MT 082 (undelivered message report at a fixed hour)
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct UndeliveredMessageReport {
+    /// 202
+    pub section_number: u32,
+    /// 203
+    pub total_sections: u32,
+    /// 171
+    pub report_date: SwiftDate,
+    /// 175
+    pub report_time: SwiftTime,
+    /// All 106 fields
+    pub messages: Vec<Mir>,
+}
+
+/// Service message or system message (category 0) with a typed body
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum SystemMessage {
+    ServiceRequest(ServiceRequest),
+    ServiceAck(ServiceAcknowledgement),
+    NonDeliveryWarning(UndeliveredMessage),
+    DeliveryNotification(DeliveryNotification),
+    SenderNotification(SenderNotification),
+    DelayedMessageTrigger(UndeliveredMessage),
+    AbortNotification(AbortNotification),
+    UndeliveredMessageReport(UndeliveredMessageReport),
+}
+
+impl SystemMessage {
+    /// Reads the body of a login, select, quit or logout request or acknowledgement or of one
+    /// of the supported system messages (MT 010, 011, 012, 015, 019 and 082)
+    pub fn from_message(message: &SwiftMtMessage) -> Result<SystemMessage, io::Error> {
+        let service_identifier = message.basic_header.service_identifier;
+        if is_service_request(service_identifier) {
+            return Ok(SystemMessage::ServiceRequest(ServiceRequest {
+                service_identifier,
+                fields: message.text.fields.clone(),
+            }));
+        }
+        if is_service_acknowledgement(service_identifier) {
+            return Ok(SystemMessage::ServiceAck(ServiceAcknowledgement {
+                service_identifier,
+                date_time: optional(&message.text, "177")
+                    .map(parse_date_time)
+                    .transpose()?,
+                error_code: optional(&message.text, "503").map(|v| v.to_string()),
+            }));
+        }
+
+        let text = &message.text;
+        return match message.application_header.message_type() {
+            Some("010") => Ok(SystemMessage::NonDeliveryWarning(
+                UndeliveredMessage::from_text(text)?,
+            )),
+            Some("011") => Ok(SystemMessage::DeliveryNotification(DeliveryNotification {
+                input_time: SwiftTime::parse_hhmm(required(text, "175")?)?,
+                mir: Mir::parse(required(text, "106")?)?,
+                message_user_reference: optional(text, "108").map(|v| v.to_string()),
+                output_time: SwiftTime::parse_hhmm(
+                    text.fields_with_tag("175")
+                        .nth(1)
+                        .map(|f| f.value.as_str())
+                        .ok_or_else(|| missing("175"))?,
+                )?,
+                mor: Mir::parse(required(text, "107")?)?,
+            })),
+            Some("012") => Ok(SystemMessage::SenderNotification(SenderNotification {
+                input_time: SwiftTime::parse_hhmm(required(text, "175")?)?,
+                mir: Mir::parse(required(text, "106")?)?,
+                message_user_reference: optional(text, "108").map(|v| v.to_string()),
+                receiver: LogicalTerminalAddress::parse(required(text, "102")?)?,
+                service_code: required(text, "103")?.to_string(),
+                payment_release_information: optional(text, "114").map(|v| v.to_string()),
+            })),
+            Some("015") => Ok(SystemMessage::DelayedMessageTrigger(
+                UndeliveredMessage::from_text(text)?,
+            )),
+            Some("019") => Ok(SystemMessage::AbortNotification(AbortNotification {
+                input_time: SwiftTime::parse_hhmm(required(text, "175")?)?,
+                mir: Mir::parse(required(text, "106")?)?,
+                message_user_reference: optional(text, "108").map(|v| v.to_string()),
+                message_status: required(text, "431")?.to_string(),
+                receiver: LogicalTerminalAddress::parse(required(text, "102")?)?,
+                priority: required(text, "104")?.to_string(),
+                abort_reason: required(text, "432")?.to_string(),
+            })),
+            Some("082") => Ok(SystemMessage::UndeliveredMessageReport(
+                UndeliveredMessageReport {
+                    section_number: parse_number(required(text, "202")?)?,
+                    total_sections: parse_number(required(text, "203")?)?,
+                    report_date: SwiftDate::parse_yymmdd(required(text, "171")?)?,
+                    report_time: SwiftTime::parse_hhmm(required(text, "175")?)?,
+                    messages: text
+                        .fields_with_tag("106")
+                        .map(|f| Mir::parse(&f.value))
+                        .collect::<Result<Vec<Mir>, io::Error>>()?,
+                },
+            )),
+            other => Err(io::Error::new(
+                InvalidData,
+                format!(
+                    "Unsupported system message: service identifier {:?}, message type {:?}",
+                    service_identifier, other
+                ),
+            )),
+        };
+    }
+
+    /// Message type of the system message, `None` for service requests and acknowledgements
+    pub fn message_type(&self) -> Option<&'static str> {
+        return match self {
+            SystemMessage::ServiceRequest(_) | SystemMessage::ServiceAck(_) => None,
+            SystemMessage::NonDeliveryWarning(_) => Some("010"),
+            SystemMessage::DeliveryNotification(_) => Some("011"),
+            SystemMessage::SenderNotification(_) => Some("012"),
+            SystemMessage::DelayedMessageTrigger(_) => Some("015"),
+            SystemMessage::AbortNotification(_) => Some("019"),
+            SystemMessage::UndeliveredMessageReport(_) => Some("082"),
+        };
+    }
+
+    pub fn to_text(&self) -> MessageText {
+        let mut text = MessageText::new();
+        let mut add = |tag: &str, value: Option<String>| {
+            if let Some(value) = value {
+                text.fields.push(Field::new(tag, &value));
+            }
+        };
+
+        match self {
+            SystemMessage::ServiceRequest(request) => {
+                for field in &request.fields {
+                    add(&field.tag, Some(field.value.clone()));
+                }
+            }
+            SystemMessage::ServiceAck(ack) => {
+                add("177", ack.date_time.map(format_date_time));
+                add("503", ack.error_code.clone());
+            }
+            SystemMessage::NonDeliveryWarning(body)
+            | SystemMessage::DelayedMessageTrigger(body) => {
+                add("106", Some(body.mir.to_string()));
+                add("108", body.message_user_reference.clone());
+                add("431", Some(body.message_status.clone()));
+                add("102", Some(body.receiver.to_string()));
+                add("104", Some(body.priority.clone()));
+            }
+            SystemMessage::DeliveryNotification(body) => {
+                add("175", Some(body.input_time.to_hhmm()));
+                add("106", Some(body.mir.to_string()));
+                add("108", body.message_user_reference.clone());
+                add("175", Some(body.output_time.to_hhmm()));
+                add("107", Some(body.mor.to_string()));
+            }
+            SystemMessage::SenderNotification(body) => {
+                add("175", Some(body.input_time.to_hhmm()));
+                add("106", Some(body.mir.to_string()));
+                add("108", body.message_user_reference.clone());
+                add("102", Some(body.receiver.to_string()));
+                add("103", Some(body.service_code.clone()));
+                add("114", body.payment_release_information.clone());
+            }
+            SystemMessage::AbortNotification(body) => {
+                add("175", Some(body.input_time.to_hhmm()));
+                add("106", Some(body.mir.to_string()));
+                add("108", body.message_user_reference.clone());
+                add("431", Some(body.message_status.clone()));
+                add("102", Some(body.receiver.to_string()));
+                add("104", Some(body.priority.clone()));
+                add("432", Some(body.abort_reason.clone()));
+            }
+            SystemMessage::UndeliveredMessageReport(body) => {
+                add("202", Some(format!("{:04}", body.section_number)));
+                add("203", Some(format!("{:04}", body.total_sections)));
+                add("171", Some(body.report_date.to_yymmdd()));
+                add("175", Some(body.report_time.to_hhmm()));
+                for mir in &body.messages {
+                    add("106", Some(mir.to_string()));
+                }
+            }
+        }
+
+        return text;
+    }
+
+    /// Builds the message with the given headers. The service identifier of the basic header
+    /// and the message type of the application header are set according to the body.
+    pub fn to_message(
+        &self,
+        mut basic_header: BasicHeader,
+        mut application_header: ApplicationHeader,
+    ) -> SwiftMtMessage {
+        match self {
+            SystemMessage::ServiceRequest(request) => {
+                basic_header.service_identifier = request.service_identifier;
+                application_header = ApplicationHeader::Empty;
+            }
+            SystemMessage::ServiceAck(ack) => {
+                basic_header.service_identifier = ack.service_identifier;
+                application_header = ApplicationHeader::Empty;
+            }
+            _ => {
+                basic_header.service_identifier = ServiceIdentifier::Message;
+                let message_type = self.message_type().unwrap_or_default().to_string();
+                match &mut application_header {
+                    ApplicationHeader::Input { data } => data.message_type = message_type,
+                    ApplicationHeader::Output { data } => data.message_type = message_type,
+                    ApplicationHeader::Empty => {}
+                }
+            }
+        }
+
+        return SwiftMtMessage {
+            application_header,
+            basic_header,
+            user_header: UserHeader::new(),
+            text: self.to_text(),
+            trailer: Trailer::new(),
+            system_trailer: SystemTrailer::new(),
+            original_message: None,
+        };
+    }
+}
+
+impl UndeliveredMessage {
+    fn from_text(text: &MessageText) -> Result<UndeliveredMessage, io::Error> {
+        return Ok(UndeliveredMessage {
+            mir: Mir::parse(required(text, "106")?)?,
+            message_user_reference: optional(text, "108").map(|v| v.to_string()),
+            message_status: required(text, "431")?.to_string(),
+            receiver: LogicalTerminalAddress::parse(required(text, "102")?)?,
+            priority: required(text, "104")?.to_string(),
+        });
+    }
+}
+
+fn is_service_request(service_identifier: ServiceIdentifier) -> bool {
+    return [
+        ServiceIdentifier::LoginRequest,
+        ServiceIdentifier::Select,
+        ServiceIdentifier::Quit,
+        ServiceIdentifier::Logout,
+    ]
+    .contains(&service_identifier);
+}
+
+fn is_service_acknowledgement(service_identifier: ServiceIdentifier) -> bool {
+    return [
+        ServiceIdentifier::LoginAck,
+        ServiceIdentifier::SelectAck,
+        ServiceIdentifier::QuitAck,
+        ServiceIdentifier::LogoutAck,
+        ServiceIdentifier::LoginNegativeAck,
+        ServiceIdentifier::SelectNegativeAck,
+    ]
+    .contains(&service_identifier);
+}
+
+fn optional<'a>(text: &'a MessageText, tag: &str) -> Option<&'a str> {
+    return text.field(tag).map(|f| f.value.as_str());
+}
+
+fn required<'a>(text: &'a MessageText, tag: &str) -> Result<&'a str, io::Error> {
+    return optional(text, tag).ok_or_else(|| missing(tag));
+}
+
+fn missing(tag: &str) -> io::Error {
+    return io::Error::new(InvalidData, format!("Mandatory field {} is missing", tag));
+}
+
+fn parse_number(value: &str) -> Result<u32, io::Error> {
+    return value
+        .parse::<u32>()
+        .map_err(|e| io::Error::new(InvalidData, format!("Invalid number {}: {}", value, e)));
+}
+
+/// Date and time in the format YYMMDDHHMM of field 177
+fn parse_date_time(value: &str) -> Result<LocalDateTime, io::Error> {
+    if value.len() != 10 || !value.is_ascii() {
+        return Err(io::Error::new(
+            InvalidData,
+            format!("Expected YYMMDDHHMM but got {}", value),
+        ));
+    }
+
+    return Ok(LocalDateTime::from_parts(
+        SwiftDate::parse_yymmdd(&value[0..6])?,
+        SwiftTime::parse_hhmm(&value[6..10])?,
+    ));
+}
+
+fn format_date_time(date_time: LocalDateTime) -> String {
+    return format!(
+        "{}{}",
+        date_time.date().to_yymmdd(),
+        date_time.time().to_hhmm()
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::swift::bic::LogicalTerminalAddress;
+    use crate::swift::mt::model::{ApplicationHeader, BasicHeader, ServiceIdentifier};
+    use crate::swift::mt::system_messages::SystemMessage;
+    use crate::swift::mt::SwiftMtParser;
+
+    #[test]
+    fn positive_tests() {
+        let raw = "{1:F01FOOBARXXAXXX0123000457}{2:O0101200230102DYLRXXXXAXXX00000000012301021200S}{4:{106:230102FOOBARXXAXXX0123000456}{108:MUR123}{431:07}{102:BANKDEFFAXXX}{104:N}}";
+        let message = SwiftMtParser::new().parse(raw.to_string()).unwrap();
+        assert_eq!(message.to_raw(), raw);
+
+        match SystemMessage::from_message(&message).unwrap() {
+            SystemMessage::NonDeliveryWarning(body) => {
+                assert_eq!(body.mir.to_string(), "230102FOOBARXXAXXX0123000456");
+                assert_eq!(body.message_user_reference.as_deref(), Some("MUR123"));
+                assert_eq!(body.message_status, "07");
+                assert_eq!(body.receiver, "BANKDEFFAXXX");
+            }
+            other => panic!("Expected a non-delivery warning but got {:?}", other),
+        }
+
+        let raw = "{1:F01FOOBARXXAXXX0123000458}{2:O0111200230102DYLRXXXXAXXX00000000022301021200S}{4:{175:1130}{106:230102FOOBARXXAXXX0123000456}{175:1145}{107:230102BANKDEFFAXXX0456000789}}";
+        let message = SwiftMtParser::new().parse(raw.to_string()).unwrap();
+        let system_message = SystemMessage::from_message(&message).unwrap();
+        match &system_message {
+            SystemMessage::DeliveryNotification(body) => {
+                assert_eq!(body.input_time.to_hhmm(), "1130");
+                assert_eq!(body.output_time.to_hhmm(), "1145");
+                assert_eq!(body.mor.logical_terminal, "BANKDEFFAXXX");
+            }
+            other => panic!("Expected a delivery notification but got {:?}", other),
+        }
+        let rebuilt = system_message.to_message(message.basic_header, message.application_header);
+        assert_eq!(rebuilt.to_raw(), raw);
+
+        let raw = "{1:F01FOOBARXXAXXX0123000459}{2:O0821200230102DYLRXXXXAXXX00000000032301021200S}{4:{202:0001}{203:0001}{171:230102}{175:1200}{106:230102FOOBARXXAXXX0123000456}{106:230102FOOBARXXAXXX0123000460}}";
+        let message = SwiftMtParser::new().parse(raw.to_string()).unwrap();
+        match SystemMessage::from_message(&message).unwrap() {
+            SystemMessage::UndeliveredMessageReport(body) => {
+                assert_eq!(body.section_number, 1);
+                assert_eq!(body.messages.len(), 2);
+                assert_eq!(body.messages[1].sequence_number, 460);
+            }
+            other => panic!("Expected an undelivered message report but got {:?}", other),
+        }
+
+        let raw = "{1:A42FOOBARXXAXXX0000000000}{4:{177:2301020800}{503:Z02}}";
+        let message = SwiftMtParser::new().parse(raw.to_string()).unwrap();
+        assert_eq!(message.to_raw(), raw);
+        match SystemMessage::from_message(&message).unwrap() {
+            SystemMessage::ServiceAck(ack) => {
+                assert_eq!(ack.service_identifier, ServiceIdentifier::LoginNegativeAck);
+                assert_eq!(ack.error_code.as_deref(), Some("Z02"));
+                assert_eq!(ack.date_time.unwrap().time().to_hhmm(), "0800");
+            }
+            other => panic!("Expected a login NAK but got {:?}", other),
+        }
+
+        let logout_ack = SystemMessage::from_message(
+            &SwiftMtParser::new()
+                .parse("{1:A26FOOBARXXAXXX0000000000}{4:{177:2301021800}}".to_string())
+                .unwrap(),
+        )
+        .unwrap();
+        let mut basic_header = BasicHeader::new();
+        basic_header.application_identifier = "A".to_string();
+        basic_header.logical_terminal = LogicalTerminalAddress::parse("FOOBARXXAXXX").unwrap();
+        assert_eq!(
+            logout_ack
+                .to_message(basic_header, ApplicationHeader::Empty)
+                .to_raw(),
+            "{1:A26FOOBARXXAXXX0000000000}{4:{177:2301021800}}"
+        );
+
+        for (raw, service_identifier) in [
+            (
+                "{1:A02FOOBARXXAXXX0124000001}{4:{177:2301020800}}",
+                ServiceIdentifier::LoginRequest,
+            ),
+            (
+                "{1:A03FOOBARXXAXXX0124000001}{4:{177:2301020801}}",
+                ServiceIdentifier::Select,
+            ),
+            (
+                "{1:A05FOOBARXXAXXX0124000002}{4:{177:2301021759}}",
+                ServiceIdentifier::Quit,
+            ),
+            ("{1:A06FOOBARXXAXXX0124000003}", ServiceIdentifier::Logout),
+        ] {
+            let message = SwiftMtParser::new().parse(raw.to_string()).unwrap();
+            let system_message = SystemMessage::from_message(&message).unwrap();
+            match &system_message {
+                SystemMessage::ServiceRequest(request) => {
+                    assert_eq!(request.service_identifier, service_identifier);
+                }
+                other => panic!("Expected a service request but got {:?}", other),
+            }
+            assert_eq!(system_message.message_type(), None);
+            assert_eq!(
+                system_message
+                    .to_message(message.basic_header, message.application_header)
+                    .to_raw(),
+                raw
+            );
+        }
+    }
+
+    #[test]
+    fn negative_tests() {
+        let missing_status = "{1:F01FOOBARXXAXXX0123000457}{2:O0101200230102DYLRXXXXAXXX00000000012301021200S}{4:{106:230102FOOBARXXAXXX0123000456}{102:BANKDEFFAXXX}{104:N}}";
+        let message = SwiftMtParser::new()
+            .parse(missing_status.to_string())
+            .unwrap();
+        assert!(SystemMessage::from_message(&message).is_err());
+
+        let user_message =
+            "{1:F01FOOBARXXAXXX0123000456}{2:I103BANKDEFFXXXXN}{4:\r\n:20:REF123\r\n-}";
+        let message = SwiftMtParser::new()
+            .parse(user_message.to_string())
+            .unwrap();
+        assert!(SystemMessage::from_message(&message).is_err());
+
+        let invalid_mir = "{1:F01FOOBARXXAXXX0123000457}{2:O0191200230102DYLRXXXXAXXX00000000012301021200S}{4:{175:1130}{106:230102FOOBARXXAXXX01230004}{431:07}{102:BANKDEFFAXXX}{104:N}{432:01}}";
+        let message = SwiftMtParser::new().parse(invalid_mir.to_string()).unwrap();
+        assert!(SystemMessage::from_message(&message).is_err());
+    }
+}
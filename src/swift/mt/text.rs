@@ -0,0 +1,234 @@
+use std::io;
+use std::io::ErrorKind::InvalidData;
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Field {
+    pub tag: String,
+    pub value: String,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MessageText {
+    pub fields: Vec<Field>,
+}
+
+impl Field {
+    pub fn new(tag: &str, value: &str) -> Field {
+        return Field {
+            tag: tag.into(),
+            value: value.into(),
+        };
+    }
+
+    /// Lines of a multi-line field value, e.g. the name and address lines of field 59
+    pub fn lines(&self) -> Vec<&str> {
+        return self.value.split("\r\n").collect();
+    }
+}
+
+impl Default for MessageText {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MessageText {
+    pub fn new() -> MessageText {
+        return MessageText { fields: Vec::new() };
+    }
+
+    /// Reads the content of block 4 (without the leading CrLf and the closing -}) into
+    /// its fields. Lines not starting with :tag: are continuation lines of the previous field.
+    pub fn from_raw(content: String) -> Result<MessageText, io::Error> {
+        let mut fields: Vec<Field> = Vec::new();
+
+        for line in content.split("\r\n") {
+            if let Some((tag, value)) = split_field_start(line) {
+                fields.push(Field::new(tag, value));
+                continue;
+            }
+
+            match fields.last_mut() {
+                Some(field) => {
+                    field.value.push_str("\r\n");
+                    field.value.push_str(line);
+                }
+                None if line.is_empty() => {}
+                None => {
+                    return Err(io::Error::new(
+                        InvalidData,
+                        format!(
+                            "Expected field tag at start of message text but got {}",
+                            line
+                        ),
+                    ))
+                }
+            }
+        }
+
+        // the block content ends with CrLf, which leaves an empty continuation on the last field
+        if let Some(field) = fields.last_mut() {
+            if field.value.ends_with("\r\n") {
+                field.value.truncate(field.value.len() - 2);
+            }
+        }
+
+        return Ok(MessageText { fields });
+    }
+
+    /// Reads a message text in system block format (`{177:2301021530}{451:0}`), as used by
+    /// service messages such as acknowledgements
+    pub fn from_system_block(content: &str) -> Result<MessageText, io::Error> {
+        let mut fields: Vec<Field> = Vec::new();
+
+        for token in content.split('}').filter(|t| !t.trim().is_empty()) {
+            let (tag, value) = token
+                .strip_prefix('{')
+                .and_then(|t| t.split_once(':'))
+                .ok_or_else(|| {
+                    io::Error::new(
+                        InvalidData,
+                        format!("Expected {{tag:value}} in message text but got {}", token),
+                    )
+                })?;
+            fields.push(Field::new(tag, value));
+        }
+
+        return Ok(MessageText { fields });
+    }
+
+    pub fn to_raw(&self) -> String {
+        let mut ret = String::new();
+        ret.push_str("{4:\r\n");
+
+        for field in &self.fields {
+            ret.push_str(&format!(":{}:{}\r\n", field.tag, field.value));
+        }
+
+        ret.push_str("-}");
+        return ret;
+    }
+
+    /// Formats the message text in system block format, see `from_system_block`
+    pub fn to_system_block(&self) -> String {
+        let mut ret = String::new();
+        ret.push_str("{4:");
+
+        for field in &self.fields {
+            ret.push_str(&format!("{{{}:{}}}", field.tag, field.value));
+        }
+
+        ret.push('}');
+        return ret;
+    }
+
+    /// First field matching the tag, see `tag_matches` for the supported patterns
+    pub fn field(&self, tag: &str) -> Option<&Field> {
+        return self.fields.iter().find(|f| tag_matches(tag, &f.tag));
+    }
+
+    pub fn fields_with_tag<'a>(&'a self, tag: &'a str) -> impl Iterator<Item = &'a Field> {
+        return self.fields.iter().filter(move |f| tag_matches(tag, &f.tag));
+    }
+
+    pub fn has_field(&self, tag: &str) -> bool {
+        return self.field(tag).is_some();
+    }
+}
+
+/// Compares a tag against a pattern following the notation of the SWIFT standards: a
+/// lowercase `a` as option letter (e.g. `50a`) stands for the field with any or no option.
+pub fn tag_matches(pattern: &str, tag: &str) -> bool {
+    if let Some(number) = pattern.strip_suffix('a') {
+        return tag.len() >= number.len()
+            && tag.starts_with(number)
+            && tag[number.len()..].chars().all(|c| c.is_ascii_uppercase());
+    }
+
+    return pattern == tag;
+}
+
+/// Fields of a header or trailer block such as `{3:{108:MUR}}`
+pub(crate) fn header_block_fields(raw: &str) -> Vec<Field> {
+    if raw.len() < 4 {
+        return Vec::new();
+    }
+
+    return MessageText::from_system_block(&raw[3..raw.len() - 1])
+        .map(|text| text.fields)
+        .unwrap_or_default();
+}
+
+fn split_field_start(line: &str) -> Option<(&str, &str)> {
+    let rest = line.strip_prefix(':')?;
+    let (tag, value) = rest.split_once(':')?;
+
+    let is_tag = tag.is_ascii()
+        && tag.len() >= 2
+        && tag.len() <= 3
+        && tag[..2].chars().all(|c| c.is_ascii_digit())
+        && tag[2..].chars().all(|c| c.is_ascii_uppercase());
+
+    return if is_tag { Some((tag, value)) } else { None };
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::swift::mt::text::{tag_matches, MessageText};
+
+    #[test]
+    fn positive_tests() {
+        let text = MessageText::from_raw(
+            ":20:REF123\r\n:32A:230102EUR1000,\r\n:59:/DE89370400440532013000\r\nJOHN DOE\r\nMAIN STREET 1\r\n:71A:SHA\r\n"
+                .to_string(),
+        )
+        .unwrap();
+
+        assert_eq!(text.fields.len(), 4);
+        assert_eq!(text.field("20").unwrap().value, "REF123");
+        assert_eq!(text.field("32A").unwrap().value, "230102EUR1000,");
+        assert_eq!(
+            text.field("59a").unwrap().lines(),
+            vec!["/DE89370400440532013000", "JOHN DOE", "MAIN STREET 1"]
+        );
+        assert_eq!(text.field("71A").unwrap().value, "SHA");
+        assert!(text.field("33B").is_none());
+
+        assert_eq!(
+            text.to_raw(),
+            "{4:\r\n:20:REF123\r\n:32A:230102EUR1000,\r\n:59:/DE89370400440532013000\r\nJOHN DOE\r\nMAIN STREET 1\r\n:71A:SHA\r\n-}"
+        );
+
+        assert!(MessageText::from_raw("".to_string())
+            .unwrap()
+            .fields
+            .is_empty());
+
+        let text = MessageText::from_system_block("{177:2301021530}{451:1}{405:T13}").unwrap();
+        assert_eq!(text.fields.len(), 3);
+        assert_eq!(text.field("451").unwrap().value, "1");
+        assert_eq!(text.field("405").unwrap().value, "T13");
+        assert_eq!(
+            text.to_system_block(),
+            "{4:{177:2301021530}{451:1}{405:T13}}"
+        );
+
+        assert!(tag_matches("50a", "50K"));
+        assert!(tag_matches("50a", "50"));
+        assert!(!tag_matches("50a", "51A"));
+        assert!(!tag_matches("50K", "50F"));
+    }
+
+    #[test]
+    fn negative_tests() {
+        assert!(MessageText::from_raw("REF123\r\n:20:REF\r\n".to_string()).is_err());
+        assert!(MessageText::from_system_block("{177}").is_err());
+
+        // not a tag, so a continuation line without a field to continue
+        assert!(MessageText::from_raw(":1é:X\r\n".to_string()).is_err());
+        let text = MessageText::from_raw(":20:REF\r\n:1é:X\r\n".to_string()).unwrap();
+        assert_eq!(text.field("20").unwrap().value, "REF\r\n:1é:X");
+    }
+}
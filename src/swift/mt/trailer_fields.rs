@@ -0,0 +1,194 @@
+use crate::swift::datetime::SwiftTime;
+use crate::swift::mt::header_fields::Mir;
+use crate::swift::mt::swift_mt_parser::SwiftMtMessage;
+use sha2::{Digest, Sha256};
+use std::fmt;
+use std::io;
+use std::io::ErrorKind::InvalidData;
+use std::str::FromStr;
+
+/// Content of the possible duplicate trailers PDE (`[4!n28!c]`, input time and MIR of the
+/// original message) and PDM (`[4!n28!c]`, output time and MOR of the original message)
+#[derive(Debug, Clone, PartialEq)]
+pub struct PossibleDuplicate {
+    pub time: SwiftTime,
+    pub reference: Mir,
+}
+
+impl PossibleDuplicate {
+    pub fn new(time: SwiftTime, reference: Mir) -> PossibleDuplicate {
+        return PossibleDuplicate { time, reference };
+    }
+
+    /// Parses the trailer value, which is either empty or the time followed by the reference
+    pub fn parse(value: &str) -> Result<Option<PossibleDuplicate>, io::Error> {
+        if value.is_empty() {
+            return Ok(None);
+        }
+
+        if value.len() != 32 || !value.is_ascii() {
+            return Err(io::Error::new(
+                InvalidData,
+                format!(
+                    "Possible duplicate trailer must be empty or have 32 characters: {}",
+                    value
+                ),
+            ));
+        }
+
+        return Ok(Some(PossibleDuplicate {
+            time: SwiftTime::parse_hhmm(&value[0..4])?,
+            reference: Mir::parse(&value[4..])?,
+        }));
+    }
+}
+
+impl FromStr for PossibleDuplicate {
+    type Err = io::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        return PossibleDuplicate::parse(s)?
+            .ok_or_else(|| io::Error::new(InvalidData, "Possible duplicate trailer is empty"));
+    }
+}
+
+impl fmt::Display for PossibleDuplicate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        return write!(f, "{}{}", self.time.to_hhmm(), self.reference);
+    }
+}
+
+/// Checksum of blocks 1 to 4 as 12 upper case hexadecimal characters.
+///
+/// The algorithm FIN uses for the CHK trailer is not published, so this is the first six
+/// bytes of the SHA-256 hash of the blocks in FIN format. It is stable and detects changes to
+/// the message, but it only matches checksums calculated by this crate, e.g. by a simulator,
+/// and not those calculated by SWIFT.
+pub fn compute_checksum(message: &SwiftMtMessage) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(message.basic_header.to_raw());
+    hasher.update(message.application_header.to_raw());
+    if !message.user_header.is_empty() {
+        hasher.update(message.user_header.to_raw());
+    }
+    hasher.update(message.text.to_raw());
+
+    return hasher.finalize()[0..6]
+        .iter()
+        .map(|b| format!("{:02X}", b))
+        .collect();
+}
+
+/// Sets the CHK trailer to the checksum of the message
+pub fn set_checksum(message: &mut SwiftMtMessage) {
+    message.trailer.chk = Some(compute_checksum(message));
+}
+
+/// Checks that the message has a CHK trailer matching its content
+pub fn verify_checksum(message: &SwiftMtMessage) -> Result<(), io::Error> {
+    let chk = message
+        .trailer
+        .chk
+        .as_deref()
+        .ok_or_else(|| io::Error::new(InvalidData, "Message has no CHK trailer"))?;
+
+    let expected = compute_checksum(message);
+    if chk != expected {
+        return Err(io::Error::new(
+            InvalidData,
+            format!("CHK {} does not match the checksum {}", chk, expected),
+        ));
+    }
+
+    return Ok(());
+}
+
+/// Marks the message as possible duplicate emission (PDE) by the sender, optionally naming
+/// the input time and MIR of the message sent before
+pub fn set_possible_duplicate_emission(
+    message: &mut SwiftMtMessage,
+    original: Option<&PossibleDuplicate>,
+) {
+    message.trailer.pde = Some(original.map(|o| o.to_string()).unwrap_or_default());
+}
+
+/// Marks the message as possible duplicate message (PDM) delivered before, optionally naming
+/// the output time and MOR of the earlier delivery
+pub fn set_possible_duplicate_message(
+    message: &mut SwiftMtMessage,
+    original: Option<&PossibleDuplicate>,
+) {
+    message.trailer.pdm = Some(original.map(|o| o.to_string()).unwrap_or_default());
+}
+
+/// Whether the message carries a PDE or PDM trailer
+pub fn is_possible_duplicate(message: &SwiftMtMessage) -> bool {
+    return message.trailer.pde.is_some() || message.trailer.pdm.is_some();
+}
+
+serde_as_string!(PossibleDuplicate);
+
+#[cfg(test)]
+mod tests {
+    use crate::swift::datetime::SwiftTime;
+    use crate::swift::mt::header_fields::Mir;
+    use crate::swift::mt::trailer_fields::{
+        compute_checksum, is_possible_duplicate, set_checksum, set_possible_duplicate_emission,
+        set_possible_duplicate_message, verify_checksum, PossibleDuplicate,
+    };
+    use crate::swift::mt::SwiftMtParser;
+
+    static MESSAGE: &str = "{1:F01FOOBARXXAXXX0123000456}{2:I103BANKDEFFXXXXN}{3:{108:MUR123}}{4:\r\n:20:REF123\r\n:23B:CRED\r\n:32A:230102EUR1000,\r\n-}";
+
+    #[test]
+    fn positive_tests() {
+        let mut message = SwiftMtParser::new().parse(MESSAGE.to_string()).unwrap();
+        let checksum = compute_checksum(&message);
+        assert_eq!(checksum.len(), 12);
+        assert!(checksum
+            .chars()
+            .all(|c| c.is_ascii_digit() || c.is_ascii_uppercase()));
+
+        set_checksum(&mut message);
+        assert!(verify_checksum(&message).is_ok());
+
+        let reparsed = SwiftMtParser::new().parse(message.to_raw()).unwrap();
+        assert!(verify_checksum(&reparsed).is_ok());
+        assert!(!is_possible_duplicate(&reparsed));
+
+        let original = PossibleDuplicate::new(
+            SwiftTime::parse_hhmm("1130").unwrap(),
+            Mir::parse("230102FOOBARXXAXXX0123000456").unwrap(),
+        );
+        set_possible_duplicate_emission(&mut message, Some(&original));
+        assert_eq!(
+            message.trailer.pde.as_deref(),
+            Some("1130230102FOOBARXXAXXX0123000456")
+        );
+        assert_eq!(
+            PossibleDuplicate::parse(message.trailer.pde.as_deref().unwrap()).unwrap(),
+            Some(original)
+        );
+        assert!(is_possible_duplicate(&message));
+        assert!(verify_checksum(&message).is_ok());
+
+        set_possible_duplicate_message(&mut message, None);
+        assert_eq!(message.trailer.pdm.as_deref(), Some(""));
+        assert!(message.to_raw().contains("{PDM:}"));
+        assert_eq!(PossibleDuplicate::parse("").unwrap(), None);
+    }
+
+    #[test]
+    fn negative_tests() {
+        let mut message = SwiftMtParser::new().parse(MESSAGE.to_string()).unwrap();
+        assert!(verify_checksum(&message).is_err());
+
+        set_checksum(&mut message);
+        message.text.fields[0].value = "REF124".to_string();
+        assert!(verify_checksum(&message).is_err());
+
+        assert!(PossibleDuplicate::parse("1130").is_err());
+        assert!(PossibleDuplicate::parse("2530230102FOOBARXXAXXX0123000456").is_err());
+        assert!("".parse::<PossibleDuplicate>().is_err());
+    }
+}
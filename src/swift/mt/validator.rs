@@ -0,0 +1,438 @@
+use crate::swift::bic::{Bic, LogicalTerminalAddress};
+use crate::swift::bic_directory::{BicDirectory, BicStatus};
+use crate::swift::charset::CharacterSet;
+use crate::swift::iban::Iban;
+use crate::swift::mt::fields::{account_line, AmountField, PartyField};
+use crate::swift::mt::model::ApplicationHeader;
+use crate::swift::mt::rules::RuleSet;
+use crate::swift::mt::swift_mt_parser::SwiftMtMessage;
+use chrono::NaiveDate;
+use chrono::Utc;
+use std::collections::HashMap;
+use std::fmt;
+
+/// Code of errors about characters outside of the character set of a field
+pub static CHARSET_ERROR: &str = "CHARSET";
+/// Code of errors about malformed BICs and logical terminal addresses
+pub static BIC_ERROR: &str = "BIC";
+/// Code of errors about invalid IBANs and IBANs not matching the country of the beneficiary bank
+pub static IBAN_ERROR: &str = "IBAN";
+/// Code of errors about malformed currency codes and amounts
+pub static AMOUNT_ERROR: &str = "AMOUNT";
+/// Code of errors about malformed header fields
+pub static HEADER_ERROR: &str = "HEADER";
+/// Code of errors about gpi confirmations and UETRs differing within a payment chain
+pub static GPI_ERROR: &str = "GPI";
+/// Network error code for amounts with more decimals than the currency allows
+pub static DECIMALS_ERROR: &str = "C03";
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ValidationError {
+    /// Network error code (e.g. D75) or one of the crate specific codes for checks the
+    /// network does not define a code for
+    pub code: String,
+    /// Tag of the offending field, if the error is about a single field
+    pub field: Option<String>,
+    pub message: String,
+}
+
+pub struct Validator {
+    rules: RuleSet,
+    character_set: CharacterSet,
+    field_character_sets: HashMap<String, CharacterSet>,
+    reject_test_bics: bool,
+    bic_directory: Option<Box<dyn BicDirectory>>,
+    reference_date: Option<NaiveDate>,
+}
+
+impl ValidationError {
+    pub fn new(code: &str, field: Option<&str>, message: String) -> ValidationError {
+        return ValidationError {
+            code: code.into(),
+            field: field.map(|f| f.into()),
+            message,
+        };
+    }
+}
+
+impl fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        return match &self.field {
+            Some(field) => write!(f, "{} (field {}): {}", self.code, field, self.message),
+            None => write!(f, "{}: {}", self.code, self.message),
+        };
+    }
+}
+
+impl Default for Validator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Validator {
+    /// Validator checking the network validated rules and the X character set for all fields
+    pub fn new() -> Validator {
+        return Validator {
+            rules: RuleSet::network_rules(),
+            character_set: CharacterSet::X,
+            field_character_sets: HashMap::new(),
+            reject_test_bics: false,
+            bic_directory: None,
+            reference_date: None,
+        };
+    }
+
+    pub fn with_rules(mut self, rules: RuleSet) -> Validator {
+        self.rules = rules;
+        return self;
+    }
+
+    /// Overrides the character set for a single field, e.g. Z for field 77E
+    pub fn with_field_character_set(mut self, tag: &str, character_set: CharacterSet) -> Validator {
+        self.field_character_sets.insert(tag.into(), character_set);
+        return self;
+    }
+
+    /// Reports test and training addresses in the headers, e.g. for production gateways
+    pub fn with_test_bics_rejected(mut self) -> Validator {
+        self.reject_test_bics = true;
+        return self;
+    }
+
+    /// Checks that the addresses of the message exist in the directory and are active
+    pub fn with_bic_directory(mut self, directory: Box<dyn BicDirectory>) -> Validator {
+        self.bic_directory = Some(directory);
+        return self;
+    }
+
+    /// Date used to check the expiry of BICs, defaults to the current date
+    pub fn with_reference_date(mut self, date: NaiveDate) -> Validator {
+        self.reference_date = Some(date);
+        return self;
+    }
+
+    pub fn validate(&self, message: &SwiftMtMessage) -> Vec<ValidationError> {
+        let mut errors = self.validate_addresses(message);
+        errors.extend(self.validate_user_header(message));
+        errors.extend(self.validate_parties(message));
+        errors.extend(self.validate_amounts(message));
+        errors.extend(self.validate_character_sets(message));
+
+        errors.extend(
+            self.rules.evaluate(message).into_iter().map(|violation| {
+                ValidationError::new(&violation.error_code, None, violation.message)
+            }),
+        );
+
+        return errors;
+    }
+
+    fn validate_addresses(&self, message: &SwiftMtMessage) -> Vec<ValidationError> {
+        let mut addresses: Vec<(&str, &LogicalTerminalAddress)> = Vec::new();
+        if !message.basic_header.logical_terminal.is_empty() {
+            addresses.push(("basic header", &message.basic_header.logical_terminal));
+        }
+
+        match &message.application_header {
+            ApplicationHeader::Input { data } => addresses.push(("destination", &data.destination)),
+            ApplicationHeader::Output { data } => {
+                addresses.push(("sender address", &data.sender_address))
+            }
+            ApplicationHeader::Empty => {}
+        }
+
+        let mut errors = Vec::new();
+        for (name, address) in addresses {
+            if let Err(e) = address.bic8().validate() {
+                errors.push(ValidationError::new(
+                    BIC_ERROR,
+                    None,
+                    format!("Invalid {} {}: {}", name, address, e),
+                ));
+            }
+
+            if self.reject_test_bics && address.bic8().is_test() {
+                errors.push(ValidationError::new(
+                    BIC_ERROR,
+                    None,
+                    format!("The {} {} is a test and training address", name, address),
+                ));
+            }
+
+            if let Some(problem) = self.check_directory(&address.bic11()) {
+                errors.push(ValidationError::new(
+                    BIC_ERROR,
+                    None,
+                    format!("The {} {} {}", name, address, problem),
+                ));
+            }
+        }
+
+        return errors;
+    }
+
+    fn validate_user_header(&self, message: &SwiftMtMessage) -> Vec<ValidationError> {
+        let user_header = &message.user_header;
+        let mut errors: Vec<ValidationError> = user_header
+            .field_errors()
+            .into_iter()
+            .map(|(tag, e)| ValidationError::new(HEADER_ERROR, Some(&tag), e.to_string()))
+            .collect();
+
+        if let Some(flag) = user_header
+            .validation_flag
+            .as_ref()
+            .filter(|f| !f.is_known())
+        {
+            errors.push(ValidationError::new(
+                HEADER_ERROR,
+                Some("119"),
+                format!("Unknown validation flag: {}", flag),
+            ));
+        }
+
+        return errors;
+    }
+
+    fn validate_parties(&self, message: &SwiftMtMessage) -> Vec<ValidationError> {
+        let mut errors = Vec::new();
+        let mut beneficiary_iban: Option<(String, String)> = None;
+        let mut beneficiary_bank_country: Option<String> = None;
+
+        for field in message.text.fields.iter() {
+            if !PartyField::is_party_field(&field.tag) {
+                continue;
+            }
+
+            let party = match PartyField::from_field(field) {
+                Ok(party) => party,
+                Err(e) => {
+                    let has_invalid_iban = account_line(field)
+                        .is_some_and(|a| Iban::looks_like_iban(&a) && Iban::parse(&a).is_err());
+                    let code = if has_invalid_iban {
+                        IBAN_ERROR
+                    } else {
+                        BIC_ERROR
+                    };
+                    errors.push(ValidationError::new(code, Some(&field.tag), e.to_string()));
+                    continue;
+                }
+            };
+
+            if let Some(bic) = &party.bic {
+                if let Some(problem) = self.check_directory(bic) {
+                    errors.push(ValidationError::new(
+                        BIC_ERROR,
+                        Some(&field.tag),
+                        format!("The BIC {} {}", bic, problem),
+                    ));
+                }
+            }
+
+            if field.tag.starts_with("59") {
+                if let Some(iban) = &party.iban {
+                    beneficiary_iban = Some((field.tag.clone(), iban.country_code().into()));
+                }
+            }
+
+            // the BIC of the beneficiary itself takes precedence over the account with institution
+            if field.tag == "59A"
+                || (field.tag.starts_with("57") && beneficiary_bank_country.is_none())
+            {
+                if let Some(bic) = &party.bic {
+                    beneficiary_bank_country = Some(bic.country_code().into());
+                }
+            }
+        }
+
+        if let (Some((tag, iban_country)), Some(bic_country)) =
+            (beneficiary_iban, beneficiary_bank_country)
+        {
+            if iban_country != bic_country {
+                errors.push(ValidationError::new(
+                    IBAN_ERROR,
+                    Some(&tag),
+                    format!(
+                        "Country {} of the beneficiary IBAN does not match country {} of the beneficiary bank",
+                        iban_country, bic_country
+                    ),
+                ));
+            }
+        }
+
+        return errors;
+    }
+
+    fn validate_amounts(&self, message: &SwiftMtMessage) -> Vec<ValidationError> {
+        let mut errors = Vec::new();
+
+        for field in message.text.fields.iter() {
+            if !AmountField::is_amount_field(&field.tag) {
+                continue;
+            }
+
+            match AmountField::from_field(field) {
+                Ok(amount) => {
+                    if let Err(e) = amount.check_decimals() {
+                        errors.push(ValidationError::new(
+                            DECIMALS_ERROR,
+                            Some(&field.tag),
+                            e.to_string(),
+                        ));
+                    }
+                }
+                Err(e) => errors.push(ValidationError::new(
+                    AMOUNT_ERROR,
+                    Some(&field.tag),
+                    e.to_string(),
+                )),
+            }
+        }
+
+        return errors;
+    }
+
+    fn check_directory(&self, bic: &Bic) -> Option<&'static str> {
+        let directory = self.bic_directory.as_ref()?;
+        let date = self
+            .reference_date
+            .unwrap_or_else(|| Utc::now().date_naive());
+
+        return match directory.status(bic, date) {
+            BicStatus::Active => None,
+            BicStatus::Unknown => Some("is not in the BIC directory"),
+            BicStatus::Expired => Some("has expired"),
+            BicStatus::NotConnected => Some("is not connected to the network"),
+        };
+    }
+
+    fn validate_character_sets(&self, message: &SwiftMtMessage) -> Vec<ValidationError> {
+        let mut errors = Vec::new();
+
+        for field in &message.text.fields {
+            let character_set = self
+                .field_character_sets
+                .get(&field.tag)
+                .unwrap_or(&self.character_set);
+
+            if let Err(invalid) = character_set.validate(&field.value) {
+                errors.extend(invalid.iter().map(|ic| {
+                    ValidationError::new(
+                        CHARSET_ERROR,
+                        Some(&field.tag),
+                        format!(
+                            "Character '{}' at position {} is not part of the {} character set",
+                            ic.character, ic.position, character_set
+                        ),
+                    )
+                }));
+            }
+        }
+
+        return errors;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::swift::bic_directory::FileBicDirectory;
+    use crate::swift::charset::CharacterSet;
+    use crate::swift::mt::validator::{
+        Validator, AMOUNT_ERROR, BIC_ERROR, CHARSET_ERROR, DECIMALS_ERROR, HEADER_ERROR, IBAN_ERROR,
+    };
+    use crate::swift::mt::SwiftMtParser;
+    use chrono::NaiveDate;
+
+    #[test]
+    fn positive_tests() {
+        let msg = "{1:F01FOOBARXXAXXX0000000000}{2:I103FOOBARXXAXXXN}{4:\r\n:20:REF\r\n:32A:230102EUR1000,\r\n:71A:SHA\r\n:77E:mail@example.com\r\n-}";
+        let message = SwiftMtParser::new().parse(msg.into()).unwrap();
+
+        let validator = Validator::new().with_field_character_set("77E", CharacterSet::Z);
+        assert!(validator.validate(&message).is_empty());
+
+        // option C of the account with institution is only an account
+        let msg = "{1:F01FOOBARXXAXXX0000000000}{2:I103FOOBARXXAXXXN}{4:\r\n:20:REF\r\n:32A:230102EUR1000,\r\n:57C://FW123456789\r\n:71A:SHA\r\n-}";
+        let message = SwiftMtParser::new().parse(msg.into()).unwrap();
+        assert!(Validator::new().validate(&message).is_empty());
+    }
+
+    #[test]
+    fn negative_tests() {
+        // malformed block 3 values do not fail the parse but are reported
+        let msg = "{1:F01FOOBARXXAXXX0000000000}{2:I103FOOBARXXAXXXN}{3:{119:XYZ}{121:NOT-A-UETR}{423:2301}{433:/BAD/}}{4:\r\n:20:REF\r\n:32A:230102EUR1000,\r\n:71A:SHA\r\n-}";
+        let message = SwiftMtParser::new().parse(msg.into()).unwrap();
+        for value in ["{119:XYZ}", "{121:NOT-A-UETR}", "{423:2301}", "{433:/BAD/}"] {
+            assert!(message.user_header.to_raw().contains(value));
+        }
+
+        let errors: Vec<_> = Validator::new()
+            .validate(&message)
+            .into_iter()
+            .filter(|e| e.code == HEADER_ERROR)
+            .collect();
+        let fields: Vec<&str> = errors.iter().filter_map(|e| e.field.as_deref()).collect();
+        assert_eq!(fields, vec!["121", "423", "433", "119"], "{:?}", errors);
+        assert!(errors[3].message.contains("Unknown validation flag: XYZ"));
+
+        let msg = "{1:F01FOOBARXXAXXX0000000000}{2:I103FOOBARXXAXXXN}{4:\r\n:20:RÉF\r\n:32A:230102EUR1000,\r\n:33B:USD1100,\r\n:71A:SHA\r\n-}";
+        let message = SwiftMtParser::new().parse(msg.into()).unwrap();
+
+        let errors = Validator::new().validate(&message);
+        assert_eq!(errors.len(), 2);
+        assert_eq!(errors[0].code, CHARSET_ERROR);
+        assert_eq!(errors[0].field.as_deref(), Some("20"));
+        assert_eq!(errors[1].code, "D75");
+        assert_eq!(errors[1].field, None);
+
+        let msg = "{1:F01FOOBXXXXAXXX0000000000}{2:I103FOOBARX0AXXXN}";
+        let message = SwiftMtParser::new().parse(msg.into()).unwrap();
+
+        let errors = Validator::new()
+            .with_test_bics_rejected()
+            .validate(&message);
+        assert_eq!(errors.len(), 2);
+        assert_eq!(errors[0].code, BIC_ERROR);
+        assert!(errors[0].message.contains("FOOBXXXXAXXX"));
+        assert_eq!(errors[1].code, BIC_ERROR);
+        assert!(errors[1].message.contains("test and training"));
+
+        let directory = FileBicDirectory::from_reader(
+            "BIC\tINSTITUTION NAME\tEXPIRY DATE\nFOOBARXXXXX\tFOO BANK\t20200101\n".as_bytes(),
+        )
+        .unwrap();
+        let msg = "{1:F01FOOBARXXAXXX0000000000}{2:I103BARBARXXAXXXN}";
+        let message = SwiftMtParser::new().parse(msg.into()).unwrap();
+
+        let errors = Validator::new()
+            .with_bic_directory(Box::new(directory))
+            .with_reference_date(NaiveDate::from_ymd_opt(2023, 1, 2).unwrap())
+            .validate(&message);
+        assert_eq!(errors.len(), 2);
+        assert!(errors[0].message.contains("has expired"));
+        assert!(errors[1].message.contains("is not in the BIC directory"));
+
+        let msg = "{1:F01FOOBARXXAXXX0000000000}{2:I103FOOBARXXAXXXN}{4:\r\n:20:REF\r\n:50K:/DE88370400440532013000\r\nJOHN DOE\r\n:57A:BNPAFRPP\r\n:59:/DE89370400440532013000\r\nJANE DOE\r\n-}";
+        let message = SwiftMtParser::new().parse(msg.into()).unwrap();
+
+        let errors = Validator::new().validate(&message);
+        assert_eq!(errors.len(), 2);
+        assert_eq!(errors[0].code, IBAN_ERROR);
+        assert_eq!(errors[0].field.as_deref(), Some("50K"));
+        assert_eq!(errors[1].code, IBAN_ERROR);
+        assert_eq!(errors[1].field.as_deref(), Some("59"));
+        assert!(errors[1].message.contains("country FR"));
+
+        let msg = "{1:F01FOOBARXXAXXX0000000000}{2:I103FOOBARXXAXXXN}{4:\r\n:20:REF\r\n:32A:230102JPY1000,50\r\n:71A:OUR\r\n:71G:EUR05,\r\n-}";
+        let message = SwiftMtParser::new().parse(msg.into()).unwrap();
+
+        let errors = Validator::new().validate(&message);
+        assert_eq!(errors.len(), 2);
+        assert_eq!(errors[0].code, DECIMALS_ERROR);
+        assert_eq!(errors[0].field.as_deref(), Some("32A"));
+        assert_eq!(errors[1].code, AMOUNT_ERROR);
+        assert_eq!(errors[1].field.as_deref(), Some("71G"));
+    }
+}
@@ -0,0 +1,85 @@
+use std::fmt;
+
+/// What a screenable value is, so the screening engine can apply the matching list and rules
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum EntityKind {
+    Name,
+    /// Address line, town or place of birth
+    Address,
+    Bic,
+    /// Country code of a party, e.g. from an option F address, a BIC or an IBAN
+    Country,
+    /// Line of a narrative field such as remittance information
+    FreeText,
+}
+
+/// Value of a message to be screened against sanctions lists, pointing back to where it was
+/// taken from
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ScreeningEntity {
+    pub kind: EntityKind,
+    /// Value in upper case with whitespace collapsed
+    pub value: String,
+    /// Tag of the field or name of the header element, e.g. `59F` or `BIC`
+    pub field: String,
+    /// Sequence of the field like `B[2]` or `GENL[1]/LINK[2]`, empty outside of sequences
+    pub sequence: String,
+    /// Location of the field in the message, a `MessagePath` for MT messages
+    pub path: String,
+    /// Line of the field the value was taken from, starting at 1
+    pub line: Option<usize>,
+}
+
+/// Messages whose parties and free text can be extracted for sanctions screening
+pub trait Screenable {
+    /// Every name, address, BIC, country and free text line, in the order of the message
+    fn screening_entities(&self) -> Vec<ScreeningEntity>;
+}
+
+impl ScreeningEntity {
+    /// Entity with the value normalised, or None if the value is blank
+    pub fn new(
+        kind: EntityKind,
+        value: &str,
+        field: &str,
+        sequence: &str,
+        path: &str,
+        line: Option<usize>,
+    ) -> Option<ScreeningEntity> {
+        let value = normalize(value);
+        if value.is_empty() {
+            return None;
+        }
+
+        return Some(ScreeningEntity {
+            kind,
+            value,
+            field: field.into(),
+            sequence: sequence.into(),
+            path: path.into(),
+            line,
+        });
+    }
+}
+
+impl fmt::Display for EntityKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        return write!(f, "{:?}", self);
+    }
+}
+
+impl fmt::Display for ScreeningEntity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        return write!(f, "{} {}: {}", self.path, self.kind, self.value);
+    }
+}
+
+pub(crate) fn normalize(value: &str) -> String {
+    return value
+        .split_whitespace()
+        .collect::<Vec<&str>>()
+        .join(" ")
+        .to_uppercase();
+}
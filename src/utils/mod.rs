@@ -1,3 +1,25 @@
+#[allow(clippy::io_other_error, clippy::bool_assert_comparison)]
 mod string_parser;
 
-pub use string_parser::StringParser;
\ No newline at end of file
+pub use string_parser::StringParser;
+
+/// Implements `Serialize` and `Deserialize` for a type through its `Display` and `FromStr`
+/// implementations, for values with a textual SWIFT representation such as BICs and amounts
+macro_rules! serde_as_string {
+    ($type:ty) => {
+        #[cfg(feature = "serde")]
+        impl serde::Serialize for $type {
+            fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                return serializer.collect_str(self);
+            }
+        }
+
+        #[cfg(feature = "serde")]
+        impl<'de> serde::Deserialize<'de> for $type {
+            fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                let value = <String as serde::Deserialize>::deserialize(deserializer)?;
+                return value.parse().map_err(serde::de::Error::custom);
+            }
+        }
+    };
+}
\ No newline at end of file
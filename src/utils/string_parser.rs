@@ -53,6 +53,13 @@ impl StringParser {
         self.position = position;
     }
 
+    /// Returns the remaining content and moves to the end
+    pub fn rest(&mut self) -> String {
+        let rest = self.data[self.position..].iter().collect();
+        self.position = self.data.len();
+        return rest;
+    }
+
     pub fn peek_line(&mut self) -> Result<String, io::Error> {
         let old_position = self.position;
         let maybe_line = self.next_line();
@@ -0,0 +1,131 @@
+use std::env;
+use std::fs;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::TcpStream;
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command, Stdio};
+use std::thread;
+use std::time::{Duration, Instant};
+
+static MESSAGE: &str = "{1:F01FOOBARXXAXXX0000000000}{2:I103BANKDEFFXXXXN}{4:\r\n:20:REF123\r\n:23B:CRED\r\n:32A:230102EUR1000,\r\n:50K:/12345678\r\nJOHN DOE\r\n:59:/DE89370400440532013000\r\nJANE DOE\r\n:71A:SHA\r\n-}";
+
+/// The simulator listening on a free port, killed when the test ends
+struct Simulator {
+    child: Child,
+    address: String,
+}
+
+impl Simulator {
+    fn start(directory: &Path) -> Simulator {
+        let mut child = Command::new(env!("CARGO_BIN_EXE_fin-simulator"))
+            .args(["--listen", "127.0.0.1:0", "--poll-interval", "20", "--drop-dir"])
+            .arg(directory.join("drop"))
+            .arg("--output-dir")
+            .arg(directory.join("output"))
+            .stderr(Stdio::piped())
+            .spawn()
+            .unwrap();
+
+        let mut stderr = BufReader::new(child.stderr.take().unwrap());
+        let mut line = String::new();
+        stderr.read_line(&mut line).unwrap();
+        let address = line.trim().strip_prefix("Listening on ").unwrap().to_string();
+        // keeps the pipe open for the messages about rejected files
+        thread::spawn(move || {
+            let _ = std::io::copy(&mut stderr, &mut std::io::sink());
+        });
+
+        return Simulator { child, address };
+    }
+}
+
+impl Drop for Simulator {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
+
+fn directory(name: &str) -> PathBuf {
+    let directory = env::temp_dir().join(format!("rustmsg-{}-{}", name, std::process::id()));
+    let _ = fs::remove_dir_all(&directory);
+    fs::create_dir_all(directory.join("drop")).unwrap();
+    return directory;
+}
+
+/// Reads up to the `$` ending an acknowledgement
+fn read_acknowledgement(stream: &mut TcpStream) -> String {
+    let mut acknowledgement = Vec::new();
+    let mut byte = [0u8; 1];
+    while stream.read(&mut byte).unwrap() == 1 && byte[0] != b'$' {
+        acknowledgement.push(byte[0]);
+    }
+
+    return String::from_utf8(acknowledgement).unwrap();
+}
+
+fn wait_for(path: &Path) -> bool {
+    let start = Instant::now();
+    while !path.exists() {
+        if start.elapsed() > Duration::from_secs(10) {
+            return false;
+        }
+        thread::sleep(Duration::from_millis(20));
+    }
+
+    return true;
+}
+
+#[test]
+fn positive_tests() {
+    let directory = directory("simulator");
+    let simulator = Simulator::start(&directory);
+
+    // the connection stays open while the acknowledgements are awaited
+    let mut stream = TcpStream::connect(&simulator.address).unwrap();
+    stream
+        .set_read_timeout(Some(Duration::from_secs(10)))
+        .unwrap();
+    for sequence in ["000001", "000002"] {
+        stream
+            .write_all(format!("{}\r\n$", MESSAGE).as_bytes())
+            .unwrap();
+        let acknowledgement = read_acknowledgement(&mut stream);
+        assert!(acknowledgement.starts_with(&format!("{{1:F21FOOBARXXAXXX0001{}}}", sequence)));
+        assert!(acknowledgement.contains("{451:0}"));
+    }
+
+    fs::write(directory.join("drop").join("batch.fin"), MESSAGE).unwrap();
+    assert!(wait_for(&directory.join("drop").join("batch.fin.done")));
+    let acknowledgement = fs::read_to_string(directory.join("drop").join("batch.ack")).unwrap();
+    assert!(acknowledgement.contains("{451:0}"));
+
+    drop(simulator);
+    fs::remove_dir_all(&directory).unwrap();
+}
+
+#[test]
+fn negative_tests() {
+    let directory = directory("simulator-bad");
+    let simulator = Simulator::start(&directory);
+
+    // unreadable files are set aside and later files are still processed
+    let drop_dir = directory.join("drop");
+    fs::write(drop_dir.join("a.fin"), [b'{', b'1', b':', 0xff, b'}']).unwrap();
+    fs::write(drop_dir.join("b.fin"), MESSAGE).unwrap();
+    assert!(wait_for(&drop_dir.join("b.fin.done")));
+    assert!(drop_dir.join("a.fin.rejected").exists());
+    assert!(!drop_dir.join("a.ack").exists());
+
+    let mut stream = TcpStream::connect(&simulator.address).unwrap();
+    stream
+        .set_read_timeout(Some(Duration::from_secs(10)))
+        .unwrap();
+    stream
+        .write_all(format!("{}{}\r\n$", MESSAGE, MESSAGE).as_bytes())
+        .unwrap();
+    assert!(read_acknowledgement(&mut stream).contains("{451:1}"));
+
+    drop(simulator);
+    fs::remove_dir_all(&directory).unwrap();
+}